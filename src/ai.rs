@@ -0,0 +1,299 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use rand::prelude::*;
+use crate::board::{GameBoard, MoveableComponent};
+use crate::fog::Fog;
+use crate::game::Game;
+use crate::rules::Rules;
+use crate::spell::{check_target, ASpell, SpellBox, SpellTarget};
+use crate::system::{BelongsToPlayer, BoardEntity, CanAttack, CanDefend};
+use crate::vec::Vec2I;
+
+// Computer wizards. ai_level runs from 0, which does anything it is allowed to, up to 9.
+// From 3 they summon the strongest creatures they have and head for the enemy wizards, from 5
+// they keep their own wizard out of fights it is likely to lose, and from 7 their creatures only
+// go for fights they are likely to win unless there's a wizard to be had.
+// The menu and the campaign only offer 1 to 8. 0 and 9 are for tournaments and challenge files,
+// as a baseline that wanders at random and a top level that plays the same as 8.
+const AIM: u8 = 3;
+const CAREFUL: u8 = 5;
+const PATIENT: u8 = 7;
+
+// Long enough to follow what a computer wizard is doing
+const THINK_TIME: f32 = 0.6;
+
+// Whose turn it is decides whether the keyboard or the computer plays it
+pub fn computer_turn(g: Res<Game>) -> bool {
+    g.player_info.get(g.player_turn as usize).is_some_and(|p| p.computer_controlled)
+}
+
+pub fn human_turn(g: Res<Game>) -> bool {
+    !computer_turn(g)
+}
+
+// True once a computer wizard has thought for long enough, and it starts thinking afresh next time
+pub fn thought(wait: &mut Option<Timer>, time: &Time) -> bool {
+    let timer = wait.get_or_insert_with(|| Timer::from_seconds(THINK_TIME, TimerMode::Once));
+    if timer.tick(time.delta()).finished() {
+        *wait = None;
+        return true;
+    }
+    false
+}
+
+// Chance of an attack with this combat beating this defence, when each has 1 up to die added
+pub fn win_chance(combat: u8, defence: u8, die: u8) -> f32 {
+    let wins = (1..=die).flat_map(|a| (1..=die).map(move |d| (a, d)))
+        .filter(|(a, d)| combat + a >= defence + d)
+        .count();
+    wins as f32 / f32::from(die).powi(2)
+}
+
+// How much a piece is worth having on the board
+fn strength(combat: u8, defence: u8, movement: u8, flying: bool) -> f32 {
+    let mut value = f32::from(combat + defence) + f32::from(movement) / 2.0;
+    if flying {
+        value += 2.0;
+    }
+    value
+}
+
+// What the wizard whose turn it is can see of a piece on the board
+#[derive(Clone, Debug)]
+pub struct Piece {
+    pub enemy: bool,
+    pub wizard: bool,
+    // None for pieces which can't attack, or can't be attacked
+    pub combat: Option<u8>,
+    pub defence: Option<u8>,
+    pub movement: u8,
+    pub flying: bool,
+}
+
+impl Piece {
+    fn strength(&self) -> f32 {
+        strength(self.combat.unwrap_or(0), self.defence.unwrap_or(0), self.movement, self.flying)
+    }
+}
+
+pub type PieceQuery<'w, 's> = Query<'w, 's, (
+    Entity,
+    &'static MoveableComponent,
+    Option<&'static CanAttack>,
+    Option<&'static CanDefend>,
+    Option<&'static BelongsToPlayer>,
+), With<BoardEntity>>;
+
+// The board as the computer wizard whose turn it is sees it. Enemies in the fog are left out,
+// the same as they are for a person.
+pub struct View<'a> {
+    board: &'a GameBoard,
+    pieces: HashMap<Entity, Piece>,
+    level: u8,
+    rules: Rules,
+}
+
+impl<'a> View<'a> {
+    pub fn new(g: &Game, board: &'a GameBoard, fog: &Fog, pieces: &PieceQuery) -> Self {
+        let player = g.get_player();
+        let me = player.handle.unwrap();
+        let pieces = pieces.iter().filter_map(|(e, moveable, attack, defend, belongs)| {
+            let pos = board.entity_pos(e)?;
+            let enemy = !g.allied(me, belongs.map_or(e, |b| b.player_entity));
+            if enemy && fog.hides(pos) {
+                return None;
+            }
+            Some((e, Piece {
+                enemy,
+                wizard: g.player_info.iter().any(|p| p.handle == Some(e)),
+                combat: attack.map(|a| a.combat),
+                defence: defend.map(|d| d.defence),
+                movement: moveable.movement,
+                flying: moveable.flying,
+            }))
+        }).collect();
+        Self { board, pieces, level: player.ai_level, rules: g.rules }
+    }
+    fn piece_at(&self, pos: Vec2I) -> Option<&Piece> {
+        self.board.get_entity(Vec2::from(pos)).and_then(|e| self.pieces.get(&e))
+    }
+    // The nearest of the enemy pieces, or only their wizards
+    fn nearest_enemy(&self, from: Vec2I, wizards_only: bool) -> Option<Vec2I> {
+        self.pieces.iter()
+            .filter(|(_, p)| p.enemy && (p.wizard || !wizards_only))
+            .map(|(e, _)| (self.board.get_entity_pos(*e), e.index()))
+            .min_by_key(|(pos, index)| (from.distance(*pos), *index))
+            .map(|(pos, _)| pos)
+    }
+}
+
+// Which spell to cast this round, None to keep them all. Disbelieve is never worth it.
+pub fn choose_spell(spells: &[SpellBox], level: u8, rng: &mut impl Rng) -> Option<usize> {
+    if spells.len() < 2 {
+        return None;
+    }
+    if level < AIM {
+        return Some(rng.gen_range(1..spells.len()));
+    }
+    // Creatures first, the strongest; anything else only once they have run out
+    let value = |i: usize| spells[i].creature().map_or(0.0, |c| strength(c.combat, c.defence, c.movement, c.flying));
+    (1..spells.len()).max_by(|a, b| value(*a).total_cmp(&value(*b)).then(b.cmp(a)))
+}
+
+// A square the spell can be cast at. Spells cast at creatures go at an enemy piece, the most
+// worth having gone once they're aiming. The rest go on an empty square, as close to an enemy
+// wizard as it can be once they're aiming. Squares in the fog look empty, as they do to a person.
+pub fn choose_target(view: &View, spell: &dyn ASpell, from: Vec2I, rng: &mut impl Rng) -> Option<Vec2I> {
+    let squares: Vec<Vec2I> = GameBoard::squares()
+        .filter(|pos| check_target(spell, from, *pos, |p| view.piece_at(p).is_some()).is_ok())
+        .collect();
+    if spell.target() == SpellTarget::Creature {
+        let enemies: Vec<(Vec2I, &Piece)> = squares.into_iter()
+            .filter_map(|pos| view.piece_at(pos).filter(|p| p.enemy).map(|p| (pos, p)))
+            .collect();
+        if view.level < AIM {
+            return enemies.choose(rng).map(|(pos, _)| *pos);
+        }
+        let worth = |p: &Piece| if p.wizard { 100.0 } else { p.strength() };
+        return enemies.iter()
+            .max_by(|(a, pa), (b, pb)| worth(pa).total_cmp(&worth(pb)).then((b.x, b.y).cmp(&(a.x, a.y))))
+            .map(|(pos, _)| *pos);
+    }
+    match view.nearest_enemy(from, true) {
+        Some(enemy) if view.level >= AIM => squares.into_iter().min_by_key(|pos| (pos.distance(enemy), pos.x, pos.y)),
+        _ => squares.choose(rng).copied(),
+    }
+}
+
+// What a piece chose to do with its move
+#[derive(Debug, PartialEq, Eq)]
+pub enum Move {
+    To(Vec2I),
+    // Attack the piece on target after getting next to it at from
+    Attack { from: Vec2I, target: Vec2I },
+}
+
+pub fn choose_move(view: &View, e: Entity, rng: &mut impl Rng) -> Option<Move> {
+    let piece = view.pieces.get(&e)?;
+    let level = view.level;
+    let from = view.board.get_entity_pos(e);
+    let can_attack = piece.combat.is_some() && (view.rules.wizards_attack || !piece.wizard);
+    let reachable = view.board.reachable(from, from, piece.movement, piece.flying, |other| {
+        can_attack && view.pieces.get(&other).is_some_and(|p| p.enemy && p.defence.is_some())
+    });
+    // Sure of winning, or going for a wizard, is worth more than anything else
+    let attack = if level >= AIM {
+        reachable.attacks.iter().map(|target| {
+            let defender = view.piece_at(*target).unwrap();
+            let chance = win_chance(piece.combat.unwrap_or(0), defender.defence.unwrap_or(0), view.rules.combat_die);
+            let value = if defender.wizard { 100.0 } else { defender.strength() };
+            (*target, chance, chance * value, defender.wizard)
+        }).max_by(|a, b| a.2.total_cmp(&b.2))
+    } else {
+        reachable.attacks.choose(rng).map(|target| (*target, 1.0, 0.0, false))
+    };
+    if let Some((target, chance, _, wizard)) = attack {
+        let wary = (piece.wizard && level >= CAREFUL) || (!wizard && level >= PATIENT);
+        if !(wary && chance < 0.5) {
+            // Ground pieces walk up to their target with a step to spare, flying ones swoop in
+            // from where they are
+            let next_to = if piece.flying {
+                Some(from)
+            } else {
+                std::iter::once(from)
+                    .chain(reachable.moves.iter().copied().filter(|pos| from.distance(*pos) < piece.movement))
+                    .find(|pos| pos.distance(target) == 1)
+            };
+            if let Some(next_to) = next_to {
+                return Some(Move::Attack { from: next_to, target });
+            }
+        }
+    }
+    if piece.wizard && level >= CAREFUL {
+        return None;
+    }
+    let goal = match level {
+        0 => return reachable.moves.choose(rng).map(|pos| Move::To(*pos)),
+        1..=2 => view.nearest_enemy(from, false),
+        _ => view.nearest_enemy(from, true),
+    }?;
+    let best = reachable.moves.iter().min_by_key(|pos| (pos.distance(goal), pos.x, pos.y))?;
+    (best.distance(goal) < from.distance(goal)).then_some(Move::To(*best))
+}
+
+// The nearest enemy in range to shoot at
+pub fn choose_ranged_target(view: &View, e: Entity, range: u8) -> Option<Vec2I> {
+    let from = view.board.get_entity_pos(e);
+    view.pieces.iter()
+        .filter(|(_, p)| p.enemy && p.defence.is_some())
+        .map(|(other, _)| view.board.get_entity_pos(*other))
+        .filter(|pos| from.distance(*pos) <= range)
+        .min_by_key(|pos| (from.distance(*pos), pos.x, pos.y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spell::load_all_spells;
+
+    #[test]
+    fn test_win_chance() {
+        assert!((win_chance(1, 1, 9) - 45.0 / 81.0).abs() < 1e-6);
+        assert!((win_chance(9, 1, 9) - 1.0).abs() < 1e-6);
+        assert!(win_chance(6, 4, 9) > win_chance(4, 6, 9));
+        assert!(win_chance(6, 4, 6) > win_chance(6, 4, 9));
+    }
+
+    #[test]
+    fn test_choose_move() {
+        let mut board = GameBoard::new();
+        let (wizard, enemy, lion) = (Entity::from_raw(0), Entity::from_raw(1), Entity::from_raw(2));
+        board.put_entity(Vec2::new(1.0, 5.0), wizard);
+        board.put_entity(Vec2::new(6.0, 5.0), enemy);
+        board.put_entity(Vec2::new(3.0, 5.0), lion);
+        let piece = |enemy, wizard, movement| Piece {
+            enemy, wizard, combat: Some(6), defence: Some(4), movement, flying: false,
+        };
+        let mut view = View {
+            board: &board,
+            pieces: HashMap::from_iter([
+                (wizard, piece(false, true, 1)),
+                (enemy, piece(true, true, 1)),
+                (lion, piece(false, false, 4)),
+            ]),
+            level: CAREFUL,
+            rules: Rules::default(),
+        };
+        let mut rng = StdRng::seed_from_u64(1);
+        // Steps up to the enemy wizard with a step to spare and attacks
+        assert_eq!(choose_move(&view, lion, &mut rng), Some(Move::Attack { from: Vec2I::new(5, 5), target: Vec2I::new(6, 5) }));
+        // A careful wizard stays where it is
+        assert_eq!(choose_move(&view, wizard, &mut rng), None);
+        view.level = AIM;
+        assert!(matches!(choose_move(&view, wizard, &mut rng), Some(Move::To(to)) if to.x == 2));
+    }
+
+    #[test]
+    fn test_choose_target_fog() {
+        let mut board = GameBoard::new();
+        let (wizard, enemy, hidden) = (Entity::from_raw(0), Entity::from_raw(1), Entity::from_raw(2));
+        board.put_entity(Vec2::new(6.0, 0.0), wizard);
+        board.put_entity(Vec2::new(8.0, 0.0), enemy);
+        // In the fog, so left out of the pieces the computer wizard knows about
+        board.put_entity(Vec2::new(7.0, 0.0), hidden);
+        let piece = |enemy| Piece {
+            enemy, wizard: true, combat: Some(1), defence: Some(1), movement: 1, flying: false,
+        };
+        let view = View {
+            board: &board,
+            pieces: HashMap::from_iter([(wizard, piece(false)), (enemy, piece(true))]),
+            level: AIM,
+            rules: Rules::default(),
+        };
+        let spells = load_all_spells();
+        let spell = spells.iter().find(|s| s.target() == SpellTarget::EmptySquare && s.cast_range() > 0).unwrap();
+        let mut rng = StdRng::seed_from_u64(1);
+        // The best square next to the enemy wizard is the hidden one, and it looks empty
+        assert_eq!(choose_target(&view, spell.as_ref(), Vec2I::new(6, 0), &mut rng), Some(Vec2I::new(7, 0)));
+    }
+}
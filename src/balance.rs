@@ -0,0 +1,280 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+use std::fmt::Write as _;
+use crate::board::{combat_rolls, GameBoard};
+use crate::rules::CLASSIC;
+use crate::creature::{load_creatures, Creature};
+use crate::player::get_start_positions;
+use crate::tournament::parse_number;
+
+// Every creature fights every other one on an empty board, to show which are worth more or less
+// than their casting chance says:
+//   mayhem-rs balance --trials 500 --seed 3 --csv balance.csv
+// The two start where two wizards would and take turns, moving and attacking as pieces do in
+// the game, until one is killed or they run out of turns. Creatures that can neither move nor
+// shoot, Gooey Blob and Magic Fire, never attack and are left out.
+const USAGE: &str = "usage: mayhem-rs balance [--trials N] [--seed N] [--max-turns N] [--csv FILE]";
+// How far from the average value a creature has to be to be called out, in standard deviations
+const OUTLIER: f32 = 1.5;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    // Fights for each pair, half with each going first
+    pub trials: u32,
+    pub seed: u64,
+    pub max_turns: u32,
+    pub csv: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self { trials: 200, seed: 0, max_turns: 30, csv: None }
+    }
+}
+
+impl Config {
+    // None if the first argument isn't "balance"
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Option<Result<Self, String>> {
+        if args.next().as_deref() != Some("balance") {
+            return None;
+        }
+        Some(Self::parse(args).map_err(|e| format!("{e}\n{USAGE}")))
+    }
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut config = Self::default();
+        while let Some(arg) = args.next() {
+            let value = args.next().ok_or_else(|| format!("{arg} needs a value"))?;
+            match arg.as_str() {
+                "--trials" => config.trials = parse_number(&value)?,
+                "--seed" => config.seed = parse_number(&value)?,
+                "--max-turns" => config.max_turns = parse_number(&value)?,
+                "--csv" => config.csv = Some(value),
+                _ => return Err(format!("unknown option {arg}")),
+            }
+        }
+        Ok(config)
+    }
+}
+
+// The side that killed the other, or None if both were still standing after max_turns each
+pub fn duel(fighters: [&Creature; 2], first: usize, max_turns: u32, rng: &mut impl Rng) -> Option<usize> {
+    let mut board = GameBoard::new();
+    let pieces = [Entity::from_raw(0), Entity::from_raw(1)];
+    for (e, pos) in pieces.iter().zip(get_start_positions(2).unwrap()) {
+        board.put_entity(pos, *e);
+    }
+    for turn in 0..max_turns * 2 {
+        let side = (first + turn as usize) % 2;
+        let (me, enemy) = (pieces[side], pieces[1 - side]);
+        let creature = fighters[side];
+        let from = board.get_entity_pos(me);
+        let target = board.get_entity_pos(enemy);
+        let reachable = board.reachable(from, from, creature.movement, creature.flying, |e| e == enemy);
+        let step = if reachable.attacks.is_empty() {
+            reachable.moves.iter().copied().filter(|pos| pos.distance(target) < from.distance(target))
+                .min_by_key(|pos| (pos.distance(target), pos.x, pos.y))
+        } else if creature.flying {
+            None
+        } else {
+            // Walks up next to the enemy first
+            std::iter::once(from).chain(reachable.moves.iter().copied()).find(|pos| pos.distance(target) == 1)
+        };
+        if let Some(to) = step {
+            board.take_entity(me);
+            board.put_entity(Vec2::from(to), me);
+        }
+        // Those that didn't fight hand to hand shoot, ranged combat against defence, if the
+        // other is in range of where they ended up
+        let combat = if !reachable.attacks.is_empty() {
+            creature.combat
+        } else if creature.ranged_combat > 0 && board.get_entity_pos(me).distance(target) <= creature.range {
+            creature.ranged_combat
+        } else {
+            continue;
+        };
+        let (attack, def) = combat_rolls(combat, fighters[1 - side].defence, CLASSIC.combat_die, rng);
+        if attack >= def {
+            return Some(side);
+        }
+    }
+    None
+}
+
+// Anything that can neither move nor shoot can't win a fight, so is no use comparing
+const fn can_attack(creature: &Creature) -> bool {
+    creature.movement > 0 || creature.ranged_combat > 0
+}
+
+#[derive(Clone, Debug, Default)]
+struct Record {
+    wins: u32,
+    losses: u32,
+    draws: u32,
+}
+
+impl Record {
+    fn fights(&self) -> u32 {
+        self.wins + self.losses + self.draws
+    }
+    fn win_rate(&self) -> f32 {
+        self.wins as f32 / self.fights().max(1) as f32
+    }
+}
+
+struct Row<'a> {
+    creature: &'a Creature,
+    record: Record,
+    // Fights won for each attempt at casting it, the win rate scaled by the casting chance
+    value: f32,
+    outlier: Option<&'static str>,
+}
+
+// Those well above the rest are too strong for how easy they are to cast, and the other way round
+fn outliers(values: &[f32]) -> Vec<Option<&'static str>> {
+    let n = values.len().max(1) as f32;
+    let mean = values.iter().sum::<f32>() / n;
+    let sd = (values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / n).sqrt();
+    values.iter().map(|v| {
+        if sd > 0.0 && *v > OUTLIER.mul_add(sd, mean) {
+            Some("strong")
+        } else if sd > 0.0 && *v < (-OUTLIER).mul_add(sd, mean) {
+            Some("weak")
+        } else {
+            None
+        }
+    }).collect()
+}
+
+// How closely two lists go up and down together, from -1 to 1
+fn correlation(xs: &[f32], ys: &[f32]) -> f32 {
+    let n = xs.len() as f32;
+    let (mx, my) = (xs.iter().sum::<f32>() / n, ys.iter().sum::<f32>() / n);
+    let cov: f32 = xs.iter().zip(ys).map(|(x, y)| (x - mx) * (y - my)).sum();
+    let sx = xs.iter().map(|x| (x - mx).powi(2)).sum::<f32>().sqrt();
+    let sy = ys.iter().map(|y| (y - my).powi(2)).sum::<f32>().sqrt();
+    if sx == 0.0 || sy == 0.0 { 0.0 } else { cov / (sx * sy) }
+}
+
+fn simulate<'a>(creatures: &'a [Creature], config: &Config) -> Vec<Row<'a>> {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut records = vec![Record::default(); creatures.len()];
+    for a in 0..creatures.len() {
+        for b in a + 1..creatures.len() {
+            for trial in 0..config.trials {
+                let result = duel([&creatures[a], &creatures[b]], trial as usize % 2, config.max_turns, &mut rng);
+                match result {
+                    Some(0) => {
+                        records[a].wins += 1;
+                        records[b].losses += 1;
+                    }
+                    Some(_) => {
+                        records[b].wins += 1;
+                        records[a].losses += 1;
+                    }
+                    None => {
+                        records[a].draws += 1;
+                        records[b].draws += 1;
+                    }
+                }
+            }
+        }
+    }
+    let values: Vec<f32> = creatures.iter().zip(&records)
+        .map(|(c, r)| r.win_rate() * f32::from(c.casting_chance) / 100.0)
+        .collect();
+    let mut rows: Vec<Row> = creatures.iter().zip(records).zip(outliers(&values)).zip(&values)
+        .map(|(((creature, record), outlier), value)| Row { creature, record, value: *value, outlier })
+        .collect();
+    rows.sort_by(|a, b| b.value.total_cmp(&a.value));
+    rows
+}
+
+fn report(rows: &[Row], left_out: &[&str]) -> String {
+    let mut text = "creature         cast  C  R rng  D  M fly str  win%  draw%  value\n".to_string();
+    for row in rows {
+        let c = row.creature;
+        let fights = row.record.fights().max(1) as f32;
+        let _ = writeln!(text, "{:16} {:3}% {:2} {:2} {:3} {:2} {:2} {:3} {:3} {:5.1} {:6.1}  {:.3} {}",
+            c.name, c.casting_chance, c.combat, c.ranged_combat, c.range, c.defence, c.movement, if c.flying { "yes" } else { "no" }, c.strength,
+            row.record.win_rate() * 100.0, row.record.draws as f32 / fights * 100.0, row.value, row.outlier.unwrap_or(""));
+    }
+    let strength: Vec<f32> = rows.iter().map(|r| f32::from(r.creature.strength)).collect();
+    let wins: Vec<f32> = rows.iter().map(|r| r.record.win_rate()).collect();
+    let _ = writeln!(text, "value is win rate times casting chance; strength field against win rate: {:.2}", correlation(&strength, &wins));
+    if !left_out.is_empty() {
+        let _ = writeln!(text, "left out, as they can neither move nor shoot: {}", left_out.join(", "));
+    }
+    text
+}
+
+fn csv(rows: &[Row]) -> String {
+    let mut text = "creature,casting_chance,combat,ranged_combat,range,defence,movement,flying,strength,wins,losses,draws,win_rate,value,outlier\n".to_string();
+    for row in rows {
+        let c = row.creature;
+        let r = &row.record;
+        let _ = writeln!(text, "{},{},{},{},{},{},{},{},{},{},{},{},{:.4},{:.4},{}",
+            c.name, c.casting_chance, c.combat, c.ranged_combat, c.range, c.defence, c.movement, c.flying, c.strength,
+            r.wins, r.losses, r.draws, r.win_rate(), row.value, row.outlier.unwrap_or(""));
+    }
+    text
+}
+
+pub fn run(config: &Config) {
+    let (mut creatures, mut left_out): (Vec<Creature>, Vec<Creature>) = load_creatures().into_values().partition(can_attack);
+    creatures.sort_by(|a, b| a.name.cmp(&b.name));
+    left_out.sort_by(|a, b| a.name.cmp(&b.name));
+    let rows = simulate(&creatures, config);
+    let left_out: Vec<&str> = left_out.iter().map(|c| c.name.as_str()).collect();
+    print!("{}", report(&rows, &left_out));
+    if let Some(path) = &config.csv {
+        if let Err(e) = std::fs::write(path, csv(&rows)) {
+            eprintln!("Could not write {path}: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn creature(name: &str) -> Creature {
+        load_creatures().remove(name).unwrap()
+    }
+
+    #[test]
+    fn test_duel() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let dragon = creature("Golden Dragon");
+        let blob = creature("Gooey Blob");
+        // Combat 9 always beats defence 0, and a blob can't move to get away
+        for first in 0..2 {
+            assert_eq!(duel([&blob, &dragon], first, 30, &mut rng), Some(1));
+        }
+        // Neither can move or hurt the other
+        let fire = creature("Magic Fire");
+        assert_eq!(duel([&blob, &fire], 0, 30, &mut rng), None);
+        assert!(!can_attack(&blob) && !can_attack(&fire));
+    }
+
+    #[test]
+    fn test_duel_ranged() {
+        let mut rng = StdRng::seed_from_u64(1);
+        // Neither can move, and they start 12 apart
+        let blob = creature("Gooey Blob");
+        let mut elf = creature("Elf");
+        elf.movement = 0;
+        elf.range = 12;
+        assert!(can_attack(&elf));
+        assert_eq!(duel([&elf, &blob], 0, 30, &mut rng), Some(0));
+        elf.range = 11;
+        assert_eq!(duel([&elf, &blob], 0, 30, &mut rng), None);
+    }
+
+    #[test]
+    fn test_outliers() {
+        let flags = outliers(&[0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 2.0]);
+        assert_eq!(flags[9], Some("strong"));
+        assert!(flags[..9].iter().all(Option::is_none));
+        assert!(outliers(&[1.0, 1.0]).iter().all(Option::is_none));
+    }
+}
@@ -1,5 +1,6 @@
 
 use bevy::{prelude::*, utils::HashMap};
+use rand::Rng;
 use crate::{constants::{WIDTH, HEIGHT}, vec::Vec2I};
 
 pub struct BoardPlugin;
@@ -13,6 +14,9 @@ impl Plugin for BoardPlugin {
         .add_system(move_entity)
         .add_event::<BoardKill>()
         .add_system(kill_entity)
+        .add_event::<BoardRemove>()
+        .add_system(remove_entity)
+        .add_event::<RangedAttack>()
         .insert_resource(GameBoard::new());
     }
 }
@@ -49,18 +53,23 @@ fn move_entity(
 ) {
     for e in ev.iter() {
         let current_pos = Vec2::from(board.get_entity_pos(e.entity));
-        println!("Do Board move from {current_pos} to {}", e.to);
+        debug!("Do Board move from {current_pos} to {}", e.to);
         let entity = board.pop_entity(current_pos);
-        println!("pop entity {entity:?}");
+        debug!("pop entity {entity:?}");
         let mut transform = query.get_mut(entity).unwrap();
         *transform = transform.with_translation(e.to.extend(1.0));
         board.put_entity(e.to, entity);
     }
 }
 
+// The owners and the killer's name go with the kill, as the killed piece is despawned before
+// every reader has seen it
 pub struct BoardKill {
     pub killer: Entity,
     pub killed: Entity,
+    pub killer_owner: Option<Entity>,
+    pub killed_owner: Option<Entity>,
+    pub by: String,
 }
 
 fn kill_entity(
@@ -72,8 +81,8 @@ fn kill_entity(
     for e in ev.iter() {
         let killed_pos = Vec2::from(board.get_entity_pos(e.killed));
         let entity = board.pop_entity(killed_pos);
-        println!("Board kill - pop entity {entity:?}");
-        commands.entity(entity).despawn();
+        debug!("Board kill - pop entity {entity:?}");
+        commands.entity(entity).despawn_recursive();
         ev_move.send(BoardMove{
             entity: e.killer,
             to: killed_pos,
@@ -81,6 +90,37 @@ fn kill_entity(
     }
 }
 
+// Taken off the board without anything taking its place
+pub struct BoardRemove(pub Entity);
+
+fn remove_entity(
+    mut ev: EventReader<BoardRemove>,
+    mut board: ResMut<GameBoard>,
+    mut commands: Commands,
+) {
+    for BoardRemove(entity) in &mut ev {
+        if board.take_entity(*entity) {
+            debug!("Board remove entity {entity:?}");
+            commands.entity(*entity).despawn_recursive();
+        }
+    }
+}
+
+// Attack and defence each have 1 up to die added, and the attacker wins ties
+pub fn combat_rolls(combat: u8, defence: u8, die: u8, rng: &mut impl Rng) -> (u8, u8) {
+    (combat + rng.gen_range(1..=die), defence + rng.gen_range(1..=die))
+}
+
+// A ranged attack has been fired
+pub struct RangedAttack;
+
+// Squares a piece can get to this move, and squares holding enemies it can attack
+#[derive(Debug, Default)]
+pub struct Reachable {
+    pub moves: Vec<Vec2I>,
+    pub attacks: Vec<Vec2I>,
+}
+
 #[derive(Resource)]
 pub struct GameBoard([GameColumn; WIDTH], HashMap<Entity, Vec2I>);
 struct GameColumn([GameSquare; HEIGHT]);
@@ -88,10 +128,10 @@ struct GameSquare(Vec<Entity>);
 
 #[allow(clippy::cast_sign_loss)]
 impl GameBoard {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self([GameColumn; WIDTH].map(|_| GameColumn::new()), HashMap::new())
     }
-    fn put_entity(&mut self, pos: Vec2, e: Entity) {
+    pub fn put_entity(&mut self, pos: Vec2, e: Entity) {
         self.0[pos.x as usize].0[pos.y as usize].0.push(e);
         self.1.insert(e, Vec2I::from(pos));
     }
@@ -111,9 +151,112 @@ impl GameBoard {
     pub fn get_entity_pos(&self, e: Entity) -> Vec2I {
         *self.1.get(&e).unwrap()
     }
+    // None if it isn't on the board
+    pub fn entity_pos(&self, e: Entity) -> Option<Vec2I> {
+        self.1.get(&e).copied()
+    }
     fn pop_entity(&mut self, pos: Vec2) -> Entity {
         let stack = &mut self.0[pos.x as usize].0[pos.y as usize].0;
-        stack.remove(stack.len()-1)
+        let e = stack.remove(stack.len()-1);
+        self.1.remove(&e);
+        e
+    }
+    // Wherever it is in the stack on its square, false if it isn't on the board
+    pub fn take_entity(&mut self, e: Entity) -> bool {
+        let Some(pos) = self.1.remove(&e) else {
+            return false;
+        };
+        self.0[pos.x as usize].0[pos.y as usize].0.retain(|x| *x != e);
+        true
+    }
+    // Empty, for a new game
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+    // The part of the board the cursor can reach, the rest is covered by the border
+    pub fn in_bounds(pos: Vec2I) -> bool {
+        pos.x >= 0 && pos.y >= 0 && (pos.x as usize) < WIDTH - 1 && (pos.y as usize) < HEIGHT - 2
+    }
+    // Flying pieces can land anywhere within range of where they started. Ground pieces step one
+    // square at a time through empty squares, and stop once they are movement squares from the start.
+    pub fn reachable(
+        &self,
+        start: Vec2I,
+        from: Vec2I,
+        movement: u8,
+        flying: bool,
+        is_enemy: impl Fn(Entity) -> bool,
+    ) -> Reachable {
+        let mut r = Reachable::default();
+        let classify = |pos: Vec2I, r: &mut Reachable| -> bool {
+            if pos == from || r.moves.contains(&pos) || r.attacks.contains(&pos) {
+                return false;
+            }
+            if !Self::in_bounds(pos) || start.distance(pos) > movement {
+                return false;
+            }
+            match self.get_entity(Vec2::from(pos)) {
+                None => {
+                    r.moves.push(pos);
+                    true
+                }
+                Some(e) => {
+                    if is_enemy(e) {
+                        r.attacks.push(pos);
+                    }
+                    false
+                }
+            }
+        };
+        if flying {
+            let m = i8::try_from(movement).unwrap_or(i8::MAX).min(i8::try_from(WIDTH).unwrap());
+            for x in start.x - m..=start.x + m {
+                for y in start.y - m..=start.y + m {
+                    classify(Vec2I::new(x, y), &mut r);
+                }
+            }
+            return r;
+        }
+        let mut frontier = vec![from];
+        while let Some(pos) = frontier.pop() {
+            if start.distance(pos) >= movement {
+                continue;
+            }
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    let next = pos + Vec2I::new(dx, dy);
+                    if classify(next, &mut r) {
+                        frontier.push(next);
+                    }
+                }
+            }
+        }
+        r
+    }
+    // Every square inside the border
+    pub fn squares() -> impl Iterator<Item = Vec2I> {
+        let (w, h) = (i8::try_from(WIDTH).unwrap(), i8::try_from(HEIGHT).unwrap());
+        (0..w).flat_map(move |x| (0..h).map(move |y| Vec2I::new(x, y))).filter(|pos| Self::in_bounds(*pos))
+    }
+    // Squares within range of any of the eyes, with nothing in the way
+    pub fn visible_from(&self, eyes: &[Vec2I], range: u8) -> Vec<Vec2I> {
+        Self::squares()
+            .filter(|pos| eyes.iter().any(|e| e.distance(*pos) <= range && self.line_of_sight(*e, *pos)))
+            .collect()
+    }
+    // Nothing standing on the squares a straight line between the two passes through
+    pub fn line_of_sight(&self, from: Vec2I, to: Vec2I) -> bool {
+        Self::line_clear(from, to, |pos| self.has_entity_at(pos))
+    }
+    // The same, with the caller saying which squares are in the way
+    pub fn line_clear(from: Vec2I, to: Vec2I, blocked: impl Fn(Vec2) -> bool) -> bool {
+        let d = to - from;
+        let steps = i32::from(d.x.abs().max(d.y.abs()));
+        (1..steps).all(|i| {
+            let x = i32::from(from.x) + (i32::from(d.x) * i * 2 + steps).div_euclid(steps * 2);
+            let y = i32::from(from.y) + (i32::from(d.y) * i * 2 + steps).div_euclid(steps * 2);
+            !blocked(Vec2::new(x as f32, y as f32))
+        })
     }
 }
 impl GameColumn {
@@ -130,6 +273,7 @@ impl GameSquare {
 #[cfg(test)]
 mod tests {
     use bevy::prelude::*;
+    use crate::vec::Vec2I;
     use super::GameBoard;
 
     #[test]
@@ -150,4 +294,59 @@ mod tests {
         assert!(e.is_some());
         assert_eq!(e.unwrap().index(), 2);
     }
-}
\ No newline at end of file
+    #[test]
+    fn reachable_ground() {
+        let mut b = GameBoard::new();
+        b.put_entity(Vec2::new(1.0, 1.0), Entity::from_raw(1));
+        b.put_entity(Vec2::new(0.0, 1.0), Entity::from_raw(2));
+        let start = Vec2I::new(0, 0);
+        let r = b.reachable(start, start, 1, false, |e| e.index() == 1);
+        assert_eq!(r.moves, vec![Vec2I::new(1, 0)]);
+        assert_eq!(r.attacks, vec![Vec2I::new(1, 1)]);
+    }
+    #[test]
+    fn reachable_ground_blocked() {
+        let mut b = GameBoard::new();
+        for y in 0..5 {
+            b.put_entity(Vec2::new(1.0, y as f32), Entity::from_raw(1));
+        }
+        let start = Vec2I::new(0, 1);
+        let r = b.reachable(start, start, 3, false, |_| false);
+        assert!(r.moves.iter().all(|p| p.x == 0));
+        assert!(r.attacks.is_empty());
+    }
+    #[test]
+    fn line_of_sight() {
+        let mut b = GameBoard::new();
+        b.put_entity(Vec2::new(2.0, 2.0), Entity::from_raw(1));
+        assert!(!b.line_of_sight(Vec2I::new(0, 0), Vec2I::new(4, 4)));
+        assert!(!b.line_of_sight(Vec2I::new(2, 0), Vec2I::new(2, 5)));
+        assert!(b.line_of_sight(Vec2I::new(0, 0), Vec2I::new(2, 2)));
+        assert!(b.line_of_sight(Vec2I::new(0, 0), Vec2I::new(4, 1)));
+        assert!(b.line_of_sight(Vec2I::new(3, 3), Vec2I::new(3, 3)));
+    }
+    #[test]
+    fn visible_from() {
+        let mut b = GameBoard::new();
+        b.put_entity(Vec2::new(2.0, 0.0), Entity::from_raw(1));
+        let seen = b.visible_from(&[Vec2I::new(0, 0)], 3);
+        assert!(seen.contains(&Vec2I::new(0, 0)));
+        assert!(seen.contains(&Vec2I::new(2, 0)));
+        // Behind the piece, and out of range
+        assert!(!seen.contains(&Vec2I::new(3, 0)));
+        assert!(!seen.contains(&Vec2I::new(0, 4)));
+        assert!(seen.contains(&Vec2I::new(0, 3)));
+    }
+    #[test]
+    fn reachable_flying() {
+        let mut b = GameBoard::new();
+        for y in 0..3 {
+            b.put_entity(Vec2::new(1.0, y as f32), Entity::from_raw(1));
+        }
+        let start = Vec2I::new(0, 1);
+        let r = b.reachable(start, start, 3, true, |_| true);
+        assert!(r.moves.contains(&Vec2I::new(3, 1)));
+        assert!(!r.moves.contains(&Vec2I::new(4, 1)));
+        assert_eq!(r.attacks.len(), 3);
+    }
+}
@@ -0,0 +1,237 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use crate::game::Game;
+use crate::gamestate::GameState;
+use crate::palette::{wizard_colors, WIZARD_COUNT};
+use crate::player::Player;
+use crate::rules::CLASSIC;
+use crate::settings::Settings;
+use crate::spell::AllSpells;
+
+// One wizard fighting a run of battles against computer wizards, more of them and better each
+// time. Every win earns a reward which stays with the wizard for the rest of the campaign, and
+// the whole thing is saved after every change so it can be carried on with another day.
+const CAMPAIGN_FILE: &str = "campaign.ron";
+
+// How many computer wizards each battle has, and their ai_level
+pub const BATTLES: [(usize, u8); 6] = [(1, 1), (1, 3), (2, 3), (2, 5), (3, 6), (4, 8)];
+const RIVALS: [&str; 8] = ["Zarn", "Morgana", "Grimwold", "Asra", "Vell", "Orbin", "Illusa", "Kesh"];
+const MAX_STAT: u8 = 9;
+// Two more spells still fit on the turn menu
+const MAX_EXTRA_SPELLS: u8 = 2;
+
+pub struct CampaignPlugin;
+
+impl Plugin for CampaignPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(Campaign::load())
+            .add_system(campaign_game_over.in_schedule(OnEnter(GameState::GameOver)));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Boost {
+    Combat,
+    Defence,
+    Manoeuvre,
+    MagicResistance,
+    Spell,
+}
+
+pub const BOOSTS: [Boost; 5] = [Boost::Combat, Boost::Defence, Boost::Manoeuvre, Boost::MagicResistance, Boost::Spell];
+
+impl Boost {
+    pub const fn key(self) -> &'static str {
+        match self {
+            Self::Combat => "campaign.combat",
+            Self::Defence => "campaign.defence",
+            Self::Manoeuvre => "campaign.manoeuvre",
+            Self::MagicResistance => "campaign.magic_resistance",
+            Self::Spell => "campaign.spell",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CampaignWizard {
+    pub name: String,
+    pub character_icon: u8,
+    pub color_index: usize,
+    pub combat: u8,
+    pub defence: u8,
+    pub manoeuvre: u8,
+    pub magic_resistance: u8,
+    // Dealt on top of the usual spells at the start of each battle
+    pub extra_spells: u8,
+}
+
+impl CampaignWizard {
+    fn stat(&mut self, boost: Boost) -> (&mut u8, u8) {
+        match boost {
+            Boost::Combat => (&mut self.combat, MAX_STAT),
+            Boost::Defence => (&mut self.defence, MAX_STAT),
+            Boost::Manoeuvre => (&mut self.manoeuvre, MAX_STAT),
+            Boost::MagicResistance => (&mut self.magic_resistance, MAX_STAT),
+            Boost::Spell => (&mut self.extra_spells, MAX_EXTRA_SPELLS),
+        }
+    }
+}
+
+#[derive(Default, Resource, Serialize, Deserialize)]
+pub struct Campaign {
+    // None until a campaign is started
+    pub wizard: Option<CampaignWizard>,
+    // Battles won, which is also the one to fight next
+    pub won: usize,
+    pub lost: u32,
+    // A battle has been won and its reward not picked yet
+    pub reward_due: bool,
+    // A battle is being fought, so the end of the game comes back here
+    #[serde(skip)]
+    pub in_battle: bool,
+}
+
+impl Campaign {
+    fn load() -> Self {
+        let Ok(f) = File::open(CAMPAIGN_FILE) else {
+            return Self::default();
+        };
+        ron::de::from_reader(f).unwrap_or_else(|e| {
+            warn!("Ignoring unreadable {CAMPAIGN_FILE}: {e}");
+            Self::default()
+        })
+    }
+    pub fn save(&self) {
+        let res = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())
+            .and_then(|s| std::fs::write(CAMPAIGN_FILE, s).map_err(|e| e.to_string()));
+        if let Err(e) = res {
+            warn!("Could not save {CAMPAIGN_FILE}: {e}");
+        }
+    }
+    // A new wizard with the same spread of strengths as any other, starting from the first battle
+    pub fn start(&mut self, name: String, rng: &mut impl Rng) {
+        let mut player = Player::new(name, false, rng.gen_range(1..=8), 0, Color::WHITE);
+        player.roll_stats(&CLASSIC, rng);
+        *self = Self {
+            wizard: Some(CampaignWizard {
+                name: player.name,
+                character_icon: player.character_icon,
+                color_index: player.color_index,
+                combat: player.combat,
+                defence: player.defence,
+                manoeuvre: player.manoeuvre,
+                magic_resistance: player.magic_resistance,
+                extra_spells: 0,
+            }),
+            ..default()
+        };
+    }
+    pub fn finished(&self) -> bool {
+        self.won >= BATTLES.len()
+    }
+    pub fn can_boost(&mut self, boost: Boost) -> bool {
+        self.wizard.as_mut().is_some_and(|w| {
+            let (stat, max) = w.stat(boost);
+            *stat < max
+        })
+    }
+    pub fn boost(&mut self, boost: Boost) {
+        if !self.reward_due || !self.can_boost(boost) {
+            return;
+        }
+        if let Some(w) = self.wizard.as_mut() {
+            *w.stat(boost).0 += 1;
+        }
+        self.reward_due = false;
+    }
+    fn record(&mut self, won: bool) {
+        if won {
+            self.won += 1;
+            // Nothing left to spend a reward on after the last battle
+            self.reward_due = !self.finished();
+        } else {
+            self.lost += 1;
+        }
+    }
+    // The campaign wizard first, then the computer wizards for the next battle in colours the
+    // campaign wizard isn't using
+    pub fn players(&self, allspells: &AllSpells, settings: &Settings, rng: &mut impl Rng) -> Vec<Player> {
+        let Some(w) = &self.wizard else {
+            return Vec::new();
+        };
+        let colors = wizard_colors(settings);
+        let mut player = Player::new(w.name.clone(), false, w.character_icon, w.color_index, colors[w.color_index]);
+        player.combat = w.combat;
+        player.defence = w.defence;
+        player.manoeuvre = w.manoeuvre;
+        player.magic_resistance = w.magic_resistance;
+        player.pick_spells(allspells);
+        let unused: Vec<&crate::spell::SpellBox> = allspells[1..].iter()
+            .filter(|s| !player.spells.spells.iter().any(|p| p.name() == s.name()))
+            .collect();
+        for spell in unused.choose_multiple(rng, w.extra_spells as usize) {
+            player.spells.spells.push((**spell).clone());
+        }
+        let (rivals, _) = BATTLES[self.won.min(BATTLES.len() - 1)];
+        let mut free: Vec<usize> = (0..WIZARD_COUNT).filter(|c| *c != w.color_index).collect();
+        free.shuffle(rng);
+        let mut players = vec![player];
+        for (i, color) in free.into_iter().take(rivals).enumerate() {
+            let name = RIVALS[(self.won + i) % RIVALS.len()].to_string();
+            let mut rival = Player::new(name, true, rng.gen_range(1..=8), color, colors[color]);
+            rival.pick_spells(allspells);
+            rival.ai_level = self.ai_level();
+            players.push(rival);
+        }
+        players
+    }
+    pub fn ai_level(&self) -> u8 {
+        BATTLES[self.won.min(BATTLES.len() - 1)].1
+    }
+}
+
+// The campaign wizard has to be the one left standing
+fn campaign_game_over(
+    g: Res<Game>,
+    mut campaign: ResMut<Campaign>,
+) {
+    if !campaign.in_battle {
+        return;
+    }
+    let won = g.player_info.first().is_some_and(|p| p.alive) && g.living_players() == 1;
+    campaign.record(won);
+    campaign.save();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boost() {
+        let mut campaign = Campaign {
+            wizard: Some(CampaignWizard {
+                name: "W".to_string(), character_icon: 1, color_index: 0,
+                combat: 8, defence: 1, manoeuvre: 3, magic_resistance: 6, extra_spells: 0,
+            }),
+            ..default()
+        };
+        // Only once a battle has been won
+        campaign.boost(Boost::Combat);
+        assert_eq!(campaign.wizard.as_ref().unwrap().combat, 8);
+        campaign.reward_due = true;
+        campaign.boost(Boost::Combat);
+        assert_eq!(campaign.wizard.as_ref().unwrap().combat, 9);
+        assert!(!campaign.reward_due);
+        campaign.reward_due = true;
+        assert!(!campaign.can_boost(Boost::Combat));
+        campaign.boost(Boost::Combat);
+        assert!(campaign.reward_due);
+        campaign.boost(Boost::Spell);
+        assert_eq!(campaign.wizard.as_ref().unwrap().extra_spells, 1);
+    }
+}
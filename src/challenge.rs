@@ -0,0 +1,201 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use crate::game::Game;
+use crate::gamestate::GameState;
+use crate::scenario::Scenario;
+
+// Hand-made puzzles, each a fixed position with something to do in it: kill a wizard this turn
+// with the spells given, or hold out for a number of rounds. The first wizard in the scenario is
+// the one being played for, and the others are computer wizards playing at the challenge's
+// ai_level. Whether it has been won or lost is looked at as each phase ends.
+const CHALLENGES_FILE: &str = "assets/challenges.ron";
+// Names of the challenges won so far
+const DONE_FILE: &str = "challenges_done.ron";
+
+pub struct ChallengePlugin;
+
+impl Plugin for ChallengePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(Challenges::load())
+            .init_resource::<ActiveChallenge>()
+            .add_system(challenge_check.in_schedule(OnEnter(GameState::TurnMenu)))
+            .add_system(challenge_check.in_schedule(OnEnter(GameState::CastSpellSetup)))
+            .add_system(challenge_check.in_schedule(OnEnter(GameState::MoveSetup)));
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Condition {
+    // The wizard at this place in the scenario is dead
+    Dead(usize),
+    // Every wizard but the first is dead
+    EnemiesDead,
+    // This many whole rounds have been played
+    Rounds(u32),
+}
+
+impl Condition {
+    fn met(&self, alive: &[bool], rounds: u32) -> bool {
+        match self {
+            Self::Dead(i) => alive.get(*i) == Some(&false),
+            Self::EnemiesDead => alive.iter().skip(1).all(|a| !a),
+            Self::Rounds(n) => rounds >= *n,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Won,
+    Lost,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Challenge {
+    pub name: String,
+    // A few short lines saying what to do
+    pub description: Vec<String>,
+    // Casting chances, combat and the wizards' own strengths all come from this
+    pub seed: u64,
+    pub scenario: Scenario,
+    pub win: Vec<Condition>,
+    #[serde(default)]
+    pub lose: Vec<Condition>,
+    #[serde(default = "default_ai_level")]
+    pub ai_level: u8,
+}
+
+// Careful enough to keep their wizards out of trouble
+const fn default_ai_level() -> u8 {
+    5
+}
+
+impl Challenge {
+    // Losing the first wizard is always a loss and being the last one standing always a win,
+    // whatever else the challenge asks for. Losing is looked at first.
+    pub fn outcome(&self, alive: &[bool], rounds: u32) -> Option<Outcome> {
+        if alive.first() != Some(&true) || self.lose.iter().any(|c| c.met(alive, rounds)) {
+            Some(Outcome::Lost)
+        } else if Condition::EnemiesDead.met(alive, rounds) || self.win.iter().any(|c| c.met(alive, rounds)) {
+            Some(Outcome::Won)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct Challenges {
+    pub list: Vec<Challenge>,
+    done: Vec<String>,
+}
+
+impl Challenges {
+    fn load() -> Self {
+        let list = File::open(CHALLENGES_FILE)
+            .map_err(|e| e.to_string())
+            .and_then(|f| ron::de::from_reader(f).map_err(|e| e.to_string()))
+            .unwrap_or_else(|e| {
+                warn!("No challenges from {CHALLENGES_FILE}: {e}");
+                Vec::new()
+            });
+        let done = File::open(DONE_FILE).ok()
+            .and_then(|f| ron::de::from_reader(f).ok())
+            .unwrap_or_default();
+        Self { list, done }
+    }
+    pub fn is_done(&self, name: &str) -> bool {
+        self.done.iter().any(|d| d == name)
+    }
+    fn complete(&mut self, name: &str) {
+        if self.is_done(name) {
+            return;
+        }
+        self.done.push(name.to_string());
+        let res = ron::ser::to_string_pretty(&self.done, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())
+            .and_then(|s| std::fs::write(DONE_FILE, s).map_err(|e| e.to_string()));
+        if let Err(e) = res {
+            warn!("Could not save {DONE_FILE}: {e}");
+        }
+    }
+}
+
+// The challenge being played, and how it went once it's over
+#[derive(Default, Resource)]
+pub struct ActiveChallenge {
+    pub challenge: Option<usize>,
+    pub outcome: Option<Outcome>,
+}
+
+impl ActiveChallenge {
+    pub fn is_active(&self) -> bool {
+        self.challenge.is_some()
+    }
+}
+
+// For the systems which move the game on to the next phase, which stop once a challenge is decided
+pub fn undecided(active: Res<ActiveChallenge>) -> bool {
+    active.outcome.is_none()
+}
+
+fn challenge_check(
+    g: Res<Game>,
+    mut challenges: ResMut<Challenges>,
+    mut active: ResMut<ActiveChallenge>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    let Some(i) = active.challenge.filter(|_| active.outcome.is_none()) else {
+        return;
+    };
+    let alive: Vec<bool> = g.player_info.iter().map(|p| p.alive).collect();
+    let Some(outcome) = challenges.list[i].outcome(&alive, g.round.saturating_sub(1)) else {
+        return;
+    };
+    if outcome == Outcome::Won {
+        let name = challenges.list[i].name.clone();
+        challenges.complete(&name);
+    }
+    active.outcome = Some(outcome);
+    state.set(GameState::ChallengeOver);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outcome() {
+        let mut challenge = Challenge {
+            name: "test".to_string(),
+            description: Vec::new(),
+            seed: 0,
+            scenario: Scenario::default(),
+            win: vec![Condition::Dead(1)],
+            lose: vec![Condition::Rounds(1)],
+            ai_level: 0,
+        };
+        assert_eq!(challenge.outcome(&[true, true, true], 0), None);
+        assert_eq!(challenge.outcome(&[true, false, true], 0), Some(Outcome::Won));
+        assert_eq!(challenge.outcome(&[true, false, true], 1), Some(Outcome::Lost));
+        assert_eq!(challenge.outcome(&[false, false, false], 0), Some(Outcome::Lost));
+        // Holding out, and the last one standing has nothing left to hold out against
+        challenge.win = vec![Condition::Rounds(5)];
+        challenge.lose.clear();
+        assert_eq!(challenge.outcome(&[true, true], 4), None);
+        assert_eq!(challenge.outcome(&[true, true], 5), Some(Outcome::Won));
+        assert_eq!(challenge.outcome(&[true, false, false], 0), Some(Outcome::Won));
+    }
+
+    #[test]
+    fn test_challenges_file() {
+        let challenges = Challenges::load();
+        assert!(!challenges.list.is_empty());
+        let creatures = crate::creature::load_creatures();
+        for challenge in &challenges.list {
+            assert!(challenge.scenario.creatures.iter().all(|c| creatures.contains_key(&c.name)), "{}", challenge.name);
+        }
+    }
+}
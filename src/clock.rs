@@ -0,0 +1,201 @@
+use bevy::prelude::*;
+use crate::display::{print_text, BottomTextEvent, LINE_LEN, RED, WHITE};
+use crate::game::Game;
+use crate::gamestate::GameState;
+use crate::locale::Locale;
+use crate::messagelog::LogEvent;
+
+// Chess clocks for hot-seat games. Each wizard can have a limit on every turn, on the whole game,
+// or both, and once either runs out whatever they were doing is over: a spell not yet cast is
+// lost and pieces not yet moved stay where they are, and a wizard out of game time passes every
+// turn after. The clock only runs while a wizard is choosing, casting or moving, so reading the
+// log or a spell's description stops it.
+pub struct ClockPlugin;
+
+impl Plugin for ClockPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Clock>()
+            .add_event::<TimeUp>()
+            .add_system(clock_tick)
+            .add_system(clock_draw.after(clock_tick));
+    }
+}
+
+// Seconds for each turn and for the whole game, picked from in player setup
+const PRESETS: [(Option<u32>, Option<u32>); 7] = [
+    (None, None),
+    (Some(30), None),
+    (Some(60), None),
+    (Some(120), None),
+    (None, Some(600)),
+    (None, Some(1200)),
+    (Some(60), Some(1200)),
+];
+
+// The time left goes red from here
+const WARNING: u32 = 10;
+
+// Choosing, casting and moving each count as a turn of their own
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Phase {
+    Choose,
+    Cast,
+    Move,
+}
+
+impl Phase {
+    // None while the clock is stopped. Attacks and explosions take as long as they take, so
+    // they don't count either.
+    fn of(state: &GameState) -> Option<Self> {
+        match state {
+            GameState::TurnMenu
+            | GameState::TurnMenuExamineSpell
+            | GameState::TurnMenuSelectSpell
+            | GameState::TurnMenuExamineBoard => Some(Self::Choose),
+            GameState::CastSpell => Some(Self::Cast),
+            GameState::MoveChoose
+            | GameState::MoveMoving
+            | GameState::RangedAttackChoose => Some(Self::Move),
+            _ => None,
+        }
+    }
+}
+
+// Sent every update while the current wizard has no time left, until their turn is over
+pub struct TimeUp;
+
+#[derive(Default, Resource)]
+pub struct Clock {
+    preset: usize,
+    // Seconds each wizard has used this game, in seat order
+    used: Vec<f32>,
+    // Whose turn the clock is running for, and how long it has been going
+    turn: Option<(Phase, usize)>,
+    turn_used: f32,
+    announced: bool,
+}
+
+impl Clock {
+    pub fn next_preset(&mut self) {
+        self.preset = (self.preset + 1) % PRESETS.len();
+    }
+    pub fn describe(&self, locale: &Locale) -> String {
+        match PRESETS[self.preset] {
+            (None, None) => locale.text("setup.clock_off").to_string(),
+            (Some(turn), None) => locale.format("setup.clock_turn", &[&format_time(turn)]),
+            (None, Some(game)) => locale.format("setup.clock_game", &[&format_time(game)]),
+            (Some(turn), Some(game)) => locale.format("setup.clock_both", &[&format_time(turn), &format_time(game)]),
+        }
+    }
+    pub fn start_game(&mut self, players: usize) {
+        self.used = vec![0.0; players];
+        self.turn = None;
+    }
+    fn limited(&self) -> bool {
+        PRESETS[self.preset] != (None, None)
+    }
+    // Whichever of the two limits the player will hit first
+    fn left(&self, player: usize) -> Option<f32> {
+        let (turn, game) = PRESETS[self.preset];
+        let turn_left = turn.map(|t| t as f32 - self.turn_used);
+        let game_left = game.map(|g| g as f32 - self.used.get(player).copied().unwrap_or(0.0));
+        match (turn_left, game_left) {
+            (Some(t), Some(g)) => Some(t.min(g)),
+            (t, g) => t.or(g),
+        }
+    }
+    // Counts the time against the player, true if they have none left
+    fn tick(&mut self, phase: Phase, player: usize, secs: f32) -> bool {
+        if self.turn != Some((phase, player)) {
+            self.turn = Some((phase, player));
+            self.turn_used = 0.0;
+            self.announced = false;
+        }
+        self.turn_used += secs;
+        if let Some(used) = self.used.get_mut(player) {
+            *used += secs;
+        }
+        self.left(player).is_some_and(|left| left <= 0.0)
+    }
+}
+
+fn format_time(secs: u32) -> String {
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+fn clock_tick(
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+    g: Res<Game>,
+    locale: Res<Locale>,
+    mut clock: ResMut<Clock>,
+    mut ev_time_up: EventWriter<TimeUp>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+    mut ev_log: EventWriter<LogEvent>,
+) {
+    let Some(phase) = Phase::of(&state.0) else {
+        return;
+    };
+    if !clock.limited() || !clock.tick(phase, g.player_turn as usize, time.delta_seconds()) {
+        return;
+    }
+    if !clock.announced {
+        clock.announced = true;
+        ev_text.send(BottomTextEvent::important(&locale.format("clock.time_up", &[&g.get_player().name])));
+        ev_log.send(LogEvent::player(&g, locale.text("log.time_up")));
+    }
+    ev_time_up.send(TimeUp);
+}
+
+#[derive(Component, Clone, Copy)]
+struct ClockText;
+
+// At the right hand end of the bottom line, only redrawn when the seconds change
+fn clock_draw(
+    mut commands: Commands,
+    g: Res<Game>,
+    clock: Res<Clock>,
+    state: Res<State<GameState>>,
+    existing: Query<Entity, With<ClockText>>,
+    mut shown: Local<Option<u32>>,
+) {
+    let left = Phase::of(&state.0)
+        .filter(|_| clock.limited())
+        .and_then(|_| clock.left(g.player_turn as usize))
+        .map(|left| (left.ceil() as i32).max(0).unsigned_abs());
+    if left == *shown {
+        return;
+    }
+    for e in &existing {
+        commands.entity(e).despawn();
+    }
+    if let Some(secs) = left {
+        let text = format_time(secs);
+        let color = if secs <= WARNING { RED } else { WHITE };
+        let x = (LINE_LEN - text.len()) as f32 / 2.0;
+        print_text(&text, &mut commands, g.fah(), Vec2::new(x, -1.5), color, ClockText);
+    }
+    *shown = left;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_tick() {
+        let mut clock = Clock { preset: 6, ..default() };
+        clock.start_game(2);
+        assert!(!clock.tick(Phase::Choose, 0, 50.0));
+        assert!(clock.tick(Phase::Choose, 0, 10.0));
+        // A new turn starts the turn limit again but not the game one
+        assert!(!clock.tick(Phase::Cast, 0, 0.0));
+        assert_eq!(clock.left(0), Some(60.0));
+        assert!(!clock.tick(Phase::Choose, 1, 59.0));
+        clock.used[0] = 1190.0;
+        assert!(!clock.tick(Phase::Move, 0, 5.0));
+        assert!(clock.tick(Phase::Move, 0, 5.0));
+        assert_eq!(format_time(75), "1:15");
+    }
+}
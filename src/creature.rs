@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, fs::File};
 use crate::board::MoveableComponent;
 use crate::display::spawn_anim;
+use crate::locale::Locale;
 use crate::player::Player;
 use crate::spell::{ASpell, SpellBox};
 use crate::system::{BoardEntity, Named, RangedCombat, CanDefend, CanAttack};
@@ -23,26 +24,29 @@ pub struct Creature {
     sprite_index: usize,
     entity: Option<Entity>,
     #[serde(default = "default_as_zero")]
-    movement: u8,
+    pub movement: u8,
     #[serde(default = "default_as_false")]
-    flying: bool,
+    pub flying: bool,
     #[serde(default = "default_as_zero")]
-    combat: u8,
+    pub combat: u8,
     #[serde(default = "default_as_zero")]
-    ranged_combat: u8,
+    pub ranged_combat: u8,
     #[serde(default = "default_as_zero")]
-    range: u8,
+    pub range: u8,
     #[serde(default = "default_as_zero")]
-    defence: u8,
+    pub defence: u8,
     #[serde(default = "default_as_false")]
     mountable: bool,
     #[serde(default = "default_as_false")]
     can_be_illusion: bool,
     #[serde(default = "default_as_zero_signed")]
     law_chaos: i8,
-    casting_chance: u8,
+    pub casting_chance: u8,
     manoeuvre: u8,
     magical_resistance: u8,
+    // Only used by the balance report so far
+    #[serde(default = "default_as_zero")]
+    pub strength: u8,
     color_r: u8,
     color_g: u8,
     color_b: u8,
@@ -55,7 +59,7 @@ pub struct CreatureComponent {
 }
 
 impl Creature {
-    fn to_entity(
+    pub fn to_entity(
         &self,
         illusion: bool,
         v: Vec2,
@@ -121,18 +125,21 @@ impl ASpell for CreatureSpell {
     fn can_be_illusion(&self) -> bool {
         self.creature.can_be_illusion
     }
+    fn creature(&self) -> Option<&Creature> {
+        Some(&self.creature)
+    }
     fn casting_chance(&self) -> u8 {
         self.creature.casting_chance
     }
-    fn get_description(&self) -> Vec<String> {
+    fn get_description(&self, locale: &Locale) -> Vec<String> {
         vec![
-            format!("Combat={}", self.creature.combat),
-            format!("Ranged Combat={} Range={}", self.creature.ranged_combat, self.creature.range),
-            format!("Defence={}", self.creature.defence),
-            format!("Movement Allowance={}", self.creature.movement),
-            format!("Manoeuver Rating={}", self.creature.manoeuvre),
-            format!("Magic Resistance={}", self.creature.magical_resistance),
-            format!("Casting Chance={}%", self.casting_chance()),
+            locale.format("creature.combat", &[&self.creature.combat]),
+            locale.format("creature.ranged", &[&self.creature.ranged_combat, &self.creature.range]),
+            locale.format("creature.defence", &[&self.creature.defence]),
+            locale.format("creature.movement", &[&self.creature.movement]),
+            locale.format("creature.manoeuvre", &[&self.creature.manoeuvre]),
+            locale.format("creature.resistance", &[&self.creature.magical_resistance]),
+            locale.format("creature.casting_chance", &[&self.casting_chance()]),
         ]
     }
 }
@@ -1,8 +1,9 @@
 use bevy::math::vec2;
 use bevy::prelude::*;
 use super::constants::{ANIMATION_TICK, WIDTH, HEIGHT, CURSOR_Z};
-use super::Game;
+use crate::game::Game;
 use crate::display;
+use crate::input::{Action, Direction};
 
 const CURSOR_SPRITE_ID: usize = 164;
 pub const CURSOR_SPELL: usize = 0;
@@ -68,7 +69,7 @@ impl Cursor {
         Vec2 { x: self.x, y: self.y }
     }
     pub fn hide_till_moved(&mut self) {
-        println!("SET HIDE TILL MOVED");
+        debug!("SET HIDE TILL MOVED");
         self.hide_till_moved = true;
     }
 }
@@ -93,72 +94,32 @@ fn cursor_setup(
     };
 }
 
-#[allow(clippy::useless_let_if_seq)]
 fn keyboard_input(
-    keys: Res<Input<KeyCode>>,
+    actions: Res<Input<Action>>,
     mut cursor: ResMut<Cursor>,
     mut ev_cursor_moved: EventWriter<CursorMovedEvent>,
 ) {
     let previous_pos = Vec2 { x: cursor.x, y: cursor.y };
-    if keys.just_pressed(KeyCode::A) && cursor.x > 0.0 {
-        cursor.x -= 1.0;
-        cursor.moved = true;
-    }
-    if keys.just_pressed(KeyCode::D) && cursor.x < WIDTH as f32 - 2.0  {
-        cursor.x += 1.0;
-        cursor.moved = true;
-    }
-    if keys.just_pressed(KeyCode::W) && cursor.y < HEIGHT as f32 - 3.0 {
-        cursor.y += 1.0;
-        cursor.moved = true;
-    }
-    if keys.just_pressed(KeyCode::Q) {
-        if cursor.y < HEIGHT as f32 - 3.0 {
-            cursor.y += 1.0;
-            cursor.moved = true;
-        }
-        if cursor.x > 0.0 {
-            cursor.x -= 1.0;
-            cursor.moved = true;
-        }
-    }
-    if keys.just_pressed(KeyCode::E) {
-        if cursor.y < HEIGHT as f32 - 3.0 {
-            cursor.y += 1.0;
-            cursor.moved = true;
+    for d in Direction::ALL {
+        if !actions.just_pressed(Action::Move(d)) {
+            continue;
         }
-        if cursor.x < WIDTH as f32 - 2.0 {
-            cursor.x += 1.0;
-            cursor.moved = true;
-        }
-    }
-    if keys.just_pressed(KeyCode::X) && cursor.y > 0.0 {
-        cursor.y -= 1.0;
-        cursor.moved = true;
-    }
-    if keys.just_pressed(KeyCode::Z) {
-        if cursor.y > 0.0 {
-            cursor.y -= 1.0;
-            cursor.moved = true;
-        }
-        if cursor.x > 0.0 {
-            cursor.x -= 1.0;
-            cursor.moved = true;
-        }
-    }
-    if keys.just_pressed(KeyCode::C) {
-        if cursor.y > 0.0 {
-            cursor.y -= 1.0;
+        // At the edge of the board diagonal moves still go as far as they can along the other axis
+        let (dx, dy) = d.offset();
+        let x = cursor.x + f32::from(dx);
+        if dx != 0 && x >= 0.0 && x <= WIDTH as f32 - 2.0 {
+            cursor.x = x;
             cursor.moved = true;
         }
-        if cursor.x < WIDTH as f32 - 2.0 {
-            cursor.x += 1.0;
+        let y = cursor.y + f32::from(dy);
+        if dy != 0 && y >= 0.0 && y <= HEIGHT as f32 - 3.0 {
+            cursor.y = y;
             cursor.moved = true;
         }
     }
     if cursor.moved {
         cursor.hide_till_moved = false;
-        println!("SEND cursor moved event");
+        debug!("SEND cursor moved event");
         ev_cursor_moved.send(CursorMovedEvent(Vec2::new(cursor.x, cursor.y), previous_pos));
     }
 }
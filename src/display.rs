@@ -1,7 +1,10 @@
 use bevy::{prelude::*, math::{vec3, vec2}};
+use bevy::input::{keyboard::KeyboardInput, ButtonState};
+use std::collections::VecDeque;
 
-use crate::{game::Game, vec::Vec2I};
+use crate::{game::Game, gamestate::GameState, vec::Vec2I};
 use crate::constants::*;
+use crate::font::char_to_pos;
 const WIZARD_IDX: usize = 170;
 
 pub struct DisplayPlugin;
@@ -107,53 +110,137 @@ pub fn print_text(str: &str, commands: &mut Commands, fah: Handle<TextureAtlas>,
     entities
 }
 
-#[derive(Default, PartialEq, Eq)]
-pub enum BottomTextState {
-    #[default]
-    Cleared,
-    Set
+// Longest line which fits inside the border
+pub const LINE_LEN: usize = 29;
+
+pub fn wrap(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+    for word in text.split(' ') {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > LINE_LEN {
+            lines.push(line);
+            line = String::new();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    lines.push(line);
+    lines
+}
+
+// Padded to sit in the middle of the screen, for titles
+pub fn centred(text: &str) -> String {
+    let pad = LINE_LEN.saturating_sub(text.chars().count()) / 2;
+    format!("{}{text}", " ".repeat(pad))
 }
 
 #[derive(Component, Copy, Clone)]
 pub struct BottomText;
 
-#[derive(Debug, Deref)]
-pub struct BottomTextEvent(Option<String>);
+// How long an important message stays up before anything else is shown, unless a key is pressed
+const IMPORTANT_TIME: f32 = 1.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TextKind {
+    Normal,
+    Important,
+    Prompt,
+}
+
+#[derive(Debug)]
+pub struct BottomTextEvent {
+    text: Option<String>,
+    kind: TextKind,
+}
+
 impl BottomTextEvent {
     pub fn from(s: &str) -> Self {
         debug!("MAKE BOTTOM TEXT {}", s);
-        Self(Some(String::from(s)))
+        Self { text: Some(String::from(s)), kind: TextKind::Normal }
     }
     pub fn clear() -> Self {
         debug!("Generate clear event for bottom text");
-        Self(None)
+        Self { text: None, kind: TextKind::Normal }
+    }
+    // Stays up for at least IMPORTANT_TIME, queued behind any other important messages
+    pub fn important(s: &str) -> Self {
+        debug!("MAKE IMPORTANT BOTTOM TEXT {}", s);
+        Self { text: Some(String::from(s)), kind: TextKind::Important }
+    }
+    // Shown whenever there is nothing else to show, until the game state changes
+    pub fn prompt(s: &str) -> Self {
+        debug!("MAKE BOTTOM TEXT PROMPT {}", s);
+        Self { text: Some(String::from(s)), kind: TextKind::Prompt }
     }
 }
 
+#[derive(Default)]
+pub struct BottomTextQueue {
+    shown: Option<String>,
+    hold: Option<Timer>,
+    important: VecDeque<String>,
+    text: Option<String>,
+    prompt: Option<(String, GameState)>,
+}
+
+// Works out what the bottom line should say once all of this frame's messages are in, so
+// messages sent together by different systems don't fight over it
 pub fn manage_text_bottom(
     mut commands: Commands,
     game: Res<Game>,
     mut ev_text: EventReader<BottomTextEvent>,
     to_despawn: Query<Entity, With<BottomText>>,
-    mut state: Local<BottomTextState>,
+    mut queue: Local<BottomTextQueue>,
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+    mut ev_keys: EventReader<KeyboardInput>,
+    buttons: Res<Input<GamepadButton>>,
 ) {
+    let pressed = ev_keys.iter().any(|k| k.state == ButtonState::Pressed)
+        || buttons.get_just_pressed().next().is_some();
+    if let Some(timer) = queue.hold.as_mut() {
+        timer.tick(time.delta());
+        if pressed || timer.finished() {
+            queue.hold = None;
+        }
+    }
     for ev in ev_text.iter() {
         debug!("Bottom text event {:#?}", ev);
-        if *state == BottomTextState::Cleared {
-            debug!("Skipping clearing bottom text state as already cleared");
-        } else {
-            debug!("DESPAWN BOTTOM TEXT");
-            for entity in &to_despawn {
-                commands.entity(entity).despawn_recursive();
+        match ev.kind {
+            TextKind::Normal => queue.text.clone_from(&ev.text),
+            TextKind::Important => queue.important.extend(ev.text.clone()),
+            TextKind::Prompt => {
+                queue.text = None;
+                queue.prompt = ev.text.clone().map(|t| (t, state.0.clone()));
             }
-            *state = BottomTextState::Cleared;
-        }
-        if ev.is_some() {
-            debug!("PRINT NEW BOTTOM TEXT {}", ev.as_ref().unwrap());
-            print_text(ev.as_ref().unwrap(), &mut commands, game.fah(), vec2(0.0, -1.5), WHITE, BottomText);
-            *state = BottomTextState::Set;
         }
     }
+    if queue.prompt.as_ref().is_some_and(|(_, s)| *s != state.0) {
+        queue.prompt = None;
+    }
+    if queue.hold.is_some() {
+        return;
+    }
+    let next = if let Some(text) = queue.important.pop_front() {
+        queue.hold = Some(Timer::from_seconds(IMPORTANT_TIME, TimerMode::Once));
+        Some(text)
+    } else {
+        queue.text.clone().or_else(|| queue.prompt.as_ref().map(|(t, _)| t.clone()))
+    };
+    if next == queue.shown {
+        return;
+    }
+    debug!("DESPAWN BOTTOM TEXT");
+    for entity in &to_despawn {
+        commands.entity(entity).despawn_recursive();
+    }
+    if let Some(text) = &next {
+        debug!("PRINT NEW BOTTOM TEXT {}", text);
+        print_text(text, &mut commands, game.fah(), vec2(0.0, -1.5), WHITE, BottomText);
+    }
+    queue.shown = next;
 }
 
 pub fn print_wizard(commands: &mut Commands, tah: Handle<TextureAtlas>, v: Vec2, idx: usize, color: Color, component: impl Component + std::marker::Copy) {
@@ -161,17 +248,6 @@ pub fn print_wizard(commands: &mut Commands, tah: Handle<TextureAtlas>, v: Vec2,
     .insert(component);
 }
 
-fn char_to_pos(c: char) -> usize {
-    let d = c as u32;
-    if (33..=126).contains(&d) {
-        return (d - 31) as usize;
-    }
-    if c == ' ' {
-        return 1;
-    }
-    0
-}
-
 #[derive(Component)]
 pub struct Mortal {
     is_alive: bool
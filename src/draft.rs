@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+use crate::player::deal_spells;
+use crate::spell::{AllSpells, SpellBox};
+
+// Before a game played with the draft rules, the wizards take it in turns to pick spells from a
+// pool everyone can see, until each has as many as the rules deal. Each pick is replaced from a
+// hidden deck, dealt the same way as the normal hands so no spell turns up more often than usual.
+pub const POOL_SIZE: usize = 14;
+
+#[derive(Default, Resource)]
+pub struct Draft {
+    pub pool: Vec<SpellBox>,
+    deck: Vec<SpellBox>,
+    pub picks: usize,
+    // Who took which spell last, shown under the pool
+    pub last: Option<(usize, String)>,
+}
+
+impl Draft {
+    pub fn new(allspells: &AllSpells, players: usize, count: usize, rng: &mut impl Rng) -> Self {
+        // Two hands over so there's still a full pool for the last picks
+        let mut deck: Vec<SpellBox> = (0..players + 2)
+            .flat_map(|_| deal_spells(allspells, count, rng).into_iter().skip(1))
+            .collect();
+        deck.shuffle(rng);
+        let pool = deck.split_off(deck.len().saturating_sub(POOL_SIZE));
+        Self { pool, deck, picks: 0, last: None }
+    }
+    // Takes a spell out of the pool for the wizard whose pick it is
+    pub fn take(&mut self, i: usize, players: usize) -> SpellBox {
+        let player = drafter(self.picks, players);
+        let spell = match self.deck.pop() {
+            Some(next) => std::mem::replace(&mut self.pool[i], next),
+            None => self.pool.remove(i),
+        };
+        self.last = Some((player, spell.name()));
+        self.picks += 1;
+        spell
+    }
+    pub fn finished(&self, players: usize, count: usize) -> bool {
+        self.picks >= players * count || self.pool.is_empty()
+    }
+}
+
+// The order goes back and forth, so whoever picks first in a round picks last in the next
+pub const fn drafter(picks: usize, players: usize) -> usize {
+    let round = picks / players;
+    let place = picks % players;
+    if round.is_multiple_of(2) { place } else { players - 1 - place }
+}
+
+// Computer wizards take the strongest creature they're likely to cast, or a useful spell if
+// there's nothing much left
+pub fn cpu_pick(pool: &[SpellBox]) -> usize {
+    let value = |spell: &SpellBox| {
+        let strength = spell.creature().map_or(4, |c| {
+            u32::from(c.combat) + u32::from(c.defence) + u32::from(c.movement) / 2 + if c.flying { 2 } else { 0 }
+        });
+        strength * u32::from(spell.casting_chance())
+    };
+    (0..pool.len()).max_by_key(|i| value(&pool[*i])).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spell::load_all_spells;
+
+    #[test]
+    fn test_drafter() {
+        let order: Vec<usize> = (0..9).map(|p| drafter(p, 3)).collect();
+        assert_eq!(order, [0, 1, 2, 2, 1, 0, 0, 1, 2]);
+    }
+
+    #[test]
+    fn test_draft() {
+        let allspells = load_all_spells();
+        let mut draft = Draft::new(&allspells, 2, 13, &mut thread_rng());
+        assert_eq!(draft.pool.len(), POOL_SIZE);
+        assert!(draft.pool.iter().all(|s| s.name() != "Disbelieve"));
+        while !draft.finished(2, 13) {
+            draft.take(cpu_pick(&draft.pool), 2);
+        }
+        assert_eq!(draft.picks, 26);
+        assert_eq!(draft.pool.len(), POOL_SIZE);
+    }
+}
@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+use crate::board::GameBoard;
+use crate::constants::HIGHLIGHT_Z;
+use crate::game::Game;
+use crate::system::{BelongsToPlayer, BoardEntity};
+use crate::vec::Vec2I;
+
+// Fog of war: in games played with the fog rules, only the squares the pieces on the side whose
+// turn it is can see are shown, and pieces anywhere else are left off the board. It follows
+// whoever's turn it is, so in hot-seat games it goes best with the hand over screen.
+pub struct FogPlugin;
+
+impl Plugin for FogPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Fog>()
+            // After the turn's moves, so nothing shows for a frame before the fog catches up
+            .add_systems((
+                    update_fog.run_if(resource_changed::<GameBoard>().or_else(resource_changed::<Game>())),
+                    apply_fog,
+                ).chain().in_base_set(CoreSet::PostUpdate));
+    }
+}
+
+// How far every piece can see
+const SIGHT: u8 = 4;
+const FOG_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+
+// The squares that can be seen, None when there's no fog
+#[derive(Default, Resource)]
+pub struct Fog(Option<Vec<Vec2I>>);
+
+impl Fog {
+    pub fn hides(&self, pos: Vec2I) -> bool {
+        self.0.as_ref().is_some_and(|seen| !seen.contains(&pos))
+    }
+}
+
+// Darkens a square which can't be seen
+#[derive(Component)]
+struct FogSquare;
+
+fn update_fog(
+    g: Res<Game>,
+    board: Res<GameBoard>,
+    pieces: Query<(Entity, Option<&BelongsToPlayer>), With<BoardEntity>>,
+    mut fog: ResMut<Fog>,
+) {
+    if !g.rules.fog_of_war || g.round == 0 {
+        if fog.0.is_some() {
+            fog.0 = None;
+        }
+        return;
+    }
+    // Between turns there's nobody to see for, so it stays as it was
+    let Some(viewer) = g.player_info.get(g.player_turn as usize).and_then(|p| p.handle) else {
+        return;
+    };
+    let eyes: Vec<Vec2I> = pieces.iter()
+        .filter(|(e, owner)| g.allied(viewer, owner.map_or(*e, |o| o.player_entity)))
+        .filter_map(|(e, _)| board.entity_pos(e))
+        .collect();
+    fog.0 = Some(board.visible_from(&eyes, SIGHT));
+}
+
+// Only while the board is up. Everything on it is hidden while a menu or other screen is shown
+// instead, and then it's left alone.
+fn apply_fog(
+    mut commands: Commands,
+    fog: Res<Fog>,
+    board: Res<GameBoard>,
+    mut pieces: Query<(Entity, &mut Visibility), (With<BoardEntity>, Without<FogSquare>)>,
+    mut squares: Query<(Entity, &mut Visibility), With<FogSquare>>,
+) {
+    let shown = pieces.iter().any(|(_, vis)| *vis != Visibility::Hidden);
+    let wanted = |hidden: bool| if hidden { Visibility::Hidden } else { Visibility::Inherited };
+    if shown && fog.0.is_some() {
+        for (e, mut vis) in &mut pieces {
+            let hidden = board.entity_pos(e).is_some_and(|pos| fog.hides(pos));
+            if *vis != wanted(hidden) {
+                *vis = wanted(hidden);
+            }
+        }
+    }
+    if fog.is_changed() {
+        for (e, _) in &squares {
+            commands.entity(e).despawn();
+        }
+        for pos in GameBoard::squares().filter(|pos| fog.hides(*pos)) {
+            commands.spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: FOG_COLOR,
+                    custom_size: Some(Vec2::ONE),
+                    ..default()
+                },
+                transform: Transform::from_translation(Vec2::from(pos).extend(HIGHLIGHT_Z)),
+                visibility: wanted(!shown),
+                ..default()
+            }).insert(FogSquare);
+        }
+    } else {
+        for (_, mut vis) in &mut squares {
+            if *vis != wanted(!shown) {
+                *vis = wanted(!shown);
+            }
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureFormat};
+
+const GLYPH_WIDTH: usize = 8;
+const GLYPH_HEIGHT: usize = 16;
+const GLYPHS_PER_ROW: usize = 20;
+// Rows of glyphs in the sprite sheet itself, the extra characters go underneath
+const SHEET_ROWS: usize = 41;
+pub const FONT_ROWS: usize = SHEET_ROWS + EXTRA_COUNT.div_ceil(GLYPHS_PER_ROW);
+const FIRST_EXTRA: usize = SHEET_ROWS * GLYPHS_PER_ROW;
+// Capitals and lowercase letters sit on this row, the ones below are left for descenders
+const BASELINE: usize = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mark {
+    Grave,
+    Acute,
+    Circumflex,
+    Diaeresis,
+    Tilde,
+    Cedilla,
+    Inverted,
+    SharpS,
+}
+
+impl Mark {
+    fn rows(self) -> [&'static str; 2] {
+        match self {
+            Self::Grave => ["..##....", "...##..."],
+            Self::Acute => ["....##..", "...##..."],
+            Self::Circumflex => ["..###...", ".##.##.."],
+            Self::Diaeresis => [".##.##..", ".##.##.."],
+            Self::Tilde => [".###.##.", "##.###.."],
+            Self::Cedilla => ["...##...", "..##...."],
+            Self::Inverted | Self::SharpS => ["........", "........"],
+        }
+    }
+}
+
+// Accented letters are made from the plain letter in the sheet plus a mark
+const ACCENTED: [(Mark, &str, &str); 8] = [
+    (Mark::Grave, "àèìòùÀÈÌÒÙ", "aeiouAEIOU"),
+    (Mark::Acute, "áéíóúýÁÉÍÓÚÝ", "aeiouyAEIOUY"),
+    (Mark::Circumflex, "âêîôûÂÊÎÔÛ", "aeiouAEIOU"),
+    (Mark::Diaeresis, "äëïöüÿÄËÏÖÜ", "aeiouyAEIOU"),
+    (Mark::Tilde, "ãñõÃÑÕ", "anoANO"),
+    (Mark::Cedilla, "çÇ", "cC"),
+    (Mark::Inverted, "¡¿", "!?"),
+    (Mark::SharpS, "ß", "s"),
+];
+
+const EXTRA_COUNT: usize = 54;
+
+fn extra_chars() -> impl Iterator<Item = (char, char, Mark)> {
+    ACCENTED.iter().flat_map(|(mark, chars, bases)| {
+        chars.chars().zip(bases.chars()).map(|(c, base)| (c, base, *mark))
+    })
+}
+
+// Where a character is in the font atlas, 0 (blank) for anything it has no glyph for
+pub fn char_to_pos(c: char) -> usize {
+    let d = c as u32;
+    if (33..=126).contains(&d) {
+        return (d - 31) as usize;
+    }
+    if c == ' ' {
+        return 1;
+    }
+    extra_chars().position(|(extra, _, _)| extra == c).map_or(0, |i| FIRST_EXTRA + i)
+}
+
+// A glyph as rows of lit pixels
+type Glyph = [[bool; GLYPH_WIDTH]; GLYPH_HEIGHT];
+
+fn bitmap(rows: &[&str]) -> Vec<[bool; GLYPH_WIDTH]> {
+    rows.iter().map(|r| {
+        let mut row = [false; GLYPH_WIDTH];
+        for (x, c) in r.chars().enumerate() {
+            row[x] = c == '#';
+        }
+        row
+    }).collect()
+}
+
+fn sharp_s() -> Glyph {
+    let mut glyph = [[false; GLYPH_WIDTH]; GLYPH_HEIGHT];
+    let rows = bitmap(&[
+        "..###...", ".##.##..", ".##.##..", ".##.##..", ".####...",
+        ".##.##..", ".##..##.", ".##..##.", ".##.##..", ".####...", ".##.....",
+    ]);
+    glyph[..rows.len()].copy_from_slice(&rows);
+    glyph
+}
+
+fn accented(base: &Glyph, mark: Mark) -> Glyph {
+    let mut glyph = *base;
+    match mark {
+        Mark::Cedilla => {
+            for (i, row) in bitmap(&mark.rows()).into_iter().enumerate() {
+                glyph[BASELINE + 1 + i] = row;
+            }
+            return glyph;
+        }
+        Mark::Inverted => {
+            // Turned upside down about the middle of the letter
+            for y in 0..=BASELINE {
+                for x in 0..GLYPH_WIDTH - 1 {
+                    glyph[y][x] = base[BASELINE - y][GLYPH_WIDTH - 2 - x];
+                }
+            }
+            return glyph;
+        }
+        Mark::SharpS => return sharp_s(),
+        _ => {}
+    }
+    // The mark needs the top three rows, capitals give them up by losing repeated rows
+    let mut body: Vec<[bool; GLYPH_WIDTH]> = glyph[..=BASELINE].iter().copied()
+        .skip_while(|row| row.iter().all(|p| !p))
+        .collect();
+    // A dotless i for i with a mark
+    if body.len() == BASELINE + 1 && body[2].iter().all(|p| !p) {
+        body.drain(..4);
+    }
+    while body.len() > BASELINE - 2 {
+        let Some(i) = (1..body.len()).rev().find(|i| body[*i] == body[*i - 1]) else {
+            break;
+        };
+        body.remove(i);
+    }
+    // Rows below the baseline are kept for descenders
+    for row in &mut glyph[..=BASELINE] {
+        *row = [false; GLYPH_WIDTH];
+    }
+    let top = (BASELINE + 1).saturating_sub(body.len()).max(3);
+    for (i, row) in body.into_iter().enumerate() {
+        glyph[top + i] = row;
+    }
+    for (i, row) in bitmap(&mark.rows()).into_iter().enumerate() {
+        glyph[i] = row;
+    }
+    glyph
+}
+
+fn glyph_origin(index: usize) -> (usize, usize) {
+    ((index % GLYPHS_PER_ROW) * GLYPH_WIDTH, (index / GLYPHS_PER_ROW) * GLYPH_HEIGHT)
+}
+
+fn read_glyph(data: &[u8], width: usize, index: usize) -> Glyph {
+    let (x0, y0) = glyph_origin(index);
+    let mut glyph = [[false; GLYPH_WIDTH]; GLYPH_HEIGHT];
+    for (y, row) in glyph.iter_mut().enumerate() {
+        for (x, lit) in row.iter_mut().enumerate() {
+            let p = ((y0 + y) * width + x0 + x) * 4;
+            *lit = data[p..p + 3].iter().any(|v| *v > 0);
+        }
+    }
+    glyph
+}
+
+fn write_glyph(data: &mut [u8], width: usize, index: usize, glyph: &Glyph) {
+    let (x0, y0) = glyph_origin(index);
+    for (y, row) in glyph.iter().enumerate() {
+        for (x, lit) in row.iter().enumerate() {
+            let p = ((y0 + y) * width + x0 + x) * 4;
+            let v = if *lit { 255 } else { 0 };
+            data[p..p + 4].copy_from_slice(&[v, v, v, 255]);
+        }
+    }
+}
+
+// Adds the extra characters below the glyphs in the sprite sheet, an RGBA image 20 glyphs wide
+fn add_extra_glyphs(data: &mut Vec<u8>, width: usize) {
+    data.resize(width * FONT_ROWS * GLYPH_HEIGHT * 4, 0);
+    for (i, (_, base, mark)) in extra_chars().enumerate() {
+        let glyph = accented(&read_glyph(data, width, char_to_pos(base)), mark);
+        write_glyph(data, width, FIRST_EXTRA + i, &glyph);
+    }
+}
+
+// The sprite sheet with the extra characters added, used for both the sprites and the font.
+// None if the sheet can't be turned into RGBA, in which case the plain font is all there is.
+pub fn with_extra_glyphs(sheet: &Image) -> Option<Image> {
+    let mut image = sheet.convert(TextureFormat::Rgba8UnormSrgb)?;
+    let width = image.texture_descriptor.size.width as usize;
+    let mut data = std::mem::take(&mut image.data);
+    add_extra_glyphs(&mut data, width);
+    image.texture_descriptor.size = Extent3d {
+        width: width as u32,
+        height: (FONT_ROWS * GLYPH_HEIGHT) as u32,
+        depth_or_array_layers: 1,
+    };
+    image.data = data;
+    Some(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extra_chars() {
+        assert_eq!(extra_chars().count(), EXTRA_COUNT);
+        assert_eq!(char_to_pos('A'), 34);
+        assert_eq!(char_to_pos(' '), 1);
+        assert_eq!(char_to_pos('à'), FIRST_EXTRA);
+        assert_eq!(char_to_pos('ß'), FIRST_EXTRA + EXTRA_COUNT - 1);
+        assert_eq!(char_to_pos('€'), 0);
+    }
+
+    #[test]
+    fn test_accented() {
+        let e = sharp_s();
+        let acute = accented(&e, Mark::Acute);
+        assert_eq!(acute[0..2].to_vec(), bitmap(&Mark::Acute.rows()));
+        assert!(acute[2].iter().all(|p| !p));
+        // The letter keeps its bottom row on the baseline
+        assert_eq!(acute[BASELINE], e[BASELINE]);
+        let turned = accented(&e, Mark::Inverted);
+        let bottom: Vec<bool> = e[BASELINE][..GLYPH_WIDTH - 1].iter().rev().copied().collect();
+        assert_eq!(turned[0][..GLYPH_WIDTH - 1], bottom[..]);
+    }
+}
@@ -1,6 +1,13 @@
+use bevy::app::PluginGroupBuilder;
 use bevy::prelude::*;
+use rand::prelude::*;
+use crate::board::BoardPutEntity;
+use crate::locale::Locale;
+use crate::messagelog::LogEvent;
 use crate::player::Player;
+use crate::rules::Rules;
 use crate::constants::*;
+use crate::font;
 
 #[derive(Default, Resource)]
 pub struct Game {
@@ -10,6 +17,8 @@ pub struct Game {
     pub ai_level: u8,
     pub player_info: Vec<Player>,
     pub player_turn: u8,
+    pub round: u32,
+    pub rules: Rules,
 }
 
 impl Game {
@@ -25,6 +34,87 @@ impl Game {
     pub fn get_player_mut(&mut self) -> &mut Player {
         &mut self.player_info[self.player_turn as usize]
     }
+    // Dead wizards don't get a turn, player_turn goes past the last player once everyone has
+    pub fn next_player_turn(&mut self) {
+        self.player_turn += 1;
+        self.skip_dead_players();
+    }
+    pub fn first_player_turn(&mut self) {
+        self.player_turn = 0;
+        self.skip_dead_players();
+    }
+    fn skip_dead_players(&mut self) {
+        while self.player_info.get(self.player_turn as usize).is_some_and(|p| !p.alive) {
+            self.player_turn += 1;
+        }
+    }
+    // Everyone still alive has their alignment noted before the next round starts
+    pub fn end_round(&mut self) {
+        for player in self.player_info.iter_mut().filter(|p| p.alive) {
+            player.alignment.push(player.law_chaos.saturating_add(player.spells_law_chaos));
+        }
+        self.round += 1;
+    }
+    pub fn living_players(&self) -> usize {
+        self.player_info.iter().filter(|p| p.alive).count()
+    }
+    // A wizard without a team is a side of their own, and the game goes on while two sides are left
+    pub fn living_sides(&self) -> usize {
+        let mut teams: Vec<u8> = self.player_info.iter().filter(|p| p.alive).filter_map(|p| p.team).collect();
+        teams.sort_unstable();
+        teams.dedup();
+        teams.len() + self.player_info.iter().filter(|p| p.alive && p.team.is_none()).count()
+    }
+    // Whether pieces belonging to these two wizards are on the same side
+    pub fn allied(&self, a: Entity, b: Entity) -> bool {
+        let team = |e: Entity| self.player_info.iter().find(|p| p.handle == Some(e)).and_then(|p| p.team);
+        a == b || team(a).is_some_and(|t| team(b) == Some(t))
+    }
+    // Ready for the menu to set up another game
+    pub fn reset(&mut self) {
+        self.players = 0;
+        self.ai_level = 0;
+        self.player_info.clear();
+        self.player_turn = 0;
+        self.round = 0;
+        self.rules = Rules::default();
+    }
+}
+
+// Every dice roll in a game comes from here, so a challenge plays out the same from its seed
+#[derive(Resource)]
+pub struct GameRng(pub StdRng);
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self(StdRng::from_entropy())
+    }
+}
+
+impl GameRng {
+    pub fn seeded(seed: u64) -> Self {
+        Self(StdRng::seed_from_u64(seed))
+    }
+}
+
+// The wizards go on the board and the first round starts
+pub fn start_game(
+    g: &mut Game,
+    commands: &mut Commands,
+    ev_board_put: &mut EventWriter<BoardPutEntity>,
+    ev_log: &mut EventWriter<LogEvent>,
+    locale: &Locale,
+) {
+    let tah = g.tah();
+    let teams: Vec<Option<u8>> = g.player_info.iter().map(|p| p.team).collect();
+    let positions = crate::player::get_team_start_positions(&teams).unwrap();
+    for (i, p) in &mut g.player_info.iter_mut().enumerate() {
+        let pos = positions[i];
+        p.spawn(commands, tah.clone(), pos);
+        ev_board_put.send(BoardPutEntity { entity: p.handle.unwrap(), pos });
+    }
+    g.round = 1;
+    ev_log.send(LogEvent::game(g, &locale.format("log.start", &[&g.players])));
 }
 
 fn setup_game(
@@ -35,16 +125,71 @@ fn setup_game(
     let texture_handle = asset_server.load("sprite_sheet.png");
     let texture_atlas = TextureAtlas::from_grid(texture_handle.clone(), Vec2::new(SPRITE_SIZE as f32, SPRITE_SIZE as f32), 10, 41, None, None);
     game.tah = texture_atlases.add(texture_atlas);
-    let font_atlas = TextureAtlas::from_grid(texture_handle, Vec2::new((SPRITE_SIZE/2) as f32, SPRITE_SIZE as f32), 20, 41, None, None);
+    let font_atlas = TextureAtlas::from_grid(texture_handle, Vec2::new((SPRITE_SIZE/2) as f32, SPRITE_SIZE as f32), 20, font::FONT_ROWS, None, None);
     game.fah = texture_atlases.add(font_atlas);
 }
 
+// The accented characters are drawn under the sprite sheet as soon as it has loaded
+fn add_font_glyphs(
+    mut ev_image: EventReader<AssetEvent<Image>>,
+    mut images: ResMut<Assets<Image>>,
+    texture_atlases: Res<Assets<TextureAtlas>>,
+    game: Res<Game>,
+) {
+    let Some(sheet) = texture_atlases.get(&game.fah).map(|atlas| &atlas.texture) else {
+        return;
+    };
+    for ev in &mut ev_image {
+        if let AssetEvent::Created { handle } = ev {
+            if handle != sheet {
+                continue;
+            }
+            if let Some(image) = images.get_mut(handle) {
+                if let Some(extended) = font::with_extra_glyphs(image) {
+                    *image = extended;
+                } else {
+                    warn!("Sprite sheet isn't RGBA, accented characters won't show");
+                }
+            }
+        }
+    }
+}
+
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app
             .init_resource::<Game>()
-            .add_startup_system(setup_game);
+            .init_resource::<GameRng>()
+            .add_startup_system(setup_game)
+            .add_system(add_font_glyphs);
+    }
+}
+
+// Everything the game needs bar the window, sound and input devices, so it can be played
+// without them by the tournament
+pub struct GamePlugins;
+
+impl PluginGroup for GamePlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(GamePlugin)
+            .add(crate::screen::ScreenPlugin)
+            .add(crate::board::BoardPlugin)
+            .add(crate::cursor::CursorPlugin)
+            .add(crate::display::DisplayPlugin)
+            .add(crate::highlight::HighlightPlugin)
+            .add(crate::fog::FogPlugin)
+            .add(crate::messagelog::MessageLogPlugin)
+            .add(crate::clock::ClockPlugin)
+            .add(crate::challenge::ChallengePlugin)
+            .add(crate::campaign::CampaignPlugin)
+            .add(crate::settings::SettingsPlugin)
+            .add(crate::locale::LocalePlugin)
+            .add(crate::palette::PalettePlugin)
+            .add(crate::profile::ProfilePlugin)
+            .add(crate::input::InputPlugin)
+            .add(crate::gamepad::GamepadPlugin)
     }
 }
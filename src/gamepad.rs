@@ -0,0 +1,262 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use crate::gamestate::GameState;
+use crate::input::{tap, keyboard_to_actions, Action, Direction};
+
+// How long a direction has to be held before it starts repeating, and the repeat rate after that
+const REPEAT_DELAY: f32 = 0.35;
+const REPEAT_RATE: f32 = 0.15;
+const STICK_DEADZONE: f32 = 0.5;
+
+// Font glyphs have an opaque background, so the highlight is drawn over the text, not behind it
+const HIGHLIGHT_Z: f32 = 0.5;
+const HIGHLIGHT_COLOR: Color = Color::rgba(0.0, 0.0, 1.0, 0.5);
+
+pub struct GamepadPlugin;
+
+impl Plugin for GamepadPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<PadMenu>()
+            .init_resource::<PadDirection>()
+            .add_system(gamepad_input.in_base_set(CoreSet::PreUpdate).after(keyboard_to_actions))
+            .add_system(draw_pad_menu);
+    }
+}
+
+// A gamepad has no letters or numbers, so screens which are driven by typing a key register the
+// keys they accept along with where they are printed. The D-pad then moves a highlight between
+// them and the South button 'types' the highlighted one.
+#[derive(Clone, Copy, Debug)]
+pub struct PadMenuItem {
+    pub pos: Vec2,
+    pub len: usize,
+    pub key: char,
+}
+
+impl PadMenuItem {
+    pub fn new(pos: Vec2, len: usize, key: char) -> Self {
+        Self { pos, len, key }
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct PadMenu {
+    state: Option<GameState>,
+    items: Vec<PadMenuItem>,
+    selected: usize,
+}
+
+impl PadMenu {
+    pub fn set(&mut self, state: GameState, items: Vec<PadMenuItem>) {
+        self.state = Some(state);
+        self.items = items;
+        self.selected = 0;
+    }
+    pub fn clear(&mut self) {
+        self.state = None;
+        self.items.clear();
+        self.selected = 0;
+    }
+    pub fn active_in(&self, state: &GameState) -> bool {
+        self.state.as_ref() == Some(state) && !self.items.is_empty()
+    }
+    fn selected_item(&self) -> PadMenuItem {
+        self.items[self.selected]
+    }
+    // Move to the closest item in the direction pressed, preferring items in a straight line
+    fn navigate(&mut self, dir: Vec2) {
+        let from = self.selected_item().pos;
+        let mut best: Option<(usize, f32)> = None;
+        for (i, item) in self.items.iter().enumerate() {
+            let offset = item.pos - from;
+            let along = offset.dot(dir);
+            if along <= 0.0 {
+                continue;
+            }
+            let across = offset.perp_dot(dir).abs();
+            let score = across.mul_add(3.0, along);
+            if best.is_none_or(|(_, s)| score < s) {
+                best = Some((i, score));
+            }
+        }
+        if let Some((i, _)) = best {
+            self.selected = i;
+        }
+    }
+}
+
+#[derive(Default, Resource)]
+struct PadDirection {
+    current: Option<IVec2>,
+    repeat: Option<Timer>,
+}
+
+// Menu keys which are game actions rather than plain menu choices
+fn char_to_action(c: char) -> Option<Action> {
+    match c.to_ascii_uppercase() {
+        'H' => Some(Action::Help),
+        'N' => Some(Action::No),
+        'Y' => Some(Action::Yes),
+        _ => None,
+    }
+}
+
+fn char_to_key(c: char) -> Option<KeyCode> {
+    Some(match c.to_ascii_uppercase() {
+        '0' => KeyCode::Key0,
+        '1' => KeyCode::Key1,
+        '2' => KeyCode::Key2,
+        '3' => KeyCode::Key3,
+        '4' => KeyCode::Key4,
+        '5' => KeyCode::Key5,
+        '6' => KeyCode::Key6,
+        '7' => KeyCode::Key7,
+        '8' => KeyCode::Key8,
+        '9' => KeyCode::Key9,
+        'R' => KeyCode::R,
+        '\r' => KeyCode::Return,
+        '\u{7f}' => KeyCode::Back,
+        '<' => KeyCode::Left,
+        '>' => KeyCode::Right,
+        _ => return None,
+    })
+}
+
+fn read_direction(gamepad: Gamepad, buttons: &Input<GamepadButton>, axes: &Axis<GamepadAxis>) -> IVec2 {
+    let mut dir = IVec2::ZERO;
+    if buttons.pressed(GamepadButton::new(gamepad, GamepadButtonType::DPadLeft)) {
+        dir.x -= 1;
+    }
+    if buttons.pressed(GamepadButton::new(gamepad, GamepadButtonType::DPadRight)) {
+        dir.x += 1;
+    }
+    if buttons.pressed(GamepadButton::new(gamepad, GamepadButtonType::DPadUp)) {
+        dir.y += 1;
+    }
+    if buttons.pressed(GamepadButton::new(gamepad, GamepadButtonType::DPadDown)) {
+        dir.y -= 1;
+    }
+    if dir == IVec2::ZERO {
+        let x = axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX)).unwrap_or(0.0);
+        let y = axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY)).unwrap_or(0.0);
+        if Vec2::new(x, y).length() > STICK_DEADZONE {
+            // Snap the stick to the nearest of the 8 directions
+            let angle = y.atan2(x);
+            let octant = (angle / std::f32::consts::FRAC_PI_4).round();
+            let snapped = Vec2::from_angle(octant * std::f32::consts::FRAC_PI_4);
+            dir = IVec2::new(snapped.x.round() as i32, snapped.y.round() as i32);
+        }
+    }
+    dir
+}
+
+fn gamepad_input(
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Input<Action>>,
+    mut chars: EventWriter<ReceivedCharacter>,
+    mut menu: ResMut<PadMenu>,
+    mut held: ResMut<PadDirection>,
+    window: Query<Entity, With<PrimaryWindow>>,
+) {
+    let Ok(window) = window.get_single() else {
+        return;
+    };
+    let menu_active = menu.active_in(&state.0);
+    let mut dir = IVec2::ZERO;
+    for gamepad in gamepads.iter() {
+        let pad_dir = read_direction(gamepad, &buttons, &axes);
+        if pad_dir != IVec2::ZERO {
+            dir = pad_dir;
+        }
+        if buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::South)) {
+            if menu_active {
+                let key = menu.selected_item().key;
+                if let Some(action) = char_to_action(key) {
+                    tap(&mut actions, action);
+                } else if let Some(keycode) = char_to_key(key) {
+                    tap(&mut keys, keycode);
+                }
+                chars.send(ReceivedCharacter { window, char: key });
+            } else {
+                // Screens which wait for 'any key' read either actions or characters
+                tap(&mut actions, Action::Select);
+                chars.send(ReceivedCharacter { window, char: ' ' });
+            }
+        }
+        for (button, action) in [
+            (GamepadButtonType::East, Action::Cancel),
+            (GamepadButtonType::North, Action::Info),
+            (GamepadButtonType::West, Action::Undo),
+            (GamepadButtonType::LeftTrigger, Action::Log),
+            (GamepadButtonType::Start, Action::EndTurn),
+            (GamepadButtonType::Select, Action::Help),
+        ] {
+            if buttons.just_pressed(GamepadButton::new(gamepad, button)) {
+                tap(&mut actions, action);
+            }
+        }
+    }
+
+    let mut fire = false;
+    if dir == IVec2::ZERO {
+        held.current = None;
+        held.repeat = None;
+    } else if held.current != Some(dir) {
+        held.current = Some(dir);
+        held.repeat = Some(Timer::from_seconds(REPEAT_DELAY, TimerMode::Once));
+        fire = true;
+    } else if let Some(timer) = held.repeat.as_mut() {
+        timer.tick(time.delta());
+        if timer.finished() {
+            *timer = Timer::from_seconds(REPEAT_RATE, TimerMode::Once);
+            fire = true;
+        }
+    }
+    if !fire {
+        return;
+    }
+    if menu_active {
+        menu.navigate(dir.as_vec2().normalize());
+    } else if let Some(d) = Direction::from_offset(dir.x, dir.y) {
+        tap(&mut actions, Action::Move(d));
+    }
+}
+
+#[derive(Component)]
+struct PadMenuHighlight;
+
+fn draw_pad_menu(
+    mut commands: Commands,
+    menu: Res<PadMenu>,
+    state: Res<State<GameState>>,
+    gamepads: Res<Gamepads>,
+    existing: Query<Entity, With<PadMenuHighlight>>,
+) {
+    if !menu.is_changed() && !state.is_changed() && !gamepads.is_changed() {
+        return;
+    }
+    for e in existing.iter() {
+        commands.entity(e).despawn();
+    }
+    if gamepads.iter().next().is_none() || !menu.active_in(&state.0) {
+        return;
+    }
+    let item = menu.selected_item();
+    let width = item.len as f32 / 2.0;
+    let center = Vec2::new(item.pos.x + (width - 0.5) / 2.0, item.pos.y);
+    commands.spawn(SpriteBundle {
+        sprite: Sprite {
+            color: HIGHLIGHT_COLOR,
+            custom_size: Some(Vec2::new(width, 1.0)),
+            ..default()
+        },
+        transform: Transform::from_translation(center.extend(HIGHLIGHT_Z)),
+        ..default()
+    }).insert(PadMenuHighlight);
+}
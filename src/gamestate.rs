@@ -7,20 +7,24 @@ pub enum GameState {
     InitialMenu,
     Help,
     HelpKeys,
+    HelpRebind,
     HelpSpells,
     HelpCombat,
     HelpRangedCombat,
     HelpUndead,
     HelpMounts,
     HelpVictory,
+    Options,
     PlayerNameMenu,
     PlayerNameMenuTransition,
+    PlayerSetup,
     TurnMenu,
     TurnMenuExamineSpell,
     TurnMenuExamineOneSpell,
     TurnMenuSelectSpell,
     TurnMenuExamineBoard,
     TurnMenuTransition,
+    PassTurn,
     CastSpellSetup,
     CastSpell,
     MoveSetup,
@@ -29,5 +33,18 @@ pub enum GameState {
     AttackDo,
     RangedAttackChoose,
     RangedAttackDo,
+    MessageLog,
+    GameOver,
+    GameStats,
+    HallOfFame,
+    Editor,
+    EditorWizard,
+    EditorSave,
+    Challenges,
+    ChallengeStart,
+    ChallengeOver,
+    CampaignMap,
+    CampaignStart,
+    Draft,
 }
 
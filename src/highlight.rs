@@ -0,0 +1,52 @@
+use bevy::prelude::*;
+use crate::constants::HIGHLIGHT_Z;
+use crate::vec::Vec2I;
+
+pub const MOVE_COLOR: Color = Color::rgba(0.0, 1.0, 0.0, 0.3);
+pub const ATTACK_COLOR: Color = Color::rgba(1.0, 0.0, 0.0, 0.4);
+pub const TARGET_COLOR: Color = Color::rgba(1.0, 1.0, 0.0, 0.3);
+
+pub struct HighlightPlugin;
+
+impl Plugin for HighlightPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<HighlightEvent>()
+            .add_system(highlight_squares);
+    }
+}
+
+#[derive(Component)]
+struct Highlight;
+
+// Tint squares on the board, replacing any squares tinted before
+pub enum HighlightEvent {
+    Show(Vec<(Vec2I, Color)>),
+    Clear,
+}
+
+fn highlight_squares(
+    mut commands: Commands,
+    mut ev: EventReader<HighlightEvent>,
+    existing: Query<Entity, With<Highlight>>,
+) {
+    let Some(last) = ev.iter().last() else {
+        return;
+    };
+    for e in existing.iter() {
+        commands.entity(e).despawn();
+    }
+    if let HighlightEvent::Show(squares) = last {
+        for (pos, color) in squares {
+            commands.spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: *color,
+                    custom_size: Some(Vec2::ONE),
+                    ..default()
+                },
+                transform: Transform::from_translation(Vec2::from(*pos).extend(HIGHLIGHT_Z)),
+                ..default()
+            }).insert(Highlight);
+        }
+    }
+}
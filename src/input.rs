@@ -0,0 +1,317 @@
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::settings::Settings;
+
+pub struct InputPlugin;
+
+impl Plugin for InputPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Input<Action>>()
+            .add_system(keyboard_to_actions.in_base_set(CoreSet::PreUpdate).after(InputSystem));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Direction {
+    UpLeft,
+    Up,
+    UpRight,
+    Left,
+    Right,
+    DownLeft,
+    Down,
+    DownRight,
+}
+
+impl Direction {
+    pub const ALL: [Self; 8] = [
+        Self::UpLeft,
+        Self::Up,
+        Self::UpRight,
+        Self::Left,
+        Self::Right,
+        Self::DownLeft,
+        Self::Down,
+        Self::DownRight,
+    ];
+    pub fn offset(self) -> (i8, i8) {
+        match self {
+            Self::UpLeft => (-1, 1),
+            Self::Up => (0, 1),
+            Self::UpRight => (1, 1),
+            Self::Left => (-1, 0),
+            Self::Right => (1, 0),
+            Self::DownLeft => (-1, -1),
+            Self::Down => (0, -1),
+            Self::DownRight => (1, -1),
+        }
+    }
+    pub fn from_offset(x: i32, y: i32) -> Option<Self> {
+        Self::ALL.into_iter().find(|d| d.offset() == (x as i8, y as i8))
+    }
+    // Looked up in the locale
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::UpLeft => "action.up_left",
+            Self::Up => "action.up",
+            Self::UpRight => "action.up_right",
+            Self::Left => "action.left",
+            Self::Right => "action.right",
+            Self::DownLeft => "action.down_left",
+            Self::Down => "action.down",
+            Self::DownRight => "action.down_right",
+        }
+    }
+}
+
+// Everything the game responds to, whatever key or button produced it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    Move(Direction),
+    Select,
+    Cancel,
+    EndTurn,
+    Info,
+    Help,
+    Yes,
+    No,
+    Undo,
+    Log,
+    // Leaving a menu or screen for the one before
+    Back,
+}
+
+impl Action {
+    // In the order they are listed on the keys help screen and asked for when rebinding
+    pub const ALL: [Self; 18] = [
+        Self::Move(Direction::UpLeft),
+        Self::Move(Direction::Up),
+        Self::Move(Direction::UpRight),
+        Self::Move(Direction::Left),
+        Self::Move(Direction::Right),
+        Self::Move(Direction::DownLeft),
+        Self::Move(Direction::Down),
+        Self::Move(Direction::DownRight),
+        Self::Select,
+        Self::Cancel,
+        Self::EndTurn,
+        Self::Info,
+        Self::Help,
+        Self::Yes,
+        Self::No,
+        Self::Undo,
+        Self::Log,
+        Self::Back,
+    ];
+    // Looked up in the locale
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Move(d) => d.name(),
+            Self::Select => "action.select",
+            Self::Cancel => "action.cancel",
+            Self::EndTurn => "action.end_turn",
+            Self::Info => "action.info",
+            Self::Help => "action.help",
+            Self::Yes => "action.yes",
+            Self::No => "action.no",
+            Self::Undo => "action.undo",
+            Self::Log => "action.log",
+            Self::Back => "action.back",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Binding {
+    #[serde(with = "key_name")]
+    pub key: KeyCode,
+    pub action: Action,
+}
+
+impl Binding {
+    const fn new(key: KeyCode, action: Action) -> Self {
+        Self { key, action }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Preset {
+    Classic,
+    Arrows,
+    Numpad,
+}
+
+const COMMON_BINDINGS: [Binding; 8] = [
+    Binding::new(KeyCode::Key0, Action::EndTurn),
+    Binding::new(KeyCode::Key0, Action::Back),
+    Binding::new(KeyCode::I, Action::Info),
+    Binding::new(KeyCode::H, Action::Help),
+    Binding::new(KeyCode::Y, Action::Yes),
+    Binding::new(KeyCode::N, Action::No),
+    Binding::new(KeyCode::U, Action::Undo),
+    Binding::new(KeyCode::L, Action::Log),
+];
+
+impl Preset {
+    pub fn bindings(self) -> Vec<Binding> {
+        let keys = match self {
+            Self::Classic => [
+                KeyCode::Q, KeyCode::W, KeyCode::E,
+                KeyCode::A, KeyCode::D,
+                KeyCode::Z, KeyCode::X, KeyCode::C,
+                KeyCode::S, KeyCode::K,
+            ],
+            Self::Arrows => [
+                KeyCode::Home, KeyCode::Up, KeyCode::PageUp,
+                KeyCode::Left, KeyCode::Right,
+                KeyCode::End, KeyCode::Down, KeyCode::PageDown,
+                KeyCode::Return, KeyCode::Back,
+            ],
+            Self::Numpad => [
+                KeyCode::Numpad7, KeyCode::Numpad8, KeyCode::Numpad9,
+                KeyCode::Numpad4, KeyCode::Numpad6,
+                KeyCode::Numpad1, KeyCode::Numpad2, KeyCode::Numpad3,
+                KeyCode::Numpad5, KeyCode::NumpadDecimal,
+            ],
+        };
+        let mut bindings: Vec<Binding> = keys.iter().zip(Action::ALL.iter())
+            .map(|(key, action)| Binding::new(*key, *action))
+            .collect();
+        bindings.extend_from_slice(&COMMON_BINDINGS);
+        bindings
+    }
+}
+
+pub fn default_bindings() -> Vec<Binding> {
+    Preset::Classic.bindings()
+}
+
+// Settings saved by an older version won't have keys for actions added since, give them the defaults
+pub fn add_missing_bindings(bindings: &mut Vec<Binding>) {
+    for default in default_bindings() {
+        if !bindings.iter().any(|b| b.action == default.action) {
+            bindings.push(default);
+        }
+    }
+}
+
+// Names of the keys which can be bound, used both for the settings file and the keys help screen
+const KEY_NAMES: [(KeyCode, &str); 72] = [
+    (KeyCode::A, "A"), (KeyCode::B, "B"), (KeyCode::C, "C"), (KeyCode::D, "D"),
+    (KeyCode::E, "E"), (KeyCode::F, "F"), (KeyCode::G, "G"), (KeyCode::H, "H"),
+    (KeyCode::I, "I"), (KeyCode::J, "J"), (KeyCode::K, "K"), (KeyCode::L, "L"),
+    (KeyCode::M, "M"), (KeyCode::N, "N"), (KeyCode::O, "O"), (KeyCode::P, "P"),
+    (KeyCode::Q, "Q"), (KeyCode::R, "R"), (KeyCode::S, "S"), (KeyCode::T, "T"),
+    (KeyCode::U, "U"), (KeyCode::V, "V"), (KeyCode::W, "W"), (KeyCode::X, "X"),
+    (KeyCode::Y, "Y"), (KeyCode::Z, "Z"),
+    (KeyCode::Key0, "0"), (KeyCode::Key1, "1"), (KeyCode::Key2, "2"), (KeyCode::Key3, "3"),
+    (KeyCode::Key4, "4"), (KeyCode::Key5, "5"), (KeyCode::Key6, "6"), (KeyCode::Key7, "7"),
+    (KeyCode::Key8, "8"), (KeyCode::Key9, "9"),
+    (KeyCode::Numpad0, "Num0"), (KeyCode::Numpad1, "Num1"), (KeyCode::Numpad2, "Num2"),
+    (KeyCode::Numpad3, "Num3"), (KeyCode::Numpad4, "Num4"), (KeyCode::Numpad5, "Num5"),
+    (KeyCode::Numpad6, "Num6"), (KeyCode::Numpad7, "Num7"), (KeyCode::Numpad8, "Num8"),
+    (KeyCode::Numpad9, "Num9"), (KeyCode::NumpadDecimal, "Num."), (KeyCode::NumpadEnter, "NumEnter"),
+    (KeyCode::NumpadAdd, "Num+"), (KeyCode::NumpadSubtract, "Num-"),
+    (KeyCode::NumpadMultiply, "Num*"), (KeyCode::NumpadDivide, "Num/"),
+    (KeyCode::Up, "Up"), (KeyCode::Down, "Down"), (KeyCode::Left, "Left"), (KeyCode::Right, "Right"),
+    (KeyCode::Home, "Home"), (KeyCode::End, "End"), (KeyCode::PageUp, "PgUp"), (KeyCode::PageDown, "PgDn"),
+    (KeyCode::Insert, "Ins"), (KeyCode::Delete, "Del"), (KeyCode::Back, "Bksp"), (KeyCode::Return, "Enter"),
+    (KeyCode::Space, "Space"), (KeyCode::Tab, "Tab"),
+    (KeyCode::Comma, ","), (KeyCode::Period, "."), (KeyCode::Slash, "/"), (KeyCode::Semicolon, ";"),
+    (KeyCode::Minus, "-"), (KeyCode::Equals, "="),
+];
+
+pub fn key_name(key: KeyCode) -> Option<&'static str> {
+    KEY_NAMES.iter().find(|(k, _)| *k == key).map(|(_, name)| *name)
+}
+
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    KEY_NAMES.iter().find(|(_, n)| *n == name).map(|(k, _)| *k)
+}
+
+mod key_name {
+    use bevy::prelude::KeyCode;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    // serde's `with` requires the value by reference
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn serialize<S: Serializer>(key: &KeyCode, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(super::key_name(*key).unwrap_or("?"))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<KeyCode, D::Error> {
+        let name = String::deserialize(d)?;
+        super::key_from_name(&name).ok_or_else(|| D::Error::custom(format!("unknown key {name}")))
+    }
+}
+
+// Names of all the keys bound to an action, e.g. "S" or "Num5/Enter"
+pub fn action_keys(settings: &Settings, action: Action) -> String {
+    settings.key_bindings.iter()
+        .filter(|b| b.action == action)
+        .filter_map(|b| key_name(b.key))
+        .collect::<Vec<&str>>()
+        .join("/")
+}
+
+// Lets a key or button press through for screens which wait for any input to continue
+pub fn any_just_pressed(keys: &Input<KeyCode>, actions: &Input<Action>) -> bool {
+    keys.get_just_pressed().next().is_some() || actions.get_just_pressed().next().is_some()
+}
+
+pub fn tap<T: Copy + Eq + std::hash::Hash + Send + Sync + 'static>(input: &mut Input<T>, t: T) {
+    // Pressing and releasing in the same frame leaves it just_pressed for this frame only
+    input.press(t);
+    input.release(t);
+}
+
+pub fn keyboard_to_actions(
+    keys: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+    mut actions: ResMut<Input<Action>>,
+) {
+    actions.clear();
+    for binding in &settings.key_bindings {
+        if keys.just_pressed(binding.key) {
+            actions.press(binding.action);
+        }
+        if keys.just_released(binding.key) {
+            actions.release(binding.action);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::KeyCode;
+    use super::{key_from_name, key_name, Action, Direction, Preset};
+
+    #[test]
+    fn key_names_round_trip() {
+        for key in [KeyCode::A, KeyCode::Key0, KeyCode::Numpad7, KeyCode::PageDown, KeyCode::Return] {
+            assert_eq!(key_from_name(key_name(key).unwrap()), Some(key));
+        }
+        assert_eq!(key_from_name("Nope"), None);
+    }
+
+    #[test]
+    fn presets_bind_every_action() {
+        for preset in [Preset::Classic, Preset::Arrows, Preset::Numpad] {
+            let bindings = preset.bindings();
+            for action in Action::ALL {
+                assert!(bindings.iter().any(|b| b.action == action), "{preset:?} missing {action:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn direction_offsets() {
+        for d in Direction::ALL {
+            let (x, y) = d.offset();
+            assert_eq!(Direction::from_offset(i32::from(x), i32::from(y)), Some(d));
+        }
+        assert_eq!(Direction::from_offset(0, 0), None);
+    }
+}
@@ -0,0 +1,155 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use std::fmt::Display;
+use std::fs::File;
+use crate::settings::Settings;
+
+const LOCALE_DIR: &str = "assets/locale";
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+pub struct LocalePlugin;
+
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        let language = app.world.get_resource::<Settings>()
+            .map_or_else(|| DEFAULT_LANGUAGE.to_string(), |s| s.language.clone());
+        app
+            .insert_resource(Locale::load(&language))
+            .add_system(change_language.run_if(resource_changed::<Settings>()));
+    }
+}
+
+// All the text shown to players, in the chosen language. Anything missing from a
+// translation is shown in English instead.
+#[derive(Resource)]
+pub struct Locale {
+    pub language: String,
+    strings: HashMap<String, String>,
+    english: HashMap<String, String>,
+}
+
+fn read_strings(language: &str) -> HashMap<String, String> {
+    let path = format!("{LOCALE_DIR}/{language}.ron");
+    let Ok(f) = File::open(&path) else {
+        warn!("No locale file {path}");
+        return HashMap::default();
+    };
+    ron::de::from_reader(f).unwrap_or_else(|e| {
+        warn!("Ignoring unreadable {path}: {e}");
+        HashMap::default()
+    })
+}
+
+impl Locale {
+    pub fn load(language: &str) -> Self {
+        info!("Loading language {language}");
+        Self {
+            language: language.to_string(),
+            strings: read_strings(language),
+            english: read_strings(DEFAULT_LANGUAGE),
+        }
+    }
+    pub fn text<'a>(&'a self, key: &'a str) -> &'a str {
+        self.strings.get(key)
+            .or_else(|| self.english.get(key))
+            .map_or(key, String::as_str)
+    }
+    // The text with each {} filled in from args in turn
+    pub fn format(&self, key: &str, args: &[&dyn Display]) -> String {
+        let mut args = args.iter();
+        let mut parts = self.text(key).split("{}");
+        let mut text = parts.next().unwrap_or_default().to_string();
+        for part in parts {
+            if let Some(arg) = args.next() {
+                text.push_str(&arg.to_string());
+            }
+            text.push_str(part);
+        }
+        text
+    }
+    // Creatures and spells are named in English in the game data
+    pub fn name<'a>(&'a self, name: &'a str) -> &'a str {
+        self.strings.get(&format!("name.{name}")).map_or(name, String::as_str)
+    }
+}
+
+// Every language there is a locale file for, in order
+pub fn languages() -> Vec<String> {
+    let Ok(dir) = std::fs::read_dir(LOCALE_DIR) else {
+        return vec![DEFAULT_LANGUAGE.to_string()];
+    };
+    let mut languages: Vec<String> = dir
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if !path.extension()?.eq_ignore_ascii_case("ron") {
+                return None;
+            }
+            path.file_stem()?.to_str().map(String::from)
+        })
+        .collect();
+    // Built in, so it can always be picked again whatever is in the directory
+    if !languages.iter().any(|l| l == DEFAULT_LANGUAGE) {
+        languages.push(DEFAULT_LANGUAGE.to_string());
+    }
+    languages.sort();
+    languages
+}
+
+fn change_language(
+    settings: Res<Settings>,
+    mut locale: ResMut<Locale>,
+) {
+    if settings.language != locale.language {
+        *locale = Locale::load(&settings.language);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::{wrap, LINE_LEN};
+    use crate::font::char_to_pos;
+
+    #[test]
+    fn test_format() {
+        let mut locale = Locale::load(DEFAULT_LANGUAGE);
+        locale.strings.insert("test".to_string(), "{} killed {}".to_string());
+        assert_eq!(locale.format("test", &[&"Orc", &"Elf"]), "Orc killed Elf");
+        assert_eq!(locale.format("test", &[&"Orc"]), "Orc killed ");
+        assert_eq!(locale.format("missing {}", &[&3]), "missing 3");
+        assert_eq!(locale.name("Gooey Blob"), "Gooey Blob");
+    }
+
+    // Every language should have every piece of text, and nothing the game no longer uses
+    #[test]
+    fn test_languages_complete() {
+        let english = read_strings(DEFAULT_LANGUAGE);
+        assert!(!english.is_empty());
+        for language in languages() {
+            let strings = read_strings(&language);
+            for key in english.keys() {
+                assert!(strings.contains_key(key), "{language} is missing {key}");
+            }
+            for key in strings.keys().filter(|k| !k.starts_with("name.")) {
+                assert!(english.contains_key(key), "{language} has unknown {key}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_languages_fit() {
+        for language in languages() {
+            for (key, text) in read_strings(&language) {
+                let missing: String = text.chars().filter(|c| *c != '\n' && char_to_pos(*c) == 0).collect();
+                assert!(missing.is_empty(), "{language} {key} has characters without glyphs: {missing}");
+                if key.starts_with("help.") && key.ends_with("text") {
+                    let lines = text.split('\n').flat_map(wrap).count();
+                    assert!(lines <= 9, "{language} {key} is {lines} lines long");
+                } else if ["menu.", "help.", "turn.", "options.", "creature."].iter().any(|p| key.starts_with(p)) {
+                    // Lines on the menu screens, inside the border
+                    assert!(text.replace("{}", "0").chars().count() <= LINE_LEN, "{language} {key} is too long");
+                }
+            }
+        }
+    }
+}
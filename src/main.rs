@@ -10,7 +10,9 @@ mod vec;
 mod screen;
 mod game;
 mod display;
+mod font;
 mod player;
+mod rules;
 mod spell;
 mod cursor;
 mod creature;
@@ -18,13 +20,45 @@ mod constants;
 mod system;
 mod gamestate;
 mod board;
+mod gamepad;
+mod highlight;
+mod fog;
+mod input;
+mod settings;
+mod locale;
+mod messagelog;
+mod clock;
+mod sound;
+mod screensize;
+mod palette;
+mod profile;
+mod ai;
+mod tournament;
+mod balance;
+mod scenario;
+mod challenge;
+mod campaign;
+mod draft;
 
 use crate::spell::load_all_spells;
-use crate::game::Game;
 use crate::constants::*;
 use crate::gamestate::GameState;
 
+fn or_exit<T>(parsed: Result<T, String>) -> T {
+    parsed.unwrap_or_else(|e| {
+        eprintln!("{e}");
+        std::process::exit(2);
+    })
+}
+
 fn main() {
+    // Tools that run without a window
+    if let Some(config) = tournament::Config::from_args(std::env::args().skip(1)) {
+        return tournament::run(or_exit(config));
+    }
+    if let Some(config) = balance::Config::from_args(std::env::args().skip(1)) {
+        return balance::run(&or_exit(config));
+    }
     App::new()
         .add_plugins(
             DefaultPlugins.set(
@@ -41,14 +75,12 @@ fn main() {
             .set(ImagePlugin::default_nearest())
             .set(LogPlugin {level: bevy::log::Level::DEBUG, ..default()})
         )
-        .add_plugin(game::GamePlugin)
         .insert_resource(ClearColor(Color::rgb(0.0, 0.0, 0.0)))
         .insert_resource(load_all_spells())
         .add_state::<GameState>()
-        .add_plugin(screen::ScreenPlugin)
-        .add_plugin(board::BoardPlugin)
-        .add_plugin(cursor::CursorPlugin)
-        .add_plugin(display::DisplayPlugin)
-        .add_system(bevy::window::close_on_esc)
+        .add_plugins(game::GamePlugins)
+        .add_plugin(sound::SoundPlugin)
+        .add_plugin(screensize::ScreenSizePlugin)
+        .add_system(bevy::window::close_on_esc.run_if(screen::not_rebinding))
         .run();
 }
\ No newline at end of file
@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+use crate::game::Game;
+
+pub struct MessageLogPlugin;
+
+impl Plugin for MessageLogPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<MessageLog>()
+            .add_event::<LogEvent>()
+            .add_system(record_log);
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LogEntry {
+    pub round: u32,
+    pub player: Option<String>,
+    pub text: String,
+}
+
+// Everything that has happened this game which the players should be able to look back at
+#[derive(Default, Resource)]
+pub struct MessageLog {
+    pub entries: Vec<LogEntry>,
+}
+
+// The round and player are taken when the event is made, as the turn may have moved on by the
+// time the log is written
+pub struct LogEvent(LogEntry);
+
+impl LogEvent {
+    // Something the current player did
+    pub fn player(g: &Game, text: &str) -> Self {
+        Self(LogEntry {
+            round: g.round,
+            player: Some(g.get_player().name.clone()),
+            text: String::from(text),
+        })
+    }
+    pub fn game(g: &Game, text: &str) -> Self {
+        Self(LogEntry {
+            round: g.round,
+            player: None,
+            text: String::from(text),
+        })
+    }
+}
+
+fn record_log(
+    mut ev_log: EventReader<LogEvent>,
+    mut log: ResMut<MessageLog>,
+) {
+    for ev in ev_log.iter() {
+        info!("Log: round {} {:?} {}", ev.0.round, ev.0.player, ev.0.text);
+        log.entries.push(ev.0.clone());
+    }
+}
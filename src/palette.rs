@@ -0,0 +1,211 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::display::*;
+use crate::font::char_to_pos;
+use crate::game::Game;
+use crate::highlight::{ATTACK_COLOR, MOVE_COLOR};
+use crate::settings::Settings;
+use crate::system::{BelongsToPlayer, BoardEntity};
+
+pub struct PalettePlugin;
+
+impl Plugin for PalettePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_system(recolor_wizards.run_if(resource_changed::<Settings>()))
+            .add_system(add_owner_markers)
+            .add_system(update_owner_markers)
+            .add_system(show_owner_markers.run_if(resource_changed::<Settings>()));
+    }
+}
+
+pub const WIZARD_COUNT: usize = 8;
+
+const CLASSIC_WIZARDS: [Color; WIZARD_COUNT] = [
+    RED,
+    PURPLE,
+    GREEN,
+    AQUA,
+    MIDYELLOW,
+    YELLOW,
+    GREY,
+    WHITE,
+];
+
+// Okabe and Ito's colours, which stay apart with any kind of colour blindness
+const VERMILLION: Color = Color::rgba(213.0/255.0, 94.0/255.0, 0.0, 1.0);
+const REDDISH_PURPLE: Color = Color::rgba(204.0/255.0, 121.0/255.0, 167.0/255.0, 1.0);
+const BLUISH_GREEN: Color = Color::rgba(0.0, 158.0/255.0, 115.0/255.0, 1.0);
+const SKY_BLUE: Color = Color::rgba(86.0/255.0, 180.0/255.0, 233.0/255.0, 1.0);
+const ORANGE: Color = Color::rgba(230.0/255.0, 159.0/255.0, 0.0, 1.0);
+const CB_YELLOW: Color = Color::rgba(240.0/255.0, 228.0/255.0, 66.0/255.0, 1.0);
+const CB_BLUE: Color = Color::rgba(0.0, 114.0/255.0, 178.0/255.0, 1.0);
+
+const COLOUR_BLIND_WIZARDS: [Color; WIZARD_COUNT] = [
+    VERMILLION,
+    REDDISH_PURPLE,
+    BLUISH_GREEN,
+    SKY_BLUE,
+    ORANGE,
+    CB_YELLOW,
+    CB_BLUE,
+    WHITE,
+];
+
+// From always cast down to least likely, see ASpell::casting_chance_band
+const CLASSIC_CHANCES: [Color; 5] = [WHITE, YELLOW, AQUA, GREEN, PURPLE];
+const COLOUR_BLIND_CHANCES: [Color; 5] = [WHITE, CB_YELLOW, SKY_BLUE, ORANGE, REDDISH_PURPLE];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Palette {
+    #[default]
+    Classic,
+    ColourBlind,
+    // The wizard colours from custom_colors in the settings file
+    Custom,
+}
+
+impl Palette {
+    pub const ALL: [Self; 3] = [Self::Classic, Self::ColourBlind, Self::Custom];
+
+    // Looked up in the locale
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Classic => "palette.classic",
+            Self::ColourBlind => "palette.colour_blind",
+            Self::Custom => "palette.custom",
+        }
+    }
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|p| *p == self).unwrap();
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
+
+// The classic colours, to be edited in the settings file
+pub fn default_custom_colors() -> [[u8; 3]; WIZARD_COUNT] {
+    [
+        [255, 0, 0],
+        [255, 0, 255],
+        [0, 255, 0],
+        [0, 255, 255],
+        [204, 204, 0],
+        [255, 255, 0],
+        [204, 204, 204],
+        [255, 255, 255],
+    ]
+}
+
+pub fn wizard_colors(settings: &Settings) -> [Color; WIZARD_COUNT] {
+    match settings.palette {
+        Palette::Classic => CLASSIC_WIZARDS,
+        Palette::ColourBlind => COLOUR_BLIND_WIZARDS,
+        Palette::Custom => settings.custom_colors.map(|[r, g, b]| Color::rgb_u8(r, g, b)),
+    }
+}
+
+pub fn chance_color(settings: &Settings, band: usize) -> Color {
+    let colors = if settings.palette == Palette::ColourBlind { COLOUR_BLIND_CHANCES } else { CLASSIC_CHANCES };
+    colors[band.min(colors.len() - 1)]
+}
+
+// Squares a piece can move to and enemies it can attack when moving
+pub fn move_colors(settings: &Settings) -> (Color, Color) {
+    if settings.palette == Palette::ColourBlind {
+        (SKY_BLUE.with_a(0.4), VERMILLION.with_a(0.5))
+    } else {
+        (MOVE_COLOR, ATTACK_COLOR)
+    }
+}
+
+// Wizards keep their place in the palette, so they change colour with it
+fn recolor_wizards(
+    settings: Res<Settings>,
+    mut g: ResMut<Game>,
+    mut sprites: Query<&mut TextureAtlasSprite>,
+) {
+    let colors = wizard_colors(&settings);
+    for player in &mut g.player_info {
+        player.color = colors[player.color_index];
+        if let Some(mut sprite) = player.handle.and_then(|e| sprites.get_mut(e).ok()) {
+            sprite.color = player.color;
+        }
+    }
+}
+
+// The owner's player number in the corner of each piece, so pieces can be told apart
+// without relying on colour
+#[derive(Component)]
+struct OwnerMarker;
+
+#[derive(Component)]
+struct HasOwnerMarker;
+
+fn add_owner_markers(
+    mut commands: Commands,
+    g: Res<Game>,
+    settings: Res<Settings>,
+    pieces: Query<(Entity, Option<&BelongsToPlayer>), (With<BoardEntity>, Without<HasOwnerMarker>)>,
+) {
+    for (entity, belongs) in &pieces {
+        let Some(digit) = owner_digit(&g, belongs.map_or(entity, |b| b.player_entity)) else {
+            continue;
+        };
+        // The piece is scaled to its 16 pixels, so this is in pixels: a half size digit
+        // in the bottom right corner
+        let mut marker = get_sprite_sheet_bundle(g.fah(), Vec2::new(6.0, -4.0), char_to_pos(digit), WHITE);
+        marker.transform = Transform::from_xyz(6.0, -4.0, 0.1).with_scale(Vec3::new(0.5, 0.5, 1.0));
+        marker.visibility = if settings.owner_markers { Visibility::Inherited } else { Visibility::Hidden };
+        let marker = commands.spawn(marker).insert(OwnerMarker).id();
+        commands.entity(entity).insert(HasOwnerMarker).add_child(marker);
+    }
+}
+
+fn owner_digit(g: &Game, owner: Entity) -> Option<char> {
+    let n = g.player_info.iter().position(|p| p.handle == Some(owner))?;
+    char::from_digit(n as u32 + 1, 10)
+}
+
+// A piece that changes sides shows its new owner
+fn update_owner_markers(
+    g: Res<Game>,
+    pieces: Query<(&BelongsToPlayer, &Children), (Changed<BelongsToPlayer>, With<HasOwnerMarker>)>,
+    mut markers: Query<&mut TextureAtlasSprite, With<OwnerMarker>>,
+) {
+    for (belongs, children) in &pieces {
+        let Some(digit) = owner_digit(&g, belongs.player_entity) else {
+            continue;
+        };
+        for child in children {
+            if let Ok(mut sprite) = markers.get_mut(*child) {
+                sprite.index = char_to_pos(digit);
+            }
+        }
+    }
+}
+
+fn show_owner_markers(
+    settings: Res<Settings>,
+    mut markers: Query<&mut Visibility, With<OwnerMarker>>,
+) {
+    for mut visibility in &mut markers {
+        *visibility = if settings.owner_markers { Visibility::Inherited } else { Visibility::Hidden };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palettes() {
+        let mut settings = Settings::default();
+        assert_eq!(wizard_colors(&settings), CLASSIC_WIZARDS);
+        settings.palette = Palette::Custom;
+        assert_eq!(wizard_colors(&settings), CLASSIC_WIZARDS);
+        settings.custom_colors[0] = [0, 0, 255];
+        assert_eq!(wizard_colors(&settings)[0], Color::rgb_u8(0, 0, 255));
+        assert_eq!(Palette::Custom.next(), Palette::Classic);
+        assert_eq!(chance_color(&settings, 10), PURPLE);
+    }
+}
@@ -1,7 +1,11 @@
 use bevy::prelude::*;
 use crate::board::MoveableComponent;
 use crate::display;
-use crate::spell::{AllSpells, SpellBox, ASpell};
+use crate::locale::Locale;
+use crate::profile::Stats;
+use crate::rules::{Rules, CLASSIC};
+use crate::spell::{in_cast_range, AllSpells, SpellBox, ASpell};
+use crate::vec::Vec2I;
 use crate::system::{BoardEntity, Named, BelongsToPlayer, CanAttack, CanDefend};
 use rand::prelude::SliceRandom;
 use rand::Rng;
@@ -9,8 +13,12 @@ use rand::Rng;
 pub struct Player {
     pub name: String,
     pub computer_controlled: bool,
+    // How well the computer plays this wizard, when it does
+    pub ai_level: u8,
     pub character_icon: u8,
     pub color: Color,
+    // Place in the palette, so the colour can follow the palette setting
+    pub color_index: usize,
     pub spells: SpellList,
     pub handle: Option<Entity>,
     pub creations: Vec<Entity>,
@@ -19,15 +27,37 @@ pub struct Player {
     pub combat: u8,
     pub manoeuvre: u8,
     pub magic_resistance: u8,
+    pub alive: bool,
+    pub death: Option<Death>,
+    // What this wizard has done this game
+    pub stats: Stats,
+    // Law and chaos of every spell cast this game added up, which only the stats look at
+    pub spells_law_chaos: i8,
+    // Where the wizard stood at the end of each round, from law_chaos and the spells cast
+    pub alignment: Vec<i8>,
+    // Wizards on the same team can't attack each other, and win or lose together
+    pub team: Option<u8>,
+}
+
+// When a wizard was killed, and by what
+#[derive(Clone, Debug)]
+pub struct Death {
+    pub round: u32,
+    // The wizard whose piece made the kill
+    pub killer: String,
+    // English name of the piece itself, the same as killer if the wizard did it
+    pub by: String,
 }
 
 impl Player {
-    pub fn new(name: String, cc: bool, icon: u8, color: Color) -> Self {
-        Self {
+    pub fn new(name: String, cc: bool, icon: u8, color_index: usize, color: Color) -> Self {
+        let mut player = Self {
             name,
             computer_controlled: cc,
+            ai_level: 0,
             character_icon: icon,
             color,
+            color_index,
             spells: SpellList {
                 spells: Vec::new(),
                 chosen_spell: None,
@@ -36,20 +66,28 @@ impl Player {
             handle: None,
             creations: Vec::new(),
             law_chaos: 0,
-            defence: rand::thread_rng().gen_range(1..6),          // 1-5
-            combat: rand::thread_rng().gen_range(1..6),           // 1-5
-            manoeuvre: rand::thread_rng().gen_range(3..8),        // 3-7
-            magic_resistance: rand::thread_rng().gen_range(6..9), // 6-8
-        }
+            defence: 0,
+            combat: 0,
+            manoeuvre: 0,
+            magic_resistance: 0,
+            alive: true,
+            death: None,
+            stats: Stats::default(),
+            spells_law_chaos: 0,
+            alignment: Vec::new(),
+            team: None,
+        };
+        player.roll_stats(&CLASSIC, &mut rand::thread_rng());
+        player
+    }
+    pub fn roll_stats(&mut self, rules: &Rules, rng: &mut impl Rng) {
+        self.defence = Rules::roll(rules.defence, rng);
+        self.combat = Rules::roll(rules.combat, rng);
+        self.manoeuvre = Rules::roll(rules.manoeuvre, rng);
+        self.magic_resistance = Rules::roll(rules.magic_resistance, rng);
     }
     pub fn pick_spells(&mut self, allspells: &AllSpells) {
-        let mut sample: Vec<SpellBox> = Vec::new();
-        for spell in allspells[1..].choose_multiple(&mut rand::thread_rng(), 13) {
-            sample.push((*spell).clone());
-        }
-        sample.insert(0, allspells[0].clone());
-        self.spells.spells = sample;
-
+        self.spells.spells = deal_spells(allspells, CLASSIC.spell_count, &mut rand::thread_rng());
     }
     pub fn spawn(
         &mut self,
@@ -81,13 +119,11 @@ impl Player {
         from: Vec2,
         to: Vec2,
         commands: &mut Commands,
-        tah: Handle<TextureAtlas>
+        tah: Handle<TextureAtlas>,
     ) -> Result<Option<Entity>, CastFailed> {
         let range = self.spells.get_chosen_spell().unwrap().cast_range();
-        let dist = (to - from).length().floor();
-        println!("RANGE IS {range} DIST IS {dist}");
-        if dist > f32::from(range) {
-            println!("Return too far");
+        if !in_cast_range(Vec2I::from(from), Vec2I::from(to), range) {
+            debug!("Return too far");
             return Err(CastFailed::OutOfRange);
         }
         let spell = self.spells.pop_chosen_spell();
@@ -143,14 +179,30 @@ impl ASpell for PlayerSpell {
     fn casting_chance(&self) -> u8 {
         self.casting_chance
     }
-    fn get_description(&self) -> Vec<String> {
+    fn get_description(&self, _locale: &Locale) -> Vec<String> {
         Vec::new()
     }
 }
 
+pub type CastSpellResult = Result<Option<Entity>, CastFailed>;
+
 pub enum CastFailed {
     OutOfRange,
     NotThere,
+    NoLineOfSight,
+    NoTarget,
+}
+
+impl CastFailed {
+    // Looked up in the locale
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::OutOfRange => "cast.out_of_range",
+            Self::NotThere => "cast.occupied",
+            Self::NoLineOfSight => "cast.no_line_of_sight",
+            Self::NoTarget => "cast.no_target",
+        }
+    }
 }
 pub struct SpellList {
     pub spells: Vec<Box<dyn ASpell + Sync + Send>>,
@@ -184,6 +236,16 @@ impl SpellList {
     }
 }
 
+// Disbelieve, which every wizard has, and count others at random
+pub fn deal_spells(allspells: &AllSpells, count: usize, rng: &mut impl Rng) -> Vec<SpellBox> {
+    let mut sample: Vec<SpellBox> = Vec::new();
+    for spell in allspells[1..].choose_multiple(rng, count) {
+        sample.push((*spell).clone());
+    }
+    sample.insert(0, allspells[0].clone());
+    sample
+}
+
 pub fn get_start_positions(num: usize) -> Result<Vec<Vec2>, &'static str> {
     match num {
         2 => Ok(vec![
@@ -238,3 +300,65 @@ pub fn get_start_positions(num: usize) -> Result<Vec<Vec2>, &'static str> {
         _ => Err("invalid number of players"),
     }
 }
+
+// How a team is shown, A for the first
+pub fn team_letter(team: u8) -> char {
+    (b'A' + team) as char
+}
+
+// Teammates start next to each other. The usual places are taken in turn going round the board,
+// starting from whichever place keeps the teams closest together.
+pub fn get_team_start_positions(teams: &[Option<u8>]) -> Result<Vec<Vec2>, &'static str> {
+    let positions = get_start_positions(teams.len())?;
+    if teams.iter().all(Option::is_none) {
+        return Ok(positions);
+    }
+    let centre = Vec2::new(7.0, 4.5);
+    let mut around = positions;
+    around.sort_by(|a, b| (a.y - centre.y).atan2(a.x - centre.x).total_cmp(&(b.y - centre.y).atan2(b.x - centre.x)));
+    let mut order: Vec<usize> = (0..teams.len()).collect();
+    order.sort_by_key(|i| teams[*i].unwrap_or(u8::MAX));
+    let place = |start: usize| {
+        let mut placed = vec![Vec2::ZERO; teams.len()];
+        for (k, i) in order.iter().enumerate() {
+            placed[*i] = around[(k + start) % around.len()];
+        }
+        placed
+    };
+    let spread = |placed: &[Vec2]| {
+        let mut total = 0.0;
+        for i in 0..teams.len() {
+            for j in i + 1..teams.len() {
+                if teams[i].is_some() && teams[i] == teams[j] {
+                    total += placed[i].distance(placed[j]);
+                }
+            }
+        }
+        total
+    };
+    let best = (0..teams.len())
+        .map(place)
+        .min_by(|a, b| spread(a).total_cmp(&spread(b)))
+        .unwrap();
+    Ok(best)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_team_start_positions() {
+        assert_eq!(get_team_start_positions(&[None; 4]).unwrap(), get_start_positions(4).unwrap());
+        // Two a side on the same edge of the board
+        let positions = get_team_start_positions(&[Some(0), Some(1), Some(0), Some(1)]).unwrap();
+        let x: Vec<i8> = positions.iter().map(|p| Vec2I::from(*p).x).collect();
+        assert_eq!(x[0], x[2]);
+        assert_eq!(x[1], x[3]);
+        assert_ne!(x[0], x[1]);
+        let positions = get_team_start_positions(&[Some(0), Some(0), Some(1), Some(1), Some(2), Some(2)]).unwrap();
+        for pair in positions.chunks(2) {
+            assert!(pair[0].distance(pair[1]) < 10.0);
+        }
+    }
+}
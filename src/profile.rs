@@ -0,0 +1,219 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use crate::player::Player;
+
+const PROFILES_FILE: &str = "profiles.ron";
+pub const START_RATING: f32 = 1500.0;
+// How far one game can move a rating
+const RATING_K: f32 = 32.0;
+
+pub struct ProfilePlugin;
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Profiles::load());
+    }
+}
+
+// What a wizard has done, either in one game or added up over every game for a profile
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    pub games_played: u32,
+    pub games_won: u32,
+    pub wizards_killed: u32,
+    pub creatures_summoned: u32,
+    pub creatures_lost: u32,
+    // Casts refused, at a square the spell can't go
+    pub spells_failed: u32,
+    pub illusions_cast: u32,
+    // Disbelieve cast at a piece which turned out to be an illusion
+    pub illusions_disbelieved: u32,
+    // Times each spell was tried, by its English name
+    pub spells_cast: BTreeMap<String, u32>,
+    // Pieces killed by each of this wizard's creatures, or by the wizard, by English name
+    pub kills: BTreeMap<String, u32>,
+}
+
+impl Stats {
+    pub fn cast(&mut self, spell: &str) {
+        *self.spells_cast.entry(spell.to_string()).or_default() += 1;
+    }
+    pub fn add(&mut self, other: &Self) {
+        self.games_played += other.games_played;
+        self.games_won += other.games_won;
+        self.wizards_killed += other.wizards_killed;
+        self.creatures_summoned += other.creatures_summoned;
+        self.creatures_lost += other.creatures_lost;
+        self.spells_failed += other.spells_failed;
+        self.illusions_cast += other.illusions_cast;
+        self.illusions_disbelieved += other.illusions_disbelieved;
+        for (spell, count) in &other.spells_cast {
+            *self.spells_cast.entry(spell.clone()).or_default() += count;
+        }
+        for (creature, count) in &other.kills {
+            *self.kills.entry(creature.clone()).or_default() += count;
+        }
+    }
+    pub fn kill(&mut self, creature: &str) {
+        *self.kills.entry(creature.to_string()).or_default() += 1;
+    }
+    pub fn spells_cast_total(&self) -> u32 {
+        self.spells_cast.values().sum()
+    }
+    // Most cast first, ties in name order
+    pub fn favourite_spells(&self, n: usize) -> Vec<(&str, u32)> {
+        most(&self.spells_cast, n)
+    }
+    pub fn top_kills(&self, n: usize) -> Vec<(&str, u32)> {
+        most(&self.kills, n)
+    }
+}
+
+fn most(counts: &BTreeMap<String, u32>, n: usize) -> Vec<(&str, u32)> {
+    let mut most: Vec<(&str, u32)> = counts.iter().map(|(s, c)| (s.as_str(), *c)).collect();
+    most.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    most.truncate(n);
+    most
+}
+
+// A human player remembered between runs, found by name
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub character_icon: u8,
+    pub color_index: usize,
+    #[serde(default = "start_rating")]
+    pub rating: f32,
+    #[serde(default)]
+    pub stats: Stats,
+}
+
+const fn start_rating() -> f32 {
+    START_RATING
+}
+
+#[derive(Default, Resource, Serialize, Deserialize)]
+pub struct Profiles(pub Vec<Profile>);
+
+impl Profiles {
+    pub fn load() -> Self {
+        let Ok(f) = File::open(PROFILES_FILE) else {
+            return Self::default();
+        };
+        ron::de::from_reader(f).unwrap_or_else(|e| {
+            warn!("Ignoring unreadable {PROFILES_FILE}: {e}");
+            Self::default()
+        })
+    }
+    pub fn save(&self) {
+        let res = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())
+            .and_then(|s| std::fs::write(PROFILES_FILE, s).map_err(|e| e.to_string()));
+        if let Err(e) = res {
+            warn!("Could not save {PROFILES_FILE}: {e}");
+        }
+    }
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.0.iter().find(|p| p.name == name)
+    }
+    // Best rated first
+    pub fn ranked(&self) -> Vec<&Profile> {
+        let mut ranked: Vec<&Profile> = self.0.iter().collect();
+        ranked.sort_by(|a, b| b.rating.total_cmp(&a.rating));
+        ranked
+    }
+    fn get_or_add(&mut self, player: &Player) -> &mut Profile {
+        let i = self.0.iter().position(|p| p.name == player.name).unwrap_or_else(|| {
+            self.0.push(Profile {
+                name: player.name.clone(),
+                character_icon: player.character_icon,
+                color_index: player.color_index,
+                rating: START_RATING,
+                stats: Stats::default(),
+            });
+            self.0.len() - 1
+        });
+        &mut self.0[i]
+    }
+    // Adds a finished game to the profiles of the human players, making profiles for any new
+    // names. Ratings only change when one human beat another at the same machine.
+    pub fn record_game(&mut self, players: &[Player], winners: &[usize]) {
+        let humans: Vec<usize> = (0..players.len()).filter(|i| !players[*i].computer_controlled).collect();
+        // Nothing to save from games the computer played on its own, like a tournament's
+        if humans.is_empty() {
+            return;
+        }
+        let ratings: Vec<f32> = humans.iter().map(|i| self.get_or_add(&players[*i]).rating).collect();
+        // A team's win counts for each of them, but only a win on their own moves a rating
+        let winner = match winners {
+            [w] => Some(*w),
+            _ => None,
+        };
+        let new_ratings = match winner.and_then(|w| humans.iter().position(|i| *i == w)) {
+            Some(w) if humans.len() > 1 => elo(&ratings, w),
+            _ => ratings,
+        };
+        for (i, rating) in humans.into_iter().zip(new_ratings) {
+            let player = &players[i];
+            let profile = self.get_or_add(player);
+            profile.character_icon = player.character_icon;
+            profile.color_index = player.color_index;
+            profile.rating = rating;
+            profile.stats.add(&player.stats);
+            profile.stats.games_played += 1;
+            if winners.contains(&i) {
+                profile.stats.games_won += 1;
+            }
+        }
+        self.save();
+    }
+}
+
+// The winner is counted as beating each of the others in turn, with each of those results
+// worth a share of a whole game so big games don't swing ratings more
+pub fn elo(ratings: &[f32], winner: usize) -> Vec<f32> {
+    let mut new = ratings.to_vec();
+    let k = RATING_K / (ratings.len() - 1) as f32;
+    for (i, rating) in ratings.iter().enumerate() {
+        if i == winner {
+            continue;
+        }
+        let expected = 1.0 / (1.0 + 10_f32.powf((rating - ratings[winner]) / 400.0));
+        let change = k * (1.0 - expected);
+        new[winner] += change;
+        new[i] -= change;
+    }
+    new
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_elo() {
+        assert_eq!(elo(&[1500.0, 1500.0], 1), vec![1484.0, 1516.0]);
+        // Beating a stronger player is worth more
+        let upset = elo(&[1400.0, 1600.0], 0);
+        assert!(upset[0] - 1400.0 > 16.0);
+        let total: f32 = elo(&[1500.0, 1450.0, 1600.0], 2).iter().sum();
+        assert!((total - 4550.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_favourite_spells() {
+        let mut stats = Stats::default();
+        for spell in ["Lion", "Magic Bolt", "Lion", "Gooey Blob", "Magic Bolt", "Lion"] {
+            stats.cast(spell);
+        }
+        assert_eq!(stats.favourite_spells(2), vec![("Lion", 3), ("Magic Bolt", 2)]);
+        let mut total = Stats::default();
+        total.add(&stats);
+        total.add(&stats);
+        assert_eq!(total.spells_cast["Lion"], 6);
+        assert_eq!(total.spells_cast_total(), 12);
+    }
+}
@@ -0,0 +1,100 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+// The parts of the game which can be changed for a different kind of game. One of the presets is
+// picked when setting up a game, and kept with the game until it's over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rules {
+    // Dealt to each wizard on top of Disbelieve
+    pub spell_count: usize,
+    // Lowest and highest each of the wizards' own strengths are rolled as
+    pub combat: (u8, u8),
+    pub defence: (u8, u8),
+    pub manoeuvre: (u8, u8),
+    pub magic_resistance: (u8, u8),
+    // Combat and defence each have 1 up to this added in a fight
+    pub combat_die: u8,
+    // The game is a draw if it goes past this many rounds
+    pub round_limit: Option<u32>,
+    pub illusions: Illusions,
+    // Whether wizards can attack pieces themselves, or have to leave it to their creatures
+    pub wizards_attack: bool,
+    // Spells are picked in turn from a shared pool before the game, rather than dealt
+    pub draft: bool,
+    // Each side only sees what its own pieces can
+    #[serde(default)]
+    pub fog_of_war: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Illusions {
+    // Any creature can be cast as an illusion instead
+    Allowed,
+    // Only real creatures can be cast
+    Off,
+}
+
+pub const CLASSIC: Rules = Rules {
+    spell_count: 13,
+    combat: (1, 5),
+    defence: (1, 5),
+    manoeuvre: (3, 7),
+    magic_resistance: (6, 8),
+    combat_die: 9,
+    round_limit: None,
+    illusions: Illusions::Allowed,
+    wizards_attack: true,
+    draft: false,
+    fog_of_war: false,
+};
+
+// Locale keys for their names, with the rules
+pub const PRESETS: [(&str, Rules); 5] = [
+    ("rules.classic", CLASSIC),
+    ("rules.quick", Rules { spell_count: 8, round_limit: Some(15), ..CLASSIC }),
+    ("rules.brawl", Rules {
+        spell_count: 10,
+        combat: (3, 7),
+        defence: (2, 6),
+        combat_die: 6,
+        illusions: Illusions::Off,
+        ..CLASSIC
+    }),
+    ("rules.draft", Rules { draft: true, ..CLASSIC }),
+    ("rules.fog", Rules { fog_of_war: true, ..CLASSIC }),
+];
+
+impl Default for Rules {
+    fn default() -> Self {
+        CLASSIC
+    }
+}
+
+impl Rules {
+    pub fn roll(range: (u8, u8), rng: &mut impl Rng) -> u8 {
+        rng.gen_range(range.0..=range.1)
+    }
+    // Locale key for the preset these are
+    pub fn name(&self) -> Option<&'static str> {
+        PRESETS.iter().find(|(_, rules)| rules == self).map(|(name, _)| *name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets() {
+        assert_eq!(PRESETS[0].1, Rules::default());
+        for (name, rules) in PRESETS {
+            // The turn menu has room for two more than the classic deal
+            assert!(rules.spell_count <= CLASSIC.spell_count + 2, "{name}");
+            for (low, high) in [rules.combat, rules.defence, rules.manoeuvre, rules.magic_resistance] {
+                assert!(low <= high && high <= 9, "{name}");
+            }
+            assert!(rules.combat_die > 0, "{name}");
+            assert_eq!(rules.name(), Some(name));
+        }
+    }
+}
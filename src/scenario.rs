@@ -0,0 +1,221 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use crate::board::GameBoard;
+use crate::creature::Creature;
+use crate::palette::{wizard_colors, WIZARD_COUNT};
+use crate::player::Player;
+use crate::rules::Rules;
+use crate::settings::Settings;
+use crate::spell::AllSpells;
+use crate::system::BelongsToPlayer;
+use crate::vec::Vec2I;
+
+// Board positions made in the editor, kept as one file each under scenarios/
+const SCENARIO_DIR: &str = "scenarios";
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    pub wizards: Vec<ScenarioWizard>,
+    pub creatures: Vec<ScenarioCreature>,
+    // Played with these, so they're kept with the position
+    #[serde(default)]
+    pub rules: Rules,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScenarioWizard {
+    pub name: String,
+    pub character_icon: u8,
+    pub color_index: usize,
+    pub x: i8,
+    pub y: i8,
+    // English names, Disbelieve included
+    pub spells: Vec<String>,
+    #[serde(default)]
+    pub law_chaos: i8,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScenarioCreature {
+    // English name, as in creatures.ron
+    pub name: String,
+    // Index into wizards
+    pub owner: usize,
+    pub x: i8,
+    pub y: i8,
+    #[serde(default)]
+    pub illusion: bool,
+}
+
+impl ScenarioWizard {
+    pub fn pos(&self) -> Vec2I {
+        Vec2I::new(self.x, self.y)
+    }
+}
+
+impl ScenarioCreature {
+    pub fn pos(&self) -> Vec2I {
+        Vec2I::new(self.x, self.y)
+    }
+}
+
+// What is standing on a square
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScenarioPiece {
+    Wizard(usize),
+    Creature(usize),
+}
+
+fn path(name: &str) -> String {
+    format!("{SCENARIO_DIR}/{name}.ron")
+}
+
+impl Scenario {
+    pub fn load(name: &str) -> Result<Self, String> {
+        let f = File::open(path(name)).map_err(|e| format!("Could not open {}: {e}", path(name)))?;
+        let scenario: Self = ron::de::from_reader(f).map_err(|e| format!("Could not read {}: {e}", path(name)))?;
+        scenario.check().map_err(|e| format!("Could not read {}: {e}", path(name)))?;
+        Ok(scenario)
+    }
+    // Files edited by hand can name colours the palette doesn't have
+    fn check(&self) -> Result<(), String> {
+        self.wizards.iter().find(|w| w.color_index >= WIZARD_COUNT)
+            .map_or(Ok(()), |w| Err(format!("{} has colour {}, there are only {WIZARD_COUNT}", w.name, w.color_index)))
+    }
+    pub fn save(&self) -> Result<(), String> {
+        std::fs::create_dir_all(SCENARIO_DIR).map_err(|e| e.to_string())?;
+        let s = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(|e| e.to_string())?;
+        std::fs::write(path(&self.name), s).map_err(|e| format!("Could not save {}: {e}", path(&self.name)))
+    }
+    // Names of every saved scenario, in order
+    pub fn saved() -> Vec<String> {
+        let Ok(dir) = std::fs::read_dir(SCENARIO_DIR) else {
+            return Vec::new();
+        };
+        let mut names: Vec<String> = dir.filter_map(Result::ok)
+            .filter_map(|entry| entry.file_name().to_str()?.strip_suffix(".ron").map(String::from))
+            .collect();
+        names.sort();
+        names
+    }
+    pub fn piece_at(&self, pos: Vec2I) -> Option<ScenarioPiece> {
+        if let Some(i) = self.wizards.iter().position(|w| w.pos() == pos) {
+            return Some(ScenarioPiece::Wizard(i));
+        }
+        self.creatures.iter().position(|c| c.pos() == pos).map(ScenarioPiece::Creature)
+    }
+    // Taking away a wizard takes their creatures with them
+    pub fn remove(&mut self, piece: ScenarioPiece) {
+        match piece {
+            ScenarioPiece::Wizard(i) => {
+                self.wizards.remove(i);
+                self.creatures.retain(|c| c.owner != i);
+                for creature in &mut self.creatures {
+                    if creature.owner > i {
+                        creature.owner -= 1;
+                    }
+                }
+            }
+            ScenarioPiece::Creature(i) => {
+                self.creatures.remove(i);
+            }
+        }
+    }
+    // The wizards as players, with the spells they were given. Spells this version doesn't
+    // have are left out.
+    pub fn players(&self, allspells: &AllSpells, settings: &Settings) -> Vec<Player> {
+        let colors = wizard_colors(settings);
+        self.wizards.iter().map(|w| {
+            let mut player = Player::new(w.name.clone(), false, w.character_icon, w.color_index, colors[w.color_index]);
+            player.spells.spells = w.spells.iter()
+                .filter_map(|name| allspells.iter().find(|s| s.name() == *name))
+                .map(|s| (*s).clone())
+                .collect();
+            player.law_chaos = w.law_chaos;
+            player
+        }).collect()
+    }
+    // Puts every piece on the board the same way the game does, each creature belonging to its
+    // wizard. Wizards are spawned hidden, as they are at the start of a game.
+    pub fn spawn(
+        &self,
+        players: &mut [Player],
+        creatures: &HashMap<String, Creature>,
+        commands: &mut Commands,
+        tah: &Handle<TextureAtlas>,
+        board: &mut GameBoard,
+    ) {
+        for (wizard, player) in self.wizards.iter().zip(players.iter_mut()) {
+            let pos = Vec2::from(wizard.pos());
+            player.spawn(commands, tah.clone(), pos);
+            board.put_entity(pos, player.handle.unwrap());
+        }
+        for c in &self.creatures {
+            let Some(creature) = creatures.get(&c.name) else {
+                warn!("Scenario {} has unknown creature {}", self.name, c.name);
+                continue;
+            };
+            let Some(owner) = players.get_mut(c.owner) else {
+                continue;
+            };
+            let pos = Vec2::from(c.pos());
+            let e = creature.to_entity(c.illusion, pos, commands, tah.clone());
+            commands.entity(e).insert(BelongsToPlayer { player_entity: owner.handle.unwrap() });
+            owner.creations.push(e);
+            board.put_entity(pos, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wizard(x: i8) -> ScenarioWizard {
+        ScenarioWizard { name: format!("W{x}"), character_icon: 1, color_index: 0, x, y: 0, spells: Vec::new(), law_chaos: 0 }
+    }
+
+    fn creature(owner: usize, x: i8) -> ScenarioCreature {
+        ScenarioCreature { name: "Lion".to_string(), owner, x, y: 1, illusion: false }
+    }
+
+    #[test]
+    fn test_remove_wizard() {
+        let mut scenario = Scenario {
+            name: "test".to_string(),
+            wizards: vec![wizard(0), wizard(5), wizard(9)],
+            creatures: vec![creature(0, 0), creature(1, 5), creature(2, 9)],
+            ..Default::default()
+        };
+        assert_eq!(scenario.piece_at(Vec2I::new(5, 0)), Some(ScenarioPiece::Wizard(1)));
+        assert_eq!(scenario.piece_at(Vec2I::new(9, 1)), Some(ScenarioPiece::Creature(2)));
+        scenario.remove(ScenarioPiece::Wizard(1));
+        assert_eq!(scenario.wizards.len(), 2);
+        // The middle wizard's creature went with them, the last one's now belongs to wizard 1
+        let owners: Vec<(usize, i8)> = scenario.creatures.iter().map(|c| (c.owner, c.x)).collect();
+        assert_eq!(owners, vec![(0, 0), (1, 9)]);
+        assert_eq!(scenario.piece_at(Vec2I::new(5, 0)), None);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let rules = crate::rules::PRESETS[1].1;
+        let scenario = Scenario { name: "x".to_string(), wizards: vec![wizard(3)], creatures: vec![creature(0, 4)], rules };
+        let text = ron::ser::to_string(&scenario).unwrap();
+        let back: Scenario = ron::de::from_str(&text).unwrap();
+        assert_eq!(back.wizards[0].x, 3);
+        assert_eq!(back.creatures[0].name, "Lion");
+        assert_eq!(back.rules, rules);
+    }
+
+    #[test]
+    fn test_check_colours() {
+        let mut scenario = Scenario { name: "x".to_string(), wizards: vec![wizard(3)], ..Default::default() };
+        assert!(scenario.check().is_ok());
+        scenario.wizards[0].color_index = WIZARD_COUNT;
+        assert!(scenario.check().is_err());
+    }
+}
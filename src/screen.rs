@@ -1,19 +1,44 @@
 use bevy::prelude::*;
 
 mod board;
+mod campaign;
+mod challenges;
+mod draft;
+mod editor;
+mod gameover;
+mod gamestats;
+mod halloffame;
 mod help;
+mod logview;
 mod menu;
+mod nameentry;
+mod options;
+mod passturn;
+mod playersetup;
 mod spellcasting;
 mod turnmenu;
 
+pub use help::not_rebinding;
+
 pub struct ScreenPlugin;
 
 impl Plugin for ScreenPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_plugin(board::BoardPlugin)
+            .add_plugin(campaign::CampaignPlugin)
+            .add_plugin(challenges::ChallengesPlugin)
+            .add_plugin(draft::DraftPlugin)
+            .add_plugin(editor::EditorPlugin)
+            .add_plugin(gameover::GameOverPlugin)
+            .add_plugin(gamestats::GameStatsPlugin)
+            .add_plugin(halloffame::HallOfFamePlugin)
             .add_plugin(help::HelpPlugin)
+            .add_plugin(logview::LogViewPlugin)
 	        .add_plugin(menu::MenuPlugin)
+            .add_plugin(options::OptionsPlugin)
+            .add_plugin(passturn::PassTurnPlugin)
+            .add_plugin(playersetup::PlayerSetupPlugin)
             .add_plugin(spellcasting::SpellCastingPlugin)
             .add_plugin(turnmenu::TurnMenuPlugin)
             ;
@@ -1,34 +1,60 @@
 use bevy::prelude::*;
-use rand::Rng;
-use crate::board::{GameBoard, BoardMove, MoveableComponent, BoardKill};
+use crate::ai;
+use crate::board::{combat_rolls, GameBoard, BoardMove, MoveableComponent, BoardKill, RangedAttack};
+use crate::campaign::Campaign;
+use crate::challenge::{self, ActiveChallenge};
+use crate::clock::TimeUp;
 use crate::gamestate::GameState;
-use crate::game::Game;
+use crate::game::{Game, GameRng};
 use crate::display::{BottomTextEvent, StartExplosion, FinishedExplosion};
 use crate::system::{Named, BelongsToPlayer, RangedCombat, CanDefend, CanAttack};
+use crate::fog::Fog;
 use crate::cursor::{CURSOR_BOX, CursorMovedEvent, CURSOR_FLY, PositionCursorOnEntity, Cursor, CURSOR_TARGET};
+use crate::highlight::HighlightEvent;
+use crate::input::Action;
+use crate::locale::Locale;
+use crate::palette::move_colors;
+use crate::settings::Settings;
+use crate::messagelog::LogEvent;
 use crate::vec::Vec2I;
+use super::logview::LogView;
+use super::passturn::PassTurn;
 
 pub struct BoardPlugin;
 
 impl Plugin for BoardPlugin {
     fn build(&self, app: &mut App) {
         app
-            .add_system(move_next.in_set(OnUpdate(GameState::MoveSetup)))
+            .init_resource::<MoveUndo>()
+            .add_system(move_next.run_if(challenge::undecided).in_set(OnUpdate(GameState::MoveSetup)))
 
             .add_system(move_choose_setup.in_schedule(OnEnter(GameState::MoveChoose)))
-            .add_systems((move_choose_keyboard, board_describe_piece).in_set(OnUpdate(GameState::MoveChoose)))
+            .add_systems((move_put_down, board_describe_piece, board_piece_info).in_set(OnUpdate(GameState::MoveChoose)))
+            .add_system(move_choose_keyboard.run_if(ai::human_turn).in_set(OnUpdate(GameState::MoveChoose)))
+            .add_system(move_choose_computer.run_if(ai::computer_turn).in_set(OnUpdate(GameState::MoveChoose)))
+            .add_system(move_undo.run_if(ai::human_turn).in_set(OnUpdate(GameState::MoveChoose)))
 
             .add_system(move_moving_keyboard.in_set(OnUpdate(GameState::MoveMoving)))
+            .add_system(move_undo.run_if(ai::human_turn).in_set(OnUpdate(GameState::MoveMoving)))
+            .add_system(move_show_reachable.in_schedule(OnEnter(GameState::MoveMoving)))
+            .add_system(move_show_reachable.run_if(resource_changed::<GameBoard>()).in_set(OnUpdate(GameState::MoveMoving)))
+            .add_system(clear_highlight.in_schedule(OnExit(GameState::MoveMoving)))
 
             .add_system(ranged_attack_setup.in_schedule(OnEnter(GameState::RangedAttackChoose)))
             .add_systems((
                     board_describe_piece,
-                    ranged_attack_keyboard,
+                    board_piece_info,
                 ).in_set(OnUpdate(GameState::RangedAttackChoose)))
+            .add_system(ranged_attack_keyboard.run_if(ai::human_turn).in_set(OnUpdate(GameState::RangedAttackChoose)))
+            .add_system(ranged_attack_computer.run_if(ai::computer_turn).in_set(OnUpdate(GameState::RangedAttackChoose)))
             .add_system(ranged_attack_exit.in_schedule(OnExit(GameState::RangedAttackChoose)))
 
             .add_system(attack_start.in_schedule(OnEnter(GameState::AttackDo)))
             .add_system(attack_do.in_set(OnUpdate(GameState::AttackDo)))
+
+            .add_system(move_time_up.in_set(OnUpdate(GameState::MoveChoose)))
+            .add_system(move_time_up.in_set(OnUpdate(GameState::MoveMoving)))
+            .add_system(move_time_up.in_set(OnUpdate(GameState::RangedAttackChoose)))
             ;
     }
 }
@@ -42,7 +68,18 @@ struct MovingComponent {
 #[derive(Component)]
 struct HasMoved;
 
+// Everything done this movement phase which can still be taken back. Emptied as soon as
+// anything random happens (an attack or ranged shot), as the dice result can't be undone.
+#[derive(Default, Resource)]
+struct MoveUndo(Vec<UndoStep>);
+
+enum UndoStep {
+    Selected(Entity),
+    Moved { entity: Entity, from: Vec2 },
+}
+
 fn move_next(
+    locale: Res<Locale>,
     mut state: ResMut<NextState<GameState>>,
     mut g: ResMut<Game>,
     mut q: Query<Entity, With<HasMoved>>,
@@ -50,25 +87,40 @@ fn move_next(
     mut ev_text: EventWriter<BottomTextEvent>,
     mut ev_cursor_pos: EventWriter<PositionCursorOnEntity>,
     mut cursor: ResMut<Cursor>,
+    mut undo: ResMut<MoveUndo>,
+    mut ev_log: EventWriter<LogEvent>,
+    settings: Res<Settings>,
+    mut pass: ResMut<PassTurn>,
+    challenge: Res<ActiveChallenge>,
+    campaign: Res<Campaign>,
 ) {
     for has_moved_entity in q.iter_mut() {
         commands.entity(has_moved_entity).remove::<HasMoved>();
     }
+    undo.0.clear();
     if g.player_turn >= g.players {
-        println!("Moving finished, next turn now");
+        debug!("Moving finished, next turn now");
         ev_text.send(BottomTextEvent::clear());
-        println!("next_turn set state GameState::TurnMenu");
-        g.player_turn = 0;
+        debug!("next_turn set state GameState::TurnMenu");
+        g.first_player_turn();
+        g.end_round();
         cursor.set_invisible();
-        state.set(GameState::TurnMenu);
+        // Out of rounds, and nobody has won. Challenges and campaign battles decide for
+        // themselves when they're over.
+        let limited = !challenge.is_active() && !campaign.in_battle;
+        if limited && g.rules.round_limit.is_some_and(|limit| g.round > limit) {
+            ev_log.send(LogEvent::game(&g, locale.text("log.round_limit")));
+            state.set(GameState::GameOver);
+            return;
+        }
+        pass.to(GameState::TurnMenu, &g, &settings, &mut state);
     } else {
-        println!("Player turn to move");
+        debug!("Player turn to move");
         let player = g.get_player();
-        let mut s = player.name.clone();
         ev_cursor_pos.send(PositionCursorOnEntity(player.handle.unwrap()));
-        s.push_str("'s turn");
-        ev_text.send(BottomTextEvent::from(&s));
-        state.set(GameState::MoveChoose);
+        ev_text.send(BottomTextEvent::from(&locale.format("move.turn", &[&player.name])));
+        ev_log.send(LogEvent::player(&g, locale.text("log.moving")));
+        pass.to(GameState::MoveChoose, &g, &settings, &mut state);
     }
 }
 
@@ -80,43 +132,61 @@ fn move_choose_setup(
 ) {
     cursor.set_visible();
     cursor.set_type(CURSOR_BOX);
-    println!("In move choose setup");
+    debug!("In move choose setup");
 }
 
-fn move_choose_keyboard(
-    mut g: ResMut<Game>,
-    mut cursor: ResMut<Cursor>,
-    board: Res<GameBoard>,
-    mut keys: ResMut<Input<KeyCode>>,
+// We return here from MoveMoving with the entity that just moved still flagged with MovingComponent
+// remove that component, but at the same time check if this entity has ranged combat, as if so we need to do that now.
+fn move_put_down(
     mut state: ResMut<NextState<GameState>>,
-    mut query: Query<(&Named, &MoveableComponent, Option<&BelongsToPlayer>, &mut Transform, Option<&HasMoved>)>,
-    mut ev_text: EventWriter<BottomTextEvent>,
     mut commands: Commands,
     moving_q: Query<(Entity, Option<&RangedCombat>), With<MovingComponent>>,
 ) {
-    // We return here from MoveMoving with the entity that just moved still flagged with MovingComponent
-    // remove that component, but at the same time check if this entity has ranged combat, as if so we need to do that now.
     for (e, ranged_combat) in moving_q.iter() {
-        println!("Movement finished, remove MovingComponent");
+        debug!("Movement finished, remove MovingComponent");
         commands.entity(e).remove::<MovingComponent>();
         if ranged_combat.is_some() {
-            println!("Do ranged attack now");
+            debug!("Do ranged attack now");
             commands.entity(e).insert(RangedAttackComponent);
             state.set(GameState::RangedAttackChoose);
         }
     }
+}
 
-    if keys.just_pressed(KeyCode::Key0) {
-        keys.reset(KeyCode::Key0);
-        println!("Finish move one, increment player turn");
-        g.player_turn += 1;
+fn move_choose_keyboard(
+    locale: Res<Locale>,
+    mut g: ResMut<Game>,
+    mut cursor: ResMut<Cursor>,
+    board: Res<GameBoard>,
+    mut actions: ResMut<Input<Action>>,
+    mut state: ResMut<NextState<GameState>>,
+    mut query: Query<(&Named, &MoveableComponent, Option<&BelongsToPlayer>, &mut Transform, Option<&HasMoved>)>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+    mut commands: Commands,
+    moving_q: Query<(), With<MovingComponent>>,
+    mut undo: ResMut<MoveUndo>,
+    mut log_view: ResMut<LogView>,
+) {
+    // Not until the piece that just moved has been put down
+    if !moving_q.is_empty() {
+        return;
+    }
+    if actions.just_pressed(Action::Log) {
+        actions.reset(Action::Log);
+        log_view.open(GameState::MoveChoose, &mut state);
+        return;
+    }
+    if actions.just_pressed(Action::EndTurn) {
+        actions.reset(Action::EndTurn);
+        debug!("Finish move one, increment player turn");
+        g.next_player_turn();
         state.set(GameState::MoveSetup);
-        println!("Next player turn");
+        debug!("Next player turn");
     }
-    if keys.just_pressed(KeyCode::S) {
-        keys.reset(KeyCode::S);
+    if actions.just_pressed(Action::Select) {
+        actions.reset(Action::Select);
         let pos = cursor.get_pos_v();
-        println!("Find thing at {}, {} to move", pos.x, pos.y);
+        debug!("Find thing at {}, {} to move", pos.x, pos.y);
         if board.has_entity_at(pos) {
             let e = board.get_entity(pos).unwrap();
             let (_, moveable, belongs, _, has_moved) = query.get_mut(e).unwrap();
@@ -128,13 +198,13 @@ fn move_choose_keyboard(
             }
             if g.get_player().handle.unwrap() == belongs_entity && has_moved.is_none() {
                 commands.entity(e).insert(HasMoved);
-                println!("Does belong to this player");
-                let mut text = String::from("Movement range=");
-                text.push_str(&moveable.movement.to_string());
+                undo.0.push(UndoStep::Selected(e));
+                debug!("Does belong to this player");
+                let key = if moveable.flying { "move.range_flying" } else { "move.range" };
+                let text = locale.format(key, &[&moveable.movement]);
                 if moveable.flying {
                     cursor.set_type(CURSOR_FLY);
                     cursor.hide_till_moved();
-                    text.push_str(" (flying)");
                 } else {
                     cursor.set_invisible();
                 }
@@ -143,7 +213,7 @@ fn move_choose_keyboard(
                     steps: 0,
                 });
                 ev_text.send(BottomTextEvent::from(&text));
-                println!("State to MoveMoving");
+                debug!("State to MoveMoving");
                 state.set(GameState::MoveMoving);
             }
         }
@@ -151,9 +221,66 @@ fn move_choose_keyboard(
 
 }
 
+// Computer wizards move their own wizard first and then their creatures, one piece at a time
+#[allow(clippy::too_many_arguments)]
+fn move_choose_computer(
+    mut g: ResMut<Game>,
+    board: Res<GameBoard>,
+    fog: Res<Fog>,
+    pieces: ai::PieceQuery,
+    moved_q: Query<(), With<HasMoved>>,
+    moving_q: Query<(), With<MovingComponent>>,
+    mut rng: ResMut<GameRng>,
+    time: Res<Time>,
+    mut wait: Local<Option<Timer>>,
+    mut commands: Commands,
+    mut cursor: ResMut<Cursor>,
+    mut ev_move: EventWriter<BoardMove>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    // Not until the piece that just moved has been put down
+    if !moving_q.is_empty() || !ai::thought(&mut wait, &time) {
+        return;
+    }
+    let player = g.get_player();
+    let next = player.handle.into_iter()
+        .chain(player.creations.iter().copied())
+        .find(|e| board.entity_pos(*e).is_some() && !moved_q.contains(*e));
+    let Some(entity) = next else {
+        g.next_player_turn();
+        state.set(GameState::MoveSetup);
+        return;
+    };
+    let view = ai::View::new(&g, &board, &fog, &pieces);
+    let from = board.get_entity_pos(entity);
+    commands.entity(entity).insert(HasMoved).insert(MovingComponent{
+        start_pos: Vec2::from(from),
+        steps: 0,
+    });
+    let to = match ai::choose_move(&view, entity, &mut rng.0) {
+        None => return,
+        Some(ai::Move::To(to)) => to,
+        Some(ai::Move::Attack{ from: next_to, target }) => {
+            commands.entity(entity).insert(AttackingComponent{
+                attackee: board.get_entity(Vec2::from(target)).unwrap(),
+            });
+            state.set(GameState::AttackDo);
+            next_to
+        }
+    };
+    if to != from {
+        cursor.set_pos(Vec2::from(to));
+        ev_move.send(BoardMove{
+            entity,
+            to: Vec2::from(to),
+        });
+    }
+}
+
 fn move_moving_keyboard(
+    locale: Res<Locale>,
     mut cursor: ResMut<Cursor>,
-    mut keys: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Input<Action>>,
     mut state: ResMut<NextState<GameState>>,
     mut ev_cursor: EventReader<CursorMovedEvent>,
     mut ev_text: EventWriter<BottomTextEvent>,
@@ -163,22 +290,29 @@ fn move_moving_keyboard(
     board: Res<GameBoard>,
     game: Res<Game>,
     mut commands: Commands,
+    mut undo: ResMut<MoveUndo>,
+    fog: Res<Fog>,
 ) {
     let (entity, movable, moving) = moving_q.single_mut();
+    let from = Vec2::from(board.get_entity_pos(entity));
     if movable.flying {
-        if keys.just_pressed(KeyCode::K) {
+        if actions.just_pressed(Action::Cancel) {
             // Cancel movement
             ev_text.send(BottomTextEvent::clear());
             cursor.set_type(CURSOR_BOX);
             state.set(GameState::MoveChoose);
             info!("cancelled move");
         }
-        if keys.just_pressed(KeyCode::S) {
-            keys.reset(KeyCode::S);
+        if actions.just_pressed(Action::Select) {
+            actions.reset(Action::Select);
             let cursor_pos = cursor.get_pos_v();
             let distance = Vec2I::from(cursor_pos).distance(Vec2I::from(moving.start_pos));
             if distance > movable.movement {
-                ev_text.send(BottomTextEvent::from("Out of range"));
+                ev_text.send(BottomTextEvent::important(locale.text("move.out_of_range")));
+                cursor.hide_till_moved();
+            // Bumping into a piece in the fog gives nothing away about it
+            } else if board.has_entity_at(cursor_pos) && fog.hides(Vec2I::from(cursor_pos)) {
+                ev_text.send(BottomTextEvent::important(locale.text("move.unseen")));
                 cursor.hide_till_moved();
             } else if board.has_entity_at(cursor_pos) {
                 let other_entity = board.get_entity(cursor_pos).unwrap();
@@ -189,8 +323,10 @@ fn move_moving_keyboard(
                 let current_player_entity = game.get_player().handle.unwrap();
                 info!("Current player is {:?}", current_player_entity);
                 info!("Belongs to {:?}", belongs_to);
-                if current_player_entity == belongs_to {
-                    ev_text.send(BottomTextEvent::from("Cannot move to occupied square"));
+                if game.allied(current_player_entity, belongs_to) {
+                    ev_text.send(BottomTextEvent::important(locale.text("move.occupied")));
+                } else if !game.rules.wizards_attack && entity == current_player_entity {
+                    ev_text.send(BottomTextEvent::important(locale.text("move.wizard_no_attack")));
                 } else {
                     info!("Can attack");
                     commands.entity(entity).insert(AttackingComponent{
@@ -204,12 +340,13 @@ fn move_moving_keyboard(
                     entity,
                     to: cursor_pos,
                 });
+                undo.0.push(UndoStep::Moved { entity, from });
                 state.set(GameState::MoveChoose);
                 info!("Finished move");
             }
         }
     } else {
-        if keys.just_pressed(KeyCode::K) {
+        if actions.just_pressed(Action::Cancel) {
             // Cancel movement
             ev_text.send(BottomTextEvent::clear());
             cursor.set_visible();
@@ -217,8 +354,11 @@ fn move_moving_keyboard(
             info!("cancelled move");
         }
         for cur in ev_cursor.iter() {
-            println!("Got cursor moved event in move one from {} to {}", moving.start_pos, cur.0);
-            if board.has_entity_at(cur.0) {
+            debug!("Got cursor moved event in move one from {} to {}", moving.start_pos, cur.0);
+            if board.has_entity_at(cur.0) && fog.hides(Vec2I::from(cur.0)) {
+                ev_text.send(BottomTextEvent::important(locale.text("move.unseen")));
+                cursor.set_pos(cur.1);
+            } else if board.has_entity_at(cur.0) {
                 let other_entity = board.get_entity(cur.0).unwrap();
                 let belongs_to = match other_q.get_mut(other_entity) {
                     Ok(b) => b.player_entity,
@@ -227,8 +367,11 @@ fn move_moving_keyboard(
                 let current_player_entity = game.get_player().handle.unwrap();
                 info!("Current player is {:?}", current_player_entity);
                 info!("Belongs to {:?}", belongs_to);
-                if current_player_entity == belongs_to {
-                    ev_text.send(BottomTextEvent::from("Cannot move to occupied square"));
+                if game.allied(current_player_entity, belongs_to) {
+                    ev_text.send(BottomTextEvent::important(locale.text("move.occupied")));
+                    cursor.set_pos(cur.1);
+                } else if !game.rules.wizards_attack && entity == current_player_entity {
+                    ev_text.send(BottomTextEvent::important(locale.text("move.wizard_no_attack")));
                     cursor.set_pos(cur.1);
                 } else {
                     info!("Can attack");
@@ -243,6 +386,7 @@ fn move_moving_keyboard(
                     entity,
                     to: cur.0,
                 });
+                undo.0.push(UndoStep::Moved { entity, from: cur.1 });
                 let distance = Vec2I::from(cur.0).distance(Vec2I::from(moving.start_pos));
                 info!("Moved distance {} has movement {}", distance, movable.movement);
                 let distance_left = movable.movement.checked_sub(distance);
@@ -257,6 +401,135 @@ fn move_moving_keyboard(
     }
 }
 
+// Out of time, so every piece stays wherever it has got to. A piece part way through its move
+// is put down without its ranged attack.
+fn move_time_up(
+    mut ev_time_up: EventReader<TimeUp>,
+    mut state: ResMut<NextState<GameState>>,
+    mut g: ResMut<Game>,
+    mut commands: Commands,
+    moving_q: Query<Entity, With<MovingComponent>>,
+) {
+    if ev_time_up.iter().last().is_none() {
+        return;
+    }
+    for e in &moving_q {
+        commands.entity(e).remove::<MovingComponent>();
+    }
+    g.next_player_turn();
+    state.set(GameState::MoveSetup);
+}
+
+// While a piece is moving undo takes back its last step, or unselects it if it hasn't gone anywhere.
+// Otherwise the whole of the last piece's move is taken back.
+fn move_undo(
+    locale: Res<Locale>,
+    mut actions: ResMut<Input<Action>>,
+    mut undo: ResMut<MoveUndo>,
+    mut cursor: ResMut<Cursor>,
+    mut commands: Commands,
+    mut ev_move: EventWriter<BoardMove>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+    game_state: Res<State<GameState>>,
+    mut state: ResMut<NextState<GameState>>,
+    moving_q: Query<Entity, With<MovingComponent>>,
+    game: Res<Game>,
+    mut ev_log: EventWriter<LogEvent>,
+) {
+    if !actions.just_pressed(Action::Undo) {
+        return;
+    }
+    actions.reset(Action::Undo);
+    let moving = moving_q.get_single().ok();
+    if game_state.0 == GameState::MoveMoving {
+        let entity = moving.unwrap();
+        match undo.0.pop() {
+            Some(UndoStep::Moved { entity: e, from }) if e == entity => {
+                ev_move.send(BoardMove{ entity, to: from });
+                // Ground movement follows the cursor, so it has to go back with the piece
+                cursor.set_pos(from);
+                ev_text.send(BottomTextEvent::from(locale.text("move.step_undone")));
+            }
+            Some(UndoStep::Selected(e)) if e == entity => {
+                commands.entity(entity).remove::<HasMoved>().remove::<MovingComponent>();
+                cursor.set_type(CURSOR_BOX);
+                cursor.set_visible();
+                ev_text.send(BottomTextEvent::clear());
+                state.set(GameState::MoveChoose);
+            }
+            step => {
+                undo.0.extend(step);
+                ev_text.send(BottomTextEvent::important(locale.text("move.nothing_to_undo")));
+            }
+        }
+        return;
+    }
+    if moving.is_some() {
+        // Just got back from moving, the piece hasn't been put down yet
+        return;
+    }
+    let mut undone = false;
+    while let Some(step) = undo.0.pop() {
+        undone = true;
+        match step {
+            UndoStep::Moved { entity, from } => {
+                ev_move.send(BoardMove{ entity, to: from });
+                cursor.set_pos(from);
+            }
+            UndoStep::Selected(entity) => {
+                commands.entity(entity).remove::<HasMoved>();
+                break;
+            }
+        }
+    }
+    if undone {
+        ev_text.send(BottomTextEvent::important(locale.text("move.undone")));
+        ev_log.send(LogEvent::player(&game, locale.text("log.undo")));
+    } else {
+        ev_text.send(BottomTextEvent::important(locale.text("move.nothing_to_undo")));
+    }
+}
+
+// Tint the squares the moving piece can still get to, and the enemies it can attack
+fn move_show_reachable(
+    board: Res<GameBoard>,
+    game: Res<Game>,
+    settings: Res<Settings>,
+    moving_q: Query<(Entity, &MoveableComponent, &MovingComponent)>,
+    owner_q: Query<&BelongsToPlayer>,
+    defender_q: Query<&CanDefend>,
+    fog: Res<Fog>,
+    mut ev_highlight: EventWriter<HighlightEvent>,
+) {
+    let Ok((entity, movable, moving)) = moving_q.get_single() else {
+        return;
+    };
+    let current_player_entity = game.get_player().handle.unwrap();
+    let can_attack = game.rules.wizards_attack || entity != current_player_entity;
+    let is_enemy = |e: Entity| {
+        let belongs_to = owner_q.get(e).map_or(e, |b| b.player_entity);
+        let hidden = board.entity_pos(e).is_some_and(|pos| fog.hides(pos));
+        can_attack && !hidden && !game.allied(belongs_to, current_player_entity) && defender_q.get(e).is_ok()
+    };
+    let reachable = board.reachable(
+        Vec2I::from(moving.start_pos),
+        board.get_entity_pos(entity),
+        movable.movement,
+        movable.flying,
+        is_enemy,
+    );
+    let (move_color, attack_color) = move_colors(&settings);
+    let mut squares: Vec<_> = reachable.moves.into_iter().map(|p| (p, move_color)).collect();
+    squares.extend(reachable.attacks.into_iter().map(|p| (p, attack_color)));
+    ev_highlight.send(HighlightEvent::Show(squares));
+}
+
+fn clear_highlight(
+    mut ev_highlight: EventWriter<HighlightEvent>,
+) {
+    ev_highlight.send(HighlightEvent::Clear);
+}
+
 #[derive(Component)]
 struct AttackingComponent {
     attackee: Entity
@@ -270,6 +543,7 @@ fn attack_start(
     mut cursor: ResMut<Cursor>,
     defender_q: Query<&CanDefend>,
     mut state: ResMut<NextState<GameState>>,
+    mut undo: ResMut<MoveUndo>,
 ) {
 
     for (_e, ac) in attacking_q.iter() {
@@ -279,6 +553,8 @@ fn attack_start(
             debug!("Cannot be attacked as no defence, going back to MoveMoving state");
             state.set(GameState::MoveMoving);
         } else {
+            // The dice are about to be rolled, no going back now
+            undo.0.clear();
             ev_text.send(BottomTextEvent::clear());
             cursor.set_invisible();
             let v = board.get_entity_pos(ac.attackee);
@@ -292,12 +568,19 @@ fn attack_start(
 }
 
 fn attack_do(
+    locale: Res<Locale>,
     mut state: ResMut<NextState<GameState>>,
     mut commands: Commands,
     mut ev_explosion: EventReader<FinishedExplosion>,
     attacking_q: Query<(Entity, &AttackingComponent, &CanAttack)>,
     defender_q: Query<&CanDefend>,
     mut ev_kill: EventWriter<BoardKill>,
+    named_q: Query<&Named>,
+    owner_q: Query<&BelongsToPlayer>,
+    game: Res<Game>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+    mut ev_log: EventWriter<LogEvent>,
+    mut rng: ResMut<GameRng>,
 ) {
     for _e in ev_explosion.iter() {
         let (e, ac, at) = attacking_q.single();
@@ -306,14 +589,22 @@ fn attack_do(
         let candefend = defender_q.get(defender_entity).unwrap();
         let defence = candefend.defence;
         commands.entity(e).remove::<AttackingComponent>();
-        let attack = combat + rand::thread_rng().gen_range(1..10);
-        let def = defence + rand::thread_rng().gen_range(1..10);
+        let (attack, def) = combat_rolls(combat, defence, game.rules.combat_die, &mut rng.0);
         info!("Doing combat, base attack is {} base defence is {}. This attack is {} this defence is {}", combat, defence, attack, def);
+        let attacker_name = locale.name(&named_q.get(e).unwrap().name);
+        let defender_name = locale.name(&named_q.get(defender_entity).unwrap().name);
+        let key = if attack >= def { "combat.killed" } else { "combat.failed" };
+        let text = locale.format(key, &[&attacker_name, &defender_name]);
+        ev_text.send(BottomTextEvent::important(&text));
+        ev_log.send(LogEvent::player(&game, &text));
         if attack >= def {
             info!("ATTACK SUCCESSFUL, KILLED");
             ev_kill.send(BoardKill {
                 killer: e,
                 killed: defender_entity,
+                killer_owner: owner_q.get(e).ok().map(|b| b.player_entity),
+                killed_owner: owner_q.get(defender_entity).ok().map(|b| b.player_entity),
+                by: named_q.get(e).unwrap().name.clone(),
             });
         } else {
             info!("Attack not successful");
@@ -324,6 +615,7 @@ fn attack_do(
 }
 
 fn ranged_attack_setup(
+    locale: Res<Locale>,
     moving_q: Query<(Entity, &RangedCombat), With<RangedAttackComponent>>,
     mut cursor: ResMut<Cursor>,
     board: Res<GameBoard>,
@@ -333,40 +625,71 @@ fn ranged_attack_setup(
     cursor.hide_till_moved();
     let (entity, ranged) = moving_q.single();
     cursor.set_pos(Vec2::from(board.get_entity_pos(entity)));
-    let mut text = String::from("Ranged attack, range=");
-    text.push_str(&ranged.range.to_string());
-    ev_text.send(BottomTextEvent::from(&text));
+    ev_text.send(BottomTextEvent::from(&locale.format("combat.ranged", &[&ranged.range])));
 }
 
 fn ranged_attack_keyboard(
-    mut keys: ResMut<Input<KeyCode>>,
+    locale: Res<Locale>,
+    mut actions: ResMut<Input<Action>>,
     mut cursor: ResMut<Cursor>,
     moving_q: Query<(Entity, &RangedCombat), With<RangedAttackComponent>>,
     board: Res<GameBoard>,
     mut state: ResMut<NextState<GameState>>,
     mut ev_text: EventWriter<BottomTextEvent>,
+    mut undo: ResMut<MoveUndo>,
+    mut ev_ranged: EventWriter<RangedAttack>,
 ) {
-    if keys.just_pressed(KeyCode::K) {
+    if actions.just_pressed(Action::Cancel) {
         ev_text.send(BottomTextEvent::clear());
         state.set(GameState::MoveChoose);
         info!("cancelled attack");
     }
-    if keys.just_pressed(KeyCode::S) {
-        keys.reset(KeyCode::S);
+    if actions.just_pressed(Action::Select) {
+        actions.reset(Action::Select);
         ev_text.send(BottomTextEvent::clear());
         let cursor_pos = cursor.get_pos_v();
         let (entity, ranged) = moving_q.single();
         let from = board.get_entity_pos(entity);
         let distance = Vec2I::from(cursor_pos).distance(from);
         if distance <= ranged.range {
-            println!("CAN TARGET WITH RANGED");
+            debug!("CAN TARGET WITH RANGED");
+            undo.0.clear();
+            ev_ranged.send(RangedAttack);
             state.set(GameState::MoveChoose);
         } else {
-            ev_text.send(BottomTextEvent::from("Out of range"));
+            ev_text.send(BottomTextEvent::important(locale.text("move.out_of_range")));
             cursor.hide_till_moved();
         }
     }
 }
+fn ranged_attack_computer(
+    g: Res<Game>,
+    board: Res<GameBoard>,
+    fog: Res<Fog>,
+    pieces: ai::PieceQuery,
+    moving_q: Query<(Entity, &RangedCombat), With<RangedAttackComponent>>,
+    time: Res<Time>,
+    mut wait: Local<Option<Timer>>,
+    mut cursor: ResMut<Cursor>,
+    mut undo: ResMut<MoveUndo>,
+    mut ev_ranged: EventWriter<RangedAttack>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    let Ok((entity, ranged)) = moving_q.get_single() else {
+        return;
+    };
+    if !ai::thought(&mut wait, &time) {
+        return;
+    }
+    let view = ai::View::new(&g, &board, &fog, &pieces);
+    if let Some(target) = ai::choose_ranged_target(&view, entity, ranged.range) {
+        cursor.set_pos(Vec2::from(target));
+        undo.0.clear();
+        ev_ranged.send(RangedAttack);
+    }
+    state.set(GameState::MoveChoose);
+}
+
 fn ranged_attack_exit(
     mut commands: Commands,
     moving_q: Query<Entity, With<RangedAttackComponent>>,
@@ -376,18 +699,53 @@ fn ranged_attack_exit(
     cursor.set_type(CURSOR_BOX);
 }
 
+// Full stats of the piece under the cursor
+pub fn board_piece_info(
+    locale: Res<Locale>,
+    board: Res<GameBoard>,
+    query: Query<(&Named, &MoveableComponent)>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+    actions: Res<Input<Action>>,
+    cursor: Res<Cursor>,
+    stats: Query<(Option<&CanAttack>, Option<&CanDefend>, Option<&RangedCombat>)>,
+    fog: Res<Fog>,
+) {
+    let pos = cursor.get_pos_v();
+    if actions.just_pressed(Action::Info) && board.has_entity_at(pos) && !fog.hides(Vec2I::from(pos)) {
+        let e = board.get_entity(cursor.get_pos_v()).unwrap();
+        let (named, moveable) = query.get(e).unwrap();
+        let (attack, defend, ranged) = stats.get(e).unwrap();
+        let mut parts = vec![locale.name(&named.name).to_string()];
+        if let Some(attack) = attack {
+            parts.push(format!("C{}", attack.combat));
+        }
+        if let Some(ranged) = ranged {
+            parts.push(format!("R{}/{}", ranged.ranged_combat, ranged.range));
+        }
+        if let Some(defend) = defend {
+            parts.push(format!("D{}", defend.defence));
+        }
+        parts.push(format!("M{}", moveable.movement));
+        let text = parts.join(" ");
+        ev_text.send(BottomTextEvent::from(&text));
+    }
+}
+
 pub fn board_describe_piece(
+    locale: Res<Locale>,
     board: Res<GameBoard>,
     mut ev_cursor: EventReader<CursorMovedEvent>,
     mut query: Query<(&Named, &MoveableComponent, Option<&BelongsToPlayer>, &mut Transform,)>,
     mut playername: Query<&Named>,
     mut ev_text: EventWriter<BottomTextEvent>,
+    fog: Res<Fog>,
 ) {
     for cur in ev_cursor.iter() {
-        if board.has_entity_at(cur.0) {
+        // Anything in the fog is left for the square to look empty
+        if board.has_entity_at(cur.0) && !fog.hides(Vec2I::from(cur.0)) {
             let e = board.get_entity(cur.0).unwrap();
             let (named, _, belongs, _) = query.get_mut(e).unwrap();
-            let mut text = named.name.clone();
+            let mut text = locale.name(&named.name).to_string();
             if let Some(belongs) = belongs {
                 text.push('(');
                 let player_named = playername.get_mut(belongs.player_entity);
@@ -0,0 +1,233 @@
+use bevy::prelude::*;
+
+use crate::board::BoardPutEntity;
+use crate::campaign::{Campaign, BATTLES, BOOSTS};
+use crate::clock::Clock;
+use crate::display::*;
+use crate::game::{start_game, Game, GameRng};
+use crate::gamepad::{PadMenu, PadMenuItem};
+use crate::gamestate::GameState;
+use crate::input::{action_keys, Action};
+use crate::locale::Locale;
+use crate::messagelog::LogEvent;
+use crate::palette::wizard_colors;
+use crate::settings::Settings;
+use crate::spell::AllSpells;
+use crate::system;
+use super::nameentry::{NameEditor, MAX_NAME_LEN};
+use super::passturn::PassTurn;
+
+// The campaign map: the wizard as they stand, the battles won and to come, and the reward to
+// pick after a win. Battles are played as normal games, coming back here at the end.
+pub struct CampaignPlugin;
+
+impl Plugin for CampaignPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<CampaignMenu>()
+            .add_system(campaign_map_enter.in_schedule(OnEnter(GameState::CampaignMap)))
+            .add_system(campaign_map_keyboard_input.in_set(OnUpdate(GameState::CampaignMap)))
+            .add_system(campaign_map_draw.run_if(resource_changed::<Campaign>().or_else(resource_changed::<CampaignMenu>())).in_set(OnUpdate(GameState::CampaignMap)))
+            .add_system(system::despawn_screen::<CampaignMapScreen>.in_schedule(OnExit(GameState::CampaignMap)))
+
+            .add_system(campaign_start.in_set(OnUpdate(GameState::CampaignStart)))
+            ;
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+struct CampaignMapScreen;
+
+// The name being typed for a new campaign's wizard
+#[derive(Default, Resource)]
+struct CampaignMenu {
+    naming: Option<NameEditor>,
+}
+
+fn campaign_map_enter(
+    mut campaign: ResMut<Campaign>,
+    mut menu: ResMut<CampaignMenu>,
+) {
+    // Back from a battle, which has already been counted
+    campaign.in_battle = false;
+    menu.naming = None;
+}
+
+fn campaign_map_draw(
+    mut commands: Commands,
+    g: Res<Game>,
+    campaign: Res<Campaign>,
+    menu: Res<CampaignMenu>,
+    locale: Res<Locale>,
+    settings: Res<Settings>,
+    mut pad_menu: ResMut<PadMenu>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+    existing: Query<Entity, With<CampaignMapScreen>>,
+) {
+    for e in &existing {
+        commands.entity(e).despawn();
+    }
+    print_text(&centred(locale.text("campaign.title")), &mut commands, g.fah(), Vec2::new(0.0, 9.0), WHITE, CampaignMapScreen);
+    if let Some(editor) = &menu.naming {
+        print_text(&locale.format("menu.enter_name", &[&MAX_NAME_LEN]), &mut commands, g.fah(), Vec2::new(0.5, 7.0), WHITE, CampaignMapScreen);
+        editor.draw(&mut commands, g.fah(), Vec2::new(0.5, 6.0), CampaignMapScreen);
+        pad_menu.clear();
+        ev_text.send(BottomTextEvent::prompt(&centred(locale.text("setup.rename_done"))));
+        return;
+    }
+    let mut commands_shown = Vec::new();
+    if let Some(wizard) = &campaign.wizard {
+        let color = wizard_colors(&settings)[wizard.color_index];
+        print_wizard(&mut commands, g.tah(), Vec2::new(0.5, 8.0), wizard.character_icon as usize - 1, color, CampaignMapScreen);
+        print_text(&wizard.name, &mut commands, g.fah(), Vec2::new(1.5, 8.0), WHITE, CampaignMapScreen);
+        if wizard.extra_spells > 0 {
+            let extra = locale.format("campaign.extra_spells", &[&wizard.extra_spells]);
+            print_text(&extra, &mut commands, g.fah(), Vec2::new(9.0, 8.0), WHITE, CampaignMapScreen);
+        }
+        let stats = [
+            locale.format("campaign.stats", &[&wizard.combat, &wizard.defence]),
+            locale.format("campaign.stats_more", &[&wizard.manoeuvre, &wizard.magic_resistance]),
+        ];
+        for (i, line) in stats.iter().enumerate() {
+            print_text(line, &mut commands, g.fah(), Vec2::new(0.5, 7.0 - i as f32), WHITE, CampaignMapScreen);
+        }
+        // The battles in a line, those won in yellow and the next one marked
+        let left = (LINE_LEN - (BATTLES.len() * 3 - 2)) as f32 / 2.0;
+        for i in 0..BATTLES.len() {
+            let x = (i * 3) as f32 / 2.0 + left;
+            let (text, color) = match i.cmp(&campaign.won) {
+                std::cmp::Ordering::Less => ("*".to_string(), YELLOW),
+                std::cmp::Ordering::Equal => ((i + 1).to_string(), WHITE),
+                std::cmp::Ordering::Greater => ((i + 1).to_string(), GREY),
+            };
+            print_text(&text, &mut commands, g.fah(), Vec2::new(x, 5.0), color, CampaignMapScreen);
+            if i == campaign.won {
+                print_text("^", &mut commands, g.fah(), Vec2::new(x, 4.0), WHITE, CampaignMapScreen);
+            }
+            if i + 1 < BATTLES.len() {
+                print_text("-", &mut commands, g.fah(), Vec2::new(x + 0.5, 5.0), GREY, CampaignMapScreen);
+            }
+        }
+        let next = if campaign.finished() {
+            locale.text("campaign.complete").to_string()
+        } else {
+            let (rivals, level) = BATTLES[campaign.won];
+            locale.format("campaign.next", &[&(campaign.won + 1), &rivals, &level])
+        };
+        print_text(&centred(&next), &mut commands, g.fah(), Vec2::new(0.0, 3.0), WHITE, CampaignMapScreen);
+        // The rewards take the place of the record until one is picked
+        if campaign.reward_due {
+            let mut items = Vec::new();
+            for (i, boost) in BOOSTS.iter().enumerate() {
+                let key = char::from_digit(i as u32 + 1, 10).unwrap();
+                let pos = Vec2::new(if i % 2 == 0 { 0.5 } else { 7.5 }, 2.0 - (i / 2) as f32);
+                let line = format!("{key} {}", locale.text(boost.key()));
+                print_text(&line, &mut commands, g.fah(), pos, WHITE, CampaignMapScreen);
+                items.push(PadMenuItem::new(pos, line.chars().count(), key));
+            }
+            pad_menu.set(GameState::CampaignMap, items);
+            ev_text.send(BottomTextEvent::prompt(&centred(locale.text("campaign.pick_reward"))));
+            return;
+        }
+        let record = locale.format("campaign.record", &[&campaign.won, &campaign.lost]);
+        print_text(&centred(&record), &mut commands, g.fah(), Vec2::new(0.0, 2.0), WHITE, CampaignMapScreen);
+        if !campaign.finished() {
+            commands_shown.push(('S', "campaign.fight"));
+        }
+    } else {
+        print_text(&centred(locale.text("campaign.none")), &mut commands, g.fah(), Vec2::new(0.0, 5.0), WHITE, CampaignMapScreen);
+    }
+    commands_shown.push(('N', "campaign.new"));
+    let mut items = Vec::new();
+    for (i, (key, text)) in commands_shown.iter().enumerate() {
+        let pos = Vec2::new(0.5, 1.0 - i as f32);
+        let line = format!("{key} {}", locale.text(text));
+        print_text(&line, &mut commands, g.fah(), pos, WHITE, CampaignMapScreen);
+        items.push(PadMenuItem::new(pos, line.chars().count(), *key));
+    }
+    pad_menu.set(GameState::CampaignMap, items);
+    ev_text.send(BottomTextEvent::prompt(&centred(&locale.format("prompt.exit", &[&action_keys(&settings, Action::Back)]))));
+}
+
+fn campaign_map_keyboard_input(
+    mut state: ResMut<NextState<GameState>>,
+    mut char_evr: EventReader<ReceivedCharacter>,
+    keys: Res<Input<KeyCode>>,
+    mut actions: ResMut<Input<Action>>,
+    locale: Res<Locale>,
+    mut campaign: ResMut<Campaign>,
+    mut menu: ResMut<CampaignMenu>,
+    mut pad_menu: ResMut<PadMenu>,
+) {
+    // Only redrawn when the name actually changes
+    if let Some(editor) = menu.bypass_change_detection().naming.as_mut() {
+        let chars: Vec<char> = char_evr.iter().map(|ev| ev.char).collect();
+        // Back and Cancel can be keys which are typed into the name rather than acted on
+        let typing = chars.iter().any(|c| !c.is_control());
+        if keys.just_pressed(KeyCode::Return) {
+            if !editor.is_empty() {
+                campaign.start(editor.text(), &mut rand::thread_rng());
+                campaign.save();
+            }
+            menu.naming = None;
+            pad_menu.clear();
+        } else if editor.update_chars(&keys, &chars) {
+            menu.set_changed();
+        } else if !typing && (actions.just_pressed(Action::Back) || actions.just_pressed(Action::Cancel)) {
+            actions.reset_all();
+            menu.naming = None;
+            pad_menu.clear();
+        }
+        return;
+    }
+    if actions.just_pressed(Action::Back) || actions.just_pressed(Action::Cancel) {
+        actions.reset_all();
+        state.set(GameState::InitialMenu);
+        return;
+    }
+    for ev in &mut char_evr {
+        if campaign.reward_due {
+            if let Some(boost) = ev.char.to_digit(10).and_then(|d| BOOSTS.get((d as usize).wrapping_sub(1))) {
+                if campaign.can_boost(*boost) {
+                    campaign.boost(*boost);
+                    campaign.save();
+                }
+            }
+            continue;
+        }
+        match ev.char.to_ascii_uppercase() {
+            'N' => menu.naming = Some(NameEditor::new(locale.text("campaign.wizard"))),
+            'S' if campaign.wizard.is_some() && !campaign.finished() => state.set(GameState::CampaignStart),
+            _ => {}
+        }
+    }
+}
+
+// In place of naming the wizards, the campaign wizard takes on the next battle's rivals
+fn campaign_start(
+    mut commands: Commands,
+    mut g: ResMut<Game>,
+    mut rng: ResMut<GameRng>,
+    mut campaign: ResMut<Campaign>,
+    allspells: Res<AllSpells>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    mut clock: ResMut<Clock>,
+    mut pass: ResMut<PassTurn>,
+    mut state: ResMut<NextState<GameState>>,
+    mut ev_board_put: EventWriter<BoardPutEntity>,
+    mut ev_log: EventWriter<LogEvent>,
+) {
+    *rng = GameRng::default();
+    g.player_info = campaign.players(&allspells, &settings, &mut rng.0);
+    if g.player_info.is_empty() {
+        state.set(GameState::CampaignMap);
+        return;
+    }
+    g.players = g.player_info.len() as u8;
+    g.ai_level = campaign.ai_level();
+    start_game(&mut g, &mut commands, &mut ev_board_put, &mut ev_log, &locale);
+    clock.start_game(g.player_info.len());
+    campaign.in_battle = true;
+    pass.to(GameState::TurnMenu, &g, &settings, &mut state);
+}
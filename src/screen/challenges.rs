@@ -0,0 +1,224 @@
+use bevy::prelude::*;
+
+use crate::board::GameBoard;
+use crate::challenge::{ActiveChallenge, Challenges, Outcome};
+use crate::clock::Clock;
+use crate::creature::load_creatures;
+use crate::cursor::Cursor;
+use crate::display::*;
+use crate::game::{Game, GameRng};
+use crate::gamepad::{PadMenu, PadMenuItem};
+use crate::gamestate::GameState;
+use crate::input::{action_keys, any_just_pressed, Action};
+use crate::locale::Locale;
+use crate::messagelog::LogEvent;
+use crate::settings::Settings;
+use crate::spell::AllSpells;
+use crate::system;
+use super::passturn::PassTurn;
+
+// The list of challenges to pick from, and the screen saying how one went. A challenge is
+// played on the normal casting and movement screens, from its own starting position.
+pub struct ChallengesPlugin;
+
+impl Plugin for ChallengesPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<ChallengeDetails>()
+            .add_system(challenges_enter.in_schedule(OnEnter(GameState::Challenges)))
+            .add_system(challenges_keyboard_input.in_set(OnUpdate(GameState::Challenges)))
+            .add_system(challenges_draw.run_if(resource_changed::<ChallengeDetails>()).in_set(OnUpdate(GameState::Challenges)))
+            .add_system(system::despawn_screen::<ChallengesScreen>.in_schedule(OnExit(GameState::Challenges)))
+
+            .add_system(challenge_start.in_set(OnUpdate(GameState::ChallengeStart)))
+
+            .add_systems((challenge_over_setup, system::hide_board_entities).in_schedule(OnEnter(GameState::ChallengeOver)))
+            .add_system(challenge_over_keyboard_input.in_set(OnUpdate(GameState::ChallengeOver)))
+            .add_systems((
+                    system::despawn_screen::<ChallengesScreen>,
+                    super::gameover::clear_game,
+                    challenge_over_exit,
+                ).in_schedule(OnExit(GameState::ChallengeOver)))
+            ;
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+struct ChallengesScreen;
+
+// Only the first few fit on the screen
+const SHOWN: usize = 8;
+
+// The challenge being looked at before starting it, if any
+#[derive(Default, Resource)]
+struct ChallengeDetails(Option<usize>);
+
+fn challenges_enter(
+    mut details: ResMut<ChallengeDetails>,
+) {
+    details.0 = None;
+}
+
+fn challenges_draw(
+    mut commands: Commands,
+    g: Res<Game>,
+    challenges: Res<Challenges>,
+    details: Res<ChallengeDetails>,
+    locale: Res<Locale>,
+    settings: Res<Settings>,
+    mut pad_menu: ResMut<PadMenu>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+    existing: Query<Entity, With<ChallengesScreen>>,
+) {
+    for e in &existing {
+        commands.entity(e).despawn();
+    }
+    let back = action_keys(&settings, Action::Back);
+    if let Some(challenge) = details.0.and_then(|i| challenges.list.get(i)) {
+        print_text(&centred(&challenge.name), &mut commands, g.fah(), Vec2::new(0.0, 9.0), WHITE, ChallengesScreen);
+        for (i, line) in challenge.description.iter().enumerate() {
+            print_text(line, &mut commands, g.fah(), Vec2::new(0.5, 7.0 - i as f32), WHITE, ChallengesScreen);
+        }
+        if challenges.is_done(&challenge.name) {
+            print_text(&centred(locale.text("challenge.done_line")), &mut commands, g.fah(), Vec2::new(0.0, 1.0), YELLOW, ChallengesScreen);
+        }
+        let start = locale.text("challenge.start");
+        print_text(start, &mut commands, g.fah(), Vec2::new(0.5, 0.0), WHITE, ChallengesScreen);
+        pad_menu.set(GameState::Challenges, vec![PadMenuItem::new(Vec2::new(0.5, 0.0), start.chars().count(), 'S')]);
+        ev_text.send(BottomTextEvent::prompt(&centred(&locale.format("prompt.exit", &[&back]))));
+        return;
+    }
+    print_text(&centred(locale.text("challenge.title")), &mut commands, g.fah(), Vec2::new(0.0, 9.0), WHITE, ChallengesScreen);
+    if challenges.list.is_empty() {
+        print_text(&centred(locale.text("challenge.none")), &mut commands, g.fah(), Vec2::new(0.0, 5.0), WHITE, ChallengesScreen);
+    }
+    let mut items = Vec::new();
+    for (i, challenge) in challenges.list.iter().take(SHOWN).enumerate() {
+        let y = 8.0 - i as f32;
+        let key = char::from_digit(i as u32 + 1, 10).unwrap();
+        print_text(&key.to_string(), &mut commands, g.fah(), Vec2::new(0.5, y), WHITE, ChallengesScreen);
+        print_text(&challenge.name, &mut commands, g.fah(), Vec2::new(1.5, y), WHITE, ChallengesScreen);
+        if challenges.is_done(&challenge.name) {
+            print_text(locale.text("challenge.done"), &mut commands, g.fah(), Vec2::new(11.5, y), YELLOW, ChallengesScreen);
+        }
+        items.push(PadMenuItem::new(Vec2::new(0.5, y), 1, key));
+    }
+    pad_menu.set(GameState::Challenges, items);
+    let shown = challenges.list.len().min(SHOWN);
+    let prompt = if shown == 0 { locale.format("prompt.exit", &[&back]) } else { locale.format("menu.press_keys", &[&shown, &back]) };
+    ev_text.send(BottomTextEvent::prompt(&centred(&prompt)));
+}
+
+fn challenges_keyboard_input(
+    mut state: ResMut<NextState<GameState>>,
+    mut char_evr: EventReader<ReceivedCharacter>,
+    mut actions: ResMut<Input<Action>>,
+    challenges: Res<Challenges>,
+    mut details: ResMut<ChallengeDetails>,
+    mut active: ResMut<ActiveChallenge>,
+) {
+    if actions.just_pressed(Action::Back) || actions.just_pressed(Action::Cancel) {
+        actions.reset_all();
+        if details.0.is_some() {
+            details.0 = None;
+        } else {
+            state.set(GameState::InitialMenu);
+        }
+        return;
+    }
+    let shown = challenges.list.len().min(SHOWN);
+    for ev in &mut char_evr {
+        if let Some(i) = details.0 {
+            if ev.char.eq_ignore_ascii_case(&'s') {
+                active.challenge = Some(i);
+                state.set(GameState::ChallengeStart);
+            }
+        } else if let Some(d) = ev.char.to_digit(10) {
+            if (1..=shown).contains(&(d as usize)) {
+                details.0 = Some(d as usize - 1);
+            }
+        }
+    }
+}
+
+// In place of naming the wizards and setting up the game, everything comes from the challenge
+fn challenge_start(
+    mut commands: Commands,
+    mut g: ResMut<Game>,
+    mut rng: ResMut<GameRng>,
+    mut board: ResMut<GameBoard>,
+    mut active: ResMut<ActiveChallenge>,
+    challenges: Res<Challenges>,
+    allspells: Res<AllSpells>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    mut clock: ResMut<Clock>,
+    mut pass: ResMut<PassTurn>,
+    mut state: ResMut<NextState<GameState>>,
+    mut ev_log: EventWriter<LogEvent>,
+) {
+    let Some(challenge) = active.challenge.and_then(|i| challenges.list.get(i)) else {
+        state.set(GameState::Challenges);
+        return;
+    };
+    *rng = GameRng::seeded(challenge.seed);
+    let scenario = &challenge.scenario;
+    g.rules = scenario.rules;
+    let mut players = scenario.players(&allspells, &settings);
+    for player in &mut players {
+        player.roll_stats(&g.rules, &mut rng.0);
+    }
+    for player in players.iter_mut().skip(1) {
+        player.computer_controlled = true;
+        player.ai_level = challenge.ai_level;
+    }
+    scenario.spawn(&mut players, &load_creatures(), &mut commands, &g.tah(), &mut board);
+    g.players = players.len() as u8;
+    g.player_info = players;
+    g.round = 1;
+    ev_log.send(LogEvent::game(&g, &locale.format("log.challenge", &[&challenge.name])));
+    clock.start_game(g.player_info.len());
+    active.outcome = None;
+    pass.to(GameState::TurnMenu, &g, &settings, &mut state);
+}
+
+fn challenge_over_setup(
+    mut commands: Commands,
+    g: Res<Game>,
+    challenges: Res<Challenges>,
+    active: Res<ActiveChallenge>,
+    locale: Res<Locale>,
+    mut cursor: ResMut<Cursor>,
+    mut pad_menu: ResMut<PadMenu>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+) {
+    cursor.set_invisible();
+    pad_menu.clear();
+    if let Some(challenge) = active.challenge.and_then(|i| challenges.list.get(i)) {
+        print_text(&centred(&challenge.name), &mut commands, g.fah(), Vec2::new(0.0, 7.0), WHITE, ChallengesScreen);
+    }
+    let key = if active.outcome == Some(Outcome::Won) { "challenge.won" } else { "challenge.lost" };
+    print_text(&centred(locale.text(key)), &mut commands, g.fah(), Vec2::new(0.0, 5.0), WHITE, ChallengesScreen);
+    let rounds = locale.format("gameover.rounds", &[&g.round]);
+    print_text(&centred(&rounds), &mut commands, g.fah(), Vec2::new(0.0, 3.0), WHITE, ChallengesScreen);
+    ev_text.send(BottomTextEvent::clear());
+    ev_text.send(BottomTextEvent::prompt(&centred(locale.text("prompt.continue"))));
+}
+
+fn challenge_over_keyboard_input(
+    mut state: ResMut<NextState<GameState>>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Input<Action>>,
+) {
+    if any_just_pressed(&keys, &actions) {
+        keys.reset_all();
+        actions.reset_all();
+        state.set(GameState::Challenges);
+    }
+}
+
+fn challenge_over_exit(
+    mut active: ResMut<ActiveChallenge>,
+) {
+    *active = ActiveChallenge::default();
+}
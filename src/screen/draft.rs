@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+
+use crate::board::BoardPutEntity;
+use crate::clock::Clock;
+use crate::display::*;
+use crate::draft::{cpu_pick, drafter, Draft};
+use crate::game::{start_game, Game};
+use crate::gamepad::PadMenu;
+use crate::gamestate::GameState;
+use crate::locale::Locale;
+use crate::messagelog::LogEvent;
+use crate::settings::Settings;
+use crate::system;
+use super::passturn::PassTurn;
+use super::turnmenu::print_spell_list;
+
+// Long enough to see what a computer wizard took before the next pick
+const CPU_DELAY: f32 = 0.6;
+
+pub struct DraftPlugin;
+
+impl Plugin for DraftPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Draft>()
+            .add_system(draft_keyboard_input.in_set(OnUpdate(GameState::Draft)))
+            .add_system(draft_draw.run_if(resource_changed::<Draft>()).in_set(OnUpdate(GameState::Draft)))
+            .add_system(system::despawn_screen::<DraftScreen>.in_schedule(OnExit(GameState::Draft)))
+            ;
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+struct DraftScreen;
+
+fn draft_draw(
+    mut commands: Commands,
+    g: Res<Game>,
+    draft: Res<Draft>,
+    locale: Res<Locale>,
+    settings: Res<Settings>,
+    mut pad_menu: ResMut<PadMenu>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+    existing: Query<Entity, With<DraftScreen>>,
+) {
+    for e in &existing {
+        commands.entity(e).despawn();
+    }
+    let players = g.player_info.len();
+    let player = &g.player_info[drafter(draft.picks, players)];
+    let turn = locale.format("draft.turn", &[&player.name, &(draft.picks / players + 1), &g.rules.spell_count]);
+    print_text(&turn, &mut commands, g.fah(), Vec2::new(0.5, 9.0), WHITE, DraftScreen);
+    let items = print_spell_list(&draft.pool, &mut commands, g.fah(), &locale, &settings, DraftScreen);
+    if let Some((who, spell)) = &draft.last {
+        let last = locale.format("draft.last", &[&g.player_info[*who].name, &locale.name(spell)]);
+        print_text(&last, &mut commands, g.fah(), Vec2::new(0.5, 0.0), WHITE, DraftScreen);
+    }
+    if player.computer_controlled {
+        pad_menu.clear();
+        ev_text.send(BottomTextEvent::prompt(&centred(locale.text("draft.cpu"))));
+    } else {
+        pad_menu.set(GameState::Draft, items);
+        ev_text.send(BottomTextEvent::prompt(&centred(locale.text("draft.pick"))));
+    }
+}
+
+fn draft_keyboard_input(
+    mut char_evr: EventReader<ReceivedCharacter>,
+    mut state: ResMut<NextState<GameState>>,
+    mut g: ResMut<Game>,
+    mut draft: ResMut<Draft>,
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    mut clock: ResMut<Clock>,
+    mut pass: ResMut<PassTurn>,
+    mut ev_board_put: EventWriter<BoardPutEntity>,
+    mut ev_log: EventWriter<LogEvent>,
+    mut wait: Local<Option<Timer>>,
+) {
+    let players = g.player_info.len();
+    let player = drafter(draft.picks, players);
+    let mut pick = None;
+    for ev in &mut char_evr {
+        let c = ev.char.to_ascii_uppercase() as usize;
+        if (65..65 + draft.pool.len()).contains(&c) {
+            pick = Some(c - 65);
+        }
+    }
+    if g.player_info[player].computer_controlled {
+        let timer = wait.get_or_insert_with(|| Timer::from_seconds(CPU_DELAY, TimerMode::Once));
+        pick = timer.tick(time.delta()).finished().then(|| cpu_pick(&draft.pool));
+        if pick.is_some() {
+            *wait = None;
+        }
+    }
+    if let Some(i) = pick {
+        let spell = draft.take(i, players);
+        g.player_info[player].spells.spells.push(spell);
+        if draft.finished(players, g.rules.spell_count) {
+            start_game(&mut g, &mut commands, &mut ev_board_put, &mut ev_log, &locale);
+            if let Some(name) = g.rules.name() {
+                ev_log.send(LogEvent::game(&g, &locale.format("log.rules", &[&locale.text(name)])));
+            }
+            clock.start_game(players);
+            pass.to(GameState::TurnMenu, &g, &settings, &mut state);
+        }
+    }
+}
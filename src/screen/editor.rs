@@ -0,0 +1,479 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use crate::board::GameBoard;
+use crate::constants::{CHAOS, LAW};
+use crate::creature::{load_creatures, Creature};
+use crate::cursor::{Cursor, CURSOR_BOX};
+use crate::display::*;
+use crate::game::Game;
+use crate::gamepad::PadMenu;
+use crate::gamestate::GameState;
+use crate::input::{action_keys, Action};
+use crate::locale::Locale;
+use crate::palette::{wizard_colors, WIZARD_COUNT};
+use crate::player::deal_spells;
+use crate::rules::PRESETS;
+use crate::scenario::{Scenario, ScenarioCreature, ScenarioPiece, ScenarioWizard};
+use crate::settings::Settings;
+use crate::spell::AllSpells;
+use crate::system::{self, BoardEntity};
+use crate::vec::Vec2I;
+use super::nameentry::NameEditor;
+
+// Setting up a board by hand, to practise a position or get back to one that showed a bug.
+// Pieces are put down with the cursor, then the wizards' spells and alignment are set on a
+// screen of their own, and the whole thing is saved under scenarios/.
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Editor>()
+            .add_system(editor_enter.in_schedule(OnEnter(GameState::Editor)))
+            .add_system(editor_keyboard.in_set(OnUpdate(GameState::Editor)))
+            .add_system(editor_draw.run_if(resource_changed::<Editor>()).in_set(OnUpdate(GameState::Editor)))
+            .add_systems((editor_leave, system::hide_board_entities).in_schedule(OnExit(GameState::Editor)))
+
+            .add_system(editor_wizard_keyboard.in_set(OnUpdate(GameState::EditorWizard)))
+            .add_system(editor_wizard_draw.run_if(resource_changed::<Editor>()).in_set(OnUpdate(GameState::EditorWizard)))
+            .add_system(system::despawn_screen::<EditorWizardScreen>.in_schedule(OnExit(GameState::EditorWizard)))
+
+            .add_system(editor_save_enter.in_schedule(OnEnter(GameState::EditorSave)))
+            .add_system(editor_save_keyboard.in_set(OnUpdate(GameState::EditorSave)))
+            .add_system(editor_save_draw.run_if(resource_changed::<Editor>()).in_set(OnUpdate(GameState::EditorSave)))
+            .add_system(system::despawn_screen::<EditorSaveScreen>.in_schedule(OnExit(GameState::EditorSave)))
+            ;
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+struct EditorWizardScreen;
+
+#[derive(Component, Clone, Copy)]
+struct EditorSaveScreen;
+
+// A wizard can't be given more spells than there are letters on their screen
+const MAX_SPELLS: usize = 14;
+
+#[derive(Default, Resource)]
+struct Editor {
+    scenario: Scenario,
+    // Loaded on the way in, with their names in order
+    creatures: HashMap<String, Creature>,
+    names: Vec<String>,
+    // What Select puts down: 0 for a wizard, then each creature in name order
+    brush: usize,
+    // The wizard new creatures belong to
+    owner: usize,
+    // The wizard on the spells screen, and the spell picked there
+    wizard: usize,
+    slot: usize,
+    naming: NameEditor,
+    // Asking before the name typed replaces a scenario already saved
+    overwrite: bool,
+    // Typing on the other screens moves the hidden cursor too, so it is put back afterwards
+    cursor_pos: Vec2,
+    started: bool,
+}
+
+impl Editor {
+    fn brush_name(&self) -> Option<&str> {
+        self.brush.checked_sub(1).map(|i| self.names[i].as_str())
+    }
+    fn place(&mut self, pos: Vec2I, allspells: &AllSpells, locale: &Locale) -> Result<(), &'static str> {
+        let Some(name) = self.brush_name() else {
+            let wizards = &self.scenario.wizards;
+            if wizards.len() >= WIZARD_COUNT {
+                return Err("editor.too_many");
+            }
+            let color_index = (0..WIZARD_COUNT).find(|c| !wizards.iter().any(|w| w.color_index == *c)).unwrap();
+            let wizard = ScenarioWizard {
+                name: locale.format("editor.wizard_name", &[&(wizards.len() + 1)]),
+                character_icon: (wizards.len() % 8) as u8 + 1,
+                color_index,
+                x: pos.x,
+                y: pos.y,
+                spells: deal_spells(allspells, self.scenario.rules.spell_count, &mut rand::thread_rng()).iter().map(|s| s.name()).collect(),
+                law_chaos: 0,
+            };
+            self.scenario.wizards.push(wizard);
+            return Ok(());
+        };
+        if self.scenario.wizards.is_empty() {
+            return Err("editor.need_wizard");
+        }
+        let creature = ScenarioCreature { name: name.to_string(), owner: self.owner, x: pos.x, y: pos.y, illusion: false };
+        self.scenario.creatures.push(creature);
+        Ok(())
+    }
+}
+
+fn keys_help(ev_text: &mut EventWriter<BottomTextEvent>, locale: &Locale, settings: &Settings) {
+    for key in ["editor.keys_place", "editor.keys_piece"] {
+        ev_text.send(BottomTextEvent::important(locale.text(key)));
+    }
+    ev_text.send(BottomTextEvent::important(&locale.format("editor.keys_file", &[&action_keys(settings, Action::Back)])));
+    ev_text.send(BottomTextEvent::important(locale.text("editor.keys_rules")));
+}
+
+fn editor_enter(
+    mut editor: ResMut<Editor>,
+    mut cursor: ResMut<Cursor>,
+    mut pad_menu: ResMut<PadMenu>,
+    locale: Res<Locale>,
+    settings: Res<Settings>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+) {
+    pad_menu.clear();
+    cursor.set_type(CURSOR_BOX);
+    cursor.set_pos(editor.cursor_pos);
+    cursor.set_visible();
+    if !editor.started {
+        editor.started = true;
+        editor.creatures = load_creatures();
+        editor.names = editor.creatures.keys().cloned().collect();
+        editor.names.sort();
+        keys_help(&mut ev_text, &locale, &settings);
+    }
+    // Drawn again, which puts the pieces hidden by the other screens back
+    editor.set_changed();
+}
+
+fn editor_leave(
+    mut editor: ResMut<Editor>,
+    mut cursor: ResMut<Cursor>,
+) {
+    editor.cursor_pos = cursor.get_pos_v();
+    cursor.set_invisible();
+}
+
+// Every piece is put down again from the scenario whenever anything changes
+fn editor_draw(
+    mut commands: Commands,
+    g: Res<Game>,
+    editor: Res<Editor>,
+    allspells: Res<AllSpells>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    mut board: ResMut<GameBoard>,
+    pieces: Query<Entity, With<BoardEntity>>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+) {
+    for e in &pieces {
+        commands.entity(e).despawn_recursive();
+    }
+    board.clear();
+    let scenario = &editor.scenario;
+    let mut players = scenario.players(&allspells, &settings);
+    scenario.spawn(&mut players, &editor.creatures, &mut commands, &g.tah(), &mut board);
+    for player in &players {
+        commands.entity(player.handle.unwrap()).insert(Visibility::Visible);
+    }
+    let text = match (editor.brush_name(), scenario.wizards.get(editor.owner)) {
+        (None, _) => locale.text("editor.brush_wizard").to_string(),
+        (Some(_), None) => locale.text("editor.need_wizard").to_string(),
+        (Some(name), Some(owner)) => locale.format("editor.brush", &[&locale.name(name), &owner.name]),
+    };
+    ev_text.send(BottomTextEvent::from(&text));
+}
+
+fn editor_keyboard(
+    mut keys: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Input<Action>>,
+    cursor: Res<Cursor>,
+    mut editor: ResMut<Editor>,
+    allspells: Res<AllSpells>,
+    locale: Res<Locale>,
+    settings: Res<Settings>,
+    mut state: ResMut<NextState<GameState>>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+    mut commands: Commands,
+    mut board: ResMut<GameBoard>,
+    pieces: Query<Entity, With<BoardEntity>>,
+) {
+    if actions.just_pressed(Action::Back) {
+        actions.reset(Action::Back);
+        for e in &pieces {
+            commands.entity(e).despawn_recursive();
+        }
+        board.clear();
+        *editor = Editor::default();
+        ev_text.send(BottomTextEvent::clear());
+        state.set(GameState::InitialMenu);
+        return;
+    }
+    if actions.just_pressed(Action::Info) {
+        keys_help(&mut ev_text, &locale, &settings);
+    }
+    let pos = Vec2I::from(cursor.get_pos_v());
+    let under = editor.scenario.piece_at(pos);
+    if actions.just_pressed(Action::Select) {
+        actions.reset(Action::Select);
+        if let Some(piece) = under {
+            editor.scenario.remove(piece);
+            let wizards = editor.scenario.wizards.len();
+            editor.owner = editor.owner.min(wizards.saturating_sub(1));
+        } else if let Err(message) = editor.place(pos, &allspells, &locale) {
+            ev_text.send(BottomTextEvent::important(locale.text(message)));
+        }
+    }
+    let brushes = editor.names.len() + 1;
+    if keys.just_pressed(KeyCode::Key1) {
+        keys.reset(KeyCode::Key1);
+        editor.brush = (editor.brush + brushes - 1) % brushes;
+    }
+    if keys.just_pressed(KeyCode::Key2) {
+        keys.reset(KeyCode::Key2);
+        editor.brush = (editor.brush + 1) % brushes;
+    }
+    let wizards = editor.scenario.wizards.len();
+    if keys.just_pressed(KeyCode::Key3) && wizards > 0 {
+        keys.reset(KeyCode::Key3);
+        // A creature under the cursor goes to the next wizard, otherwise the next one put down does
+        if let Some(ScenarioPiece::Creature(i)) = under {
+            let scenario = &mut editor.scenario;
+            let creature = &mut scenario.creatures[i];
+            creature.owner = (creature.owner + 1) % wizards;
+            let owner = &scenario.wizards[creature.owner].name;
+            ev_text.send(BottomTextEvent::important(&locale.format("editor.owner", &[&locale.name(&creature.name), owner])));
+        } else {
+            editor.owner = (editor.owner + 1) % wizards;
+        }
+    }
+    if keys.just_pressed(KeyCode::Key4) {
+        keys.reset(KeyCode::Key4);
+        if let Some(ScenarioPiece::Creature(i)) = under {
+            let creature = &mut editor.scenario.creatures[i];
+            creature.illusion = !creature.illusion;
+            let key = if creature.illusion { "editor.illusion" } else { "editor.real" };
+            ev_text.send(BottomTextEvent::important(&locale.format(key, &[&locale.name(&creature.name)])));
+        }
+    }
+    if keys.just_pressed(KeyCode::Key5) {
+        keys.reset(KeyCode::Key5);
+        if let Some(ScenarioPiece::Wizard(i)) = under {
+            editor.wizard = i;
+            editor.slot = 0;
+            state.set(GameState::EditorWizard);
+        }
+    }
+    if keys.just_pressed(KeyCode::Key6) {
+        keys.reset(KeyCode::Key6);
+        state.set(GameState::EditorSave);
+    }
+    if keys.just_pressed(KeyCode::Key7) {
+        keys.reset(KeyCode::Key7);
+        load_next(&mut editor, &locale, &mut ev_text);
+    }
+    if keys.just_pressed(KeyCode::Key8) {
+        keys.reset(KeyCode::Key8);
+        // Round the presets, from whichever the scenario has
+        let next = PRESETS.iter().position(|(_, rules)| *rules == editor.scenario.rules).map_or(0, |i| (i + 1) % PRESETS.len());
+        let (name, rules) = PRESETS[next];
+        editor.scenario.rules = rules;
+        ev_text.send(BottomTextEvent::important(&locale.format("setup.rules_line", &[&locale.text(name)])));
+    }
+}
+
+// The saved scenario after the one being edited, round to the first
+fn load_next(editor: &mut Editor, locale: &Locale, ev_text: &mut EventWriter<BottomTextEvent>) {
+    let saved = Scenario::saved();
+    if saved.is_empty() {
+        ev_text.send(BottomTextEvent::important(locale.text("editor.none_saved")));
+        return;
+    }
+    let next = saved.iter().position(|n| *n == editor.scenario.name).map_or(0, |i| (i + 1) % saved.len());
+    match Scenario::load(&saved[next]) {
+        Ok(scenario) => {
+            editor.scenario = scenario;
+            editor.owner = 0;
+            ev_text.send(BottomTextEvent::important(&locale.format("editor.loaded", &[&saved[next]])));
+        }
+        Err(e) => {
+            warn!("{e}");
+            ev_text.send(BottomTextEvent::important(&locale.format("editor.load_failed", &[&saved[next]])));
+        }
+    }
+}
+
+fn editor_wizard_draw(
+    mut commands: Commands,
+    g: Res<Game>,
+    editor: Res<Editor>,
+    locale: Res<Locale>,
+    settings: Res<Settings>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+    existing: Query<Entity, With<EditorWizardScreen>>,
+) {
+    for e in &existing {
+        commands.entity(e).despawn();
+    }
+    let Some(wizard) = editor.scenario.wizards.get(editor.wizard) else {
+        return;
+    };
+    let color = wizard_colors(&settings)[wizard.color_index];
+    print_wizard(&mut commands, g.tah(), Vec2::new(0.5, 9.0), wizard.character_icon as usize - 1, color, EditorWizardScreen);
+    print_text(&wizard.name, &mut commands, g.fah(), Vec2::new(1.5, 9.0), WHITE, EditorWizardScreen);
+    let side = match wizard.law_chaos {
+        0 => "",
+        a if a > 0 => LAW,
+        _ => CHAOS,
+    };
+    let alignment = locale.format("editor.alignment", &[&format!("{}{side}", wizard.law_chaos.unsigned_abs())]);
+    print_text(&alignment, &mut commands, g.fah(), Vec2::new(8.5, 9.0), WHITE, EditorWizardScreen);
+    // One past the end is where a new spell goes
+    let slots = (wizard.spells.len() + 1).min(MAX_SPELLS);
+    for i in 0..slots {
+        let key = (b'A' + i as u8) as char;
+        let name = wizard.spells.get(i).map_or("-", |s| locale.name(s));
+        let pos = Vec2::new(if i % 2 == 1 { 7.5 } else { 0.5 }, 8.0 - (i / 2) as f32);
+        let color = if i == editor.slot { YELLOW } else { WHITE };
+        print_text(&format!("{key}. {name}"), &mut commands, g.fah(), pos, color, EditorWizardScreen);
+    }
+    print_text(locale.text("editor.spell_keys"), &mut commands, g.fah(), Vec2::new(0.5, 1.0), WHITE, EditorWizardScreen);
+    let keys = locale.format("editor.wizard_keys", &[&action_keys(&settings, Action::Back)]);
+    print_text(&keys, &mut commands, g.fah(), Vec2::new(0.5, 0.0), WHITE, EditorWizardScreen);
+    ev_text.send(BottomTextEvent::prompt(&centred(locale.text("editor.pick_slot"))));
+}
+
+fn editor_wizard_keyboard(
+    keys: Res<Input<KeyCode>>,
+    mut actions: ResMut<Input<Action>>,
+    mut char_evr: EventReader<ReceivedCharacter>,
+    mut editor: ResMut<Editor>,
+    allspells: Res<AllSpells>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    if actions.just_pressed(Action::Back) {
+        actions.reset(Action::Back);
+        state.set(GameState::Editor);
+        return;
+    }
+    let slot = editor.slot;
+    let i = editor.wizard;
+    let spells = editor.scenario.wizards[i].spells.len();
+    for ev in &mut char_evr {
+        let c = ev.char.to_ascii_uppercase();
+        if c.is_ascii_uppercase() {
+            let picked = (c as u8 - b'A') as usize;
+            if picked <= spells && picked < MAX_SPELLS {
+                editor.slot = picked;
+            }
+        }
+    }
+    let catalogue: Vec<String> = allspells.iter().map(|s| s.name()).collect();
+    let step = if keys.just_pressed(KeyCode::Key1) {
+        Some(catalogue.len() - 1)
+    } else if keys.just_pressed(KeyCode::Key2) {
+        Some(1)
+    } else {
+        None
+    };
+    if let Some(step) = step {
+        let wizard = &mut editor.scenario.wizards[i];
+        let current = wizard.spells.get(slot).and_then(|s| catalogue.iter().position(|c| c == s));
+        // An empty slot starts from the first spell either way
+        let next = current.map_or(0, |c| (c + step) % catalogue.len());
+        match wizard.spells.get_mut(slot) {
+            Some(spell) => spell.clone_from(&catalogue[next]),
+            None => wizard.spells.push(catalogue[next].clone()),
+        }
+    }
+    if keys.just_pressed(KeyCode::Key3) && slot < spells {
+        editor.scenario.wizards[i].spells.remove(slot);
+    }
+    if keys.just_pressed(KeyCode::Key4) {
+        let wizard = &mut editor.scenario.wizards[i];
+        wizard.law_chaos = wizard.law_chaos.saturating_sub(1);
+    }
+    if keys.just_pressed(KeyCode::Key5) {
+        let wizard = &mut editor.scenario.wizards[i];
+        wizard.law_chaos = wizard.law_chaos.saturating_add(1);
+    }
+    if keys.just_pressed(KeyCode::Key6) {
+        editor.scenario.wizards[i].spells = deal_spells(&allspells, editor.scenario.rules.spell_count, &mut rand::thread_rng()).iter().map(|s| s.name()).collect();
+        editor.slot = 0;
+    }
+}
+
+fn editor_save_enter(
+    mut editor: ResMut<Editor>,
+) {
+    editor.naming = NameEditor::new(&editor.scenario.name);
+    editor.overwrite = false;
+}
+
+fn editor_save_draw(
+    mut commands: Commands,
+    g: Res<Game>,
+    editor: Res<Editor>,
+    locale: Res<Locale>,
+    settings: Res<Settings>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+    existing: Query<Entity, With<EditorSaveScreen>>,
+) {
+    for e in &existing {
+        commands.entity(e).despawn();
+    }
+    print_text(locale.text("editor.save_title"), &mut commands, g.fah(), Vec2::new(0.5, 8.0), WHITE, EditorSaveScreen);
+    editor.naming.draw(&mut commands, g.fah(), Vec2::new(0.5, 7.0), EditorSaveScreen);
+    let prompt = if editor.overwrite {
+        locale.format("editor.overwrite", &[&editor.naming.text(), &action_keys(&settings, Action::Yes), &action_keys(&settings, Action::No)])
+    } else {
+        locale.text("setup.rename_done").to_string()
+    };
+    ev_text.send(BottomTextEvent::prompt(&centred(&prompt)));
+}
+
+fn editor_save_keyboard(
+    keys: Res<Input<KeyCode>>,
+    actions: Res<Input<Action>>,
+    mut char_evr: EventReader<ReceivedCharacter>,
+    mut editor: ResMut<Editor>,
+    locale: Res<Locale>,
+    mut state: ResMut<NextState<GameState>>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+) {
+    let chars: Vec<char> = char_evr.iter().map(|ev| ev.char).collect();
+    if editor.overwrite {
+        if actions.just_pressed(Action::Yes) {
+            save_scenario(&mut editor, &locale, &mut ev_text);
+            state.set(GameState::Editor);
+        } else if actions.just_pressed(Action::No) || actions.just_pressed(Action::Cancel) {
+            editor.overwrite = false;
+        }
+        return;
+    }
+    // Only drawn again when the name actually changes
+    if editor.bypass_change_detection().naming.update_chars(&keys, &chars) {
+        editor.set_changed();
+        return;
+    }
+    // Select and Cancel can be letters, which are typed into the name rather than acted on
+    if chars.iter().any(|c| !c.is_control()) {
+        return;
+    }
+    if actions.just_pressed(Action::Cancel) {
+        state.set(GameState::Editor);
+    } else if (keys.just_pressed(KeyCode::Return) || actions.just_pressed(Action::Select)) && !editor.naming.is_empty() {
+        if Scenario::saved().contains(&editor.naming.text()) {
+            editor.overwrite = true;
+            return;
+        }
+        save_scenario(&mut editor, &locale, &mut ev_text);
+        state.set(GameState::Editor);
+    }
+}
+
+fn save_scenario(editor: &mut Editor, locale: &Locale, ev_text: &mut EventWriter<BottomTextEvent>) {
+    editor.scenario.name = editor.naming.text();
+    let text = match editor.scenario.save() {
+        Ok(()) => locale.format("editor.saved", &[&editor.scenario.name]),
+        Err(e) => {
+            warn!("{e}");
+            locale.text("editor.save_failed").to_string()
+        }
+    };
+    ev_text.send(BottomTextEvent::important(&text));
+}
@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+
+use crate::board::{BoardKill, BoardRemove, GameBoard};
+use crate::campaign::Campaign;
+use crate::challenge::ActiveChallenge;
+use crate::cursor::Cursor;
+use crate::display::*;
+use crate::game::Game;
+use crate::gamestate::GameState;
+use crate::input::{any_just_pressed, Action};
+use crate::locale::Locale;
+use crate::messagelog::{LogEvent, MessageLog};
+use crate::profile::Profiles;
+use crate::player::{team_letter, Death};
+use crate::system::{self, BoardEntity};
+
+pub struct GameOverPlugin;
+
+impl Plugin for GameOverPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_system(record_kill)
+            .add_systems((game_over_setup, system::hide_board_entities).in_schedule(OnEnter(GameState::GameOver)))
+            .add_system(game_over_keyboard_input.in_set(OnUpdate(GameState::GameOver)))
+            .add_system(system::despawn_screen::<GameOverScreen>.in_schedule(OnExit(GameState::GameOver)))
+            .add_system(clear_game.in_schedule(OnExit(GameState::GameStats)))
+            ;
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+struct GameOverScreen;
+
+// Kills are added to the stats of the wizards on both sides. A wizard is out of the game when
+// killed, and everything they made goes with them.
+fn record_kill(
+    mut ev_kill: EventReader<BoardKill>,
+    mut g: ResMut<Game>,
+    locale: Res<Locale>,
+    mut state: ResMut<NextState<GameState>>,
+    mut ev_remove: EventWriter<BoardRemove>,
+    mut ev_log: EventWriter<LogEvent>,
+    challenge: Res<ActiveChallenge>,
+    campaign: Res<Campaign>,
+) {
+    for kill in &mut ev_kill {
+        let killer = kill.killer_owner.unwrap_or(kill.killer);
+        let by = kill.by.clone();
+        let killer_name = match g.player_info.iter_mut().find(|p| p.handle == Some(killer)) {
+            Some(player) => {
+                player.stats.kill(&by);
+                player.name.clone()
+            }
+            None => by.clone(),
+        };
+        if let Some(owner) = kill.killed_owner {
+            if let Some(player) = g.player_info.iter_mut().find(|p| p.handle == Some(owner)) {
+                player.creations.retain(|e| *e != kill.killed);
+                player.stats.creatures_lost += 1;
+            }
+        }
+        let Some(dead) = g.player_info.iter().position(|p| p.alive && p.handle == Some(kill.killed)) else {
+            continue;
+        };
+        if let Some(player) = g.player_info.iter_mut().find(|p| p.handle == Some(killer)) {
+            player.stats.wizards_killed += 1;
+        }
+        let round = g.round;
+        let player = &mut g.player_info[dead];
+        player.alive = false;
+        player.death = Some(Death { round, killer: killer_name, by });
+        for e in player.creations.drain(..) {
+            ev_remove.send(BoardRemove(e));
+        }
+        info!("Wizard {} is dead, {} left", g.player_info[dead].name, g.living_players());
+        ev_log.send(LogEvent::game(&g, &locale.format("log.wizard_killed", &[&g.player_info[dead].name])));
+        // A challenge decides for itself when it's over, and a campaign battle is lost as soon
+        // as the campaign wizard is
+        let campaign_lost = campaign.in_battle && dead == 0;
+        if g.living_sides() <= 1 && !challenge.is_active() || campaign_lost {
+            state.set(GameState::GameOver);
+        }
+    }
+}
+
+fn game_over_setup(
+    mut commands: Commands,
+    g: Res<Game>,
+    locale: Res<Locale>,
+    mut profiles: ResMut<Profiles>,
+    mut cursor: ResMut<Cursor>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+    mut ev_log: EventWriter<LogEvent>,
+) {
+    cursor.set_invisible();
+    // More than one side left standing when the rounds ran out is a draw
+    let winners: Vec<usize> = if g.living_sides() == 1 {
+        (0..g.player_info.len()).filter(|i| g.player_info[*i].alive).collect()
+    } else {
+        Vec::new()
+    };
+    profiles.record_game(&g.player_info, &winners);
+    print_text(&centred(locale.text("gameover.title")), &mut commands, g.fah(), Vec2::new(0.0, 8.0), WHITE, GameOverScreen);
+    if winners.is_empty() {
+        print_text(&centred(locale.text("gameover.draw")), &mut commands, g.fah(), Vec2::new(0.0, 6.0), WHITE, GameOverScreen);
+    }
+    if let Some(first) = winners.first().map(|w| &g.player_info[*w]) {
+        // The whole team left standing shares the win
+        let text = match first.team {
+            Some(team) if winners.len() > 1 => locale.format("gameover.team_winner", &[&team_letter(team)]),
+            _ => locale.format("gameover.winner", &[&first.name]),
+        };
+        print_text(&centred(&text), &mut commands, g.fah(), Vec2::new(0.0, 6.0), WHITE, GameOverScreen);
+        let left = 7.0 - (winners.len() - 1) as f32;
+        for (k, w) in winners.iter().enumerate() {
+            let player = &g.player_info[*w];
+            print_wizard(&mut commands, g.tah(), Vec2::new((k as f32).mul_add(2.0, left), 4.5), player.character_icon as usize - 1, player.color, GameOverScreen);
+        }
+        ev_log.send(LogEvent::game(&g, &text));
+    }
+    let rounds = locale.format("gameover.rounds", &[&g.round]);
+    print_text(&centred(&rounds), &mut commands, g.fah(), Vec2::new(0.0, 3.0), WHITE, GameOverScreen);
+    ev_text.send(BottomTextEvent::prompt(&centred(locale.text("prompt.continue"))));
+}
+
+fn game_over_keyboard_input(
+    mut state: ResMut<NextState<GameState>>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Input<Action>>,
+) {
+    if any_just_pressed(&keys, &actions) {
+        keys.reset_all();
+        actions.reset_all();
+        state.set(GameState::GameStats);
+    }
+}
+
+// Everything from the finished game goes, ready for the menu to start another
+pub fn clear_game(
+    mut commands: Commands,
+    mut g: ResMut<Game>,
+    mut board: ResMut<GameBoard>,
+    mut log: ResMut<MessageLog>,
+    pieces: Query<Entity, With<BoardEntity>>,
+) {
+    for e in &pieces {
+        commands.entity(e).despawn_recursive();
+    }
+    board.clear();
+    g.reset();
+    log.entries.clear();
+}
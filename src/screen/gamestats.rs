@@ -0,0 +1,197 @@
+use bevy::prelude::*;
+
+use crate::campaign::Campaign;
+use crate::display::*;
+use crate::game::Game;
+use crate::gamepad::PadMenu;
+use crate::gamestate::GameState;
+use crate::input::{action_keys, Action, Direction};
+use crate::locale::Locale;
+use crate::player::Player;
+use crate::settings::Settings;
+use crate::system;
+
+pub struct GameStatsPlugin;
+
+impl Plugin for GameStatsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<StatsPage>()
+            .add_system(game_stats_setup.in_schedule(OnEnter(GameState::GameStats)))
+            .add_system(game_stats_keyboard_input.in_set(OnUpdate(GameState::GameStats)))
+            .add_system(game_stats_draw.run_if(resource_changed::<StatsPage>()).in_set(OnUpdate(GameState::GameStats)))
+            .add_system(system::despawn_screen::<GameStatsScreen>.in_schedule(OnExit(GameState::GameStats)))
+            ;
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+struct GameStatsScreen;
+
+// Kills listed for each wizard, the rest are left off
+const KILLS_SHOWN: usize = 2;
+// The alignment graph runs along the bottom of the screen, one column per round until it
+// runs out of room
+const GRAPH_COLUMNS: usize = 28;
+const GRAPH_Y: f32 = 0.0;
+const GRAPH_HEIGHT: f32 = 0.75;
+
+// The wizard whose game is being shown
+#[derive(Default, Resource)]
+struct StatsPage(usize);
+
+fn game_stats_setup(
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    mut page: ResMut<StatsPage>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+) {
+    page.0 = 0;
+    let keys = |action| action_keys(&settings, action);
+    let text = locale.format("stats.keys", &[
+        &keys(Action::Move(Direction::Left)),
+        &keys(Action::Move(Direction::Right)),
+        &keys(Action::Cancel),
+    ]);
+    ev_text.send(BottomTextEvent::prompt(&text));
+}
+
+fn game_stats_draw(
+    mut commands: Commands,
+    g: Res<Game>,
+    page: Res<StatsPage>,
+    locale: Res<Locale>,
+    mut pad_menu: ResMut<PadMenu>,
+    existing: Query<Entity, With<GameStatsScreen>>,
+) {
+    for e in &existing {
+        commands.entity(e).despawn();
+    }
+    pad_menu.clear();
+    let Some(player) = g.player_info.get(page.0) else {
+        return;
+    };
+    print_wizard(&mut commands, g.tah(), Vec2::new(0.5, 9.0), player.character_icon as usize - 1, player.color, GameStatsScreen);
+    print_text(&player.name, &mut commands, g.fah(), Vec2::new(1.5, 9.0), WHITE, GameStatsScreen);
+    let page_text = format!("{}/{}", page.0 + 1, g.player_info.len());
+    print_text(&page_text, &mut commands, g.fah(), Vec2::new(13.5, 9.0), WHITE, GameStatsScreen);
+    for (i, line) in stats_lines(player, &locale).iter().enumerate() {
+        print_text(line, &mut commands, g.fah(), Vec2::new(0.0, 8.0 - i as f32), WHITE, GameStatsScreen);
+    }
+    // Side by side under the heading
+    for (i, (creature, count)) in player.stats.top_kills(KILLS_SHOWN).into_iter().enumerate() {
+        let pos = Vec2::new((i as f32).mul_add(7.5, 0.5), 2.0);
+        print_text(&format!("{} {count}", locale.name(creature)), &mut commands, g.fah(), pos, WHITE, GameStatsScreen);
+    }
+    print_text(locale.text("stats.alignment"), &mut commands, g.fah(), Vec2::new(0.0, GRAPH_Y + 1.0), WHITE, GameStatsScreen);
+    draw_alignment(&mut commands, &graph_columns(&player.alignment, GRAPH_COLUMNS));
+}
+
+fn stats_lines(player: &Player, locale: &Locale) -> Vec<String> {
+    let stats = &player.stats;
+    let mut lines = vec![
+        locale.format("stats.spells", &[&stats.spells_cast_total(), &stats.spells_failed]),
+        locale.format("stats.illusions", &[&stats.illusions_cast, &stats.illusions_disbelieved]),
+        locale.format("stats.creatures", &[&stats.creatures_summoned, &stats.creatures_lost]),
+    ];
+    match &player.death {
+        Some(death) => {
+            lines.push(locale.format("stats.died", &[&death.round]));
+            lines.push(if death.by == death.killer {
+                locale.format("stats.killed_by", &[&death.killer])
+            } else {
+                locale.format("stats.killed_by_creature", &[&locale.name(&death.by), &death.killer])
+            });
+        }
+        None => lines.extend([locale.text("stats.survived").to_string(), String::new()]),
+    }
+    let kills = if stats.kills.is_empty() { "stats.no_kills" } else { "stats.kills" };
+    lines.push(locale.text(kills).to_string());
+    lines
+}
+
+// Law above the line and chaos below, scaled so the furthest the wizard went fills the height
+fn draw_alignment(commands: &mut Commands, columns: &[i8]) {
+    let furthest = columns.iter().map(|a| a.unsigned_abs()).max().unwrap_or(0).max(1);
+    commands.spawn(SpriteBundle {
+        sprite: Sprite {
+            color: GREY,
+            custom_size: Some(Vec2::new(GRAPH_COLUMNS as f32 / 2.0, 1.0 / 16.0)),
+            ..default()
+        },
+        transform: Transform::from_translation(Vec2::new(GRAPH_COLUMNS as f32 / 4.0 - 0.25, GRAPH_Y).extend(1.0)),
+        ..default()
+    }).insert(GameStatsScreen);
+    for (i, alignment) in columns.iter().enumerate() {
+        if *alignment == 0 {
+            continue;
+        }
+        let height = f32::from(alignment.unsigned_abs()) / f32::from(furthest) * GRAPH_HEIGHT;
+        let (y, color) = if *alignment > 0 { (GRAPH_Y + height / 2.0, AQUA) } else { (GRAPH_Y - height / 2.0, PURPLE) };
+        commands.spawn(SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(Vec2::new(0.5 - 1.0 / 16.0, height)),
+                ..default()
+            },
+            transform: Transform::from_translation(Vec2::new(i as f32 / 2.0, y).extend(1.0)),
+            ..default()
+        }).insert(GameStatsScreen);
+    }
+}
+
+// Long games are squashed to fit, each column showing where the wizard stood at the end of the
+// rounds it covers
+fn graph_columns(alignment: &[i8], columns: usize) -> Vec<i8> {
+    if alignment.len() <= columns {
+        return alignment.to_vec();
+    }
+    (1..=columns).map(|c| alignment[c * alignment.len() / columns - 1]).collect()
+}
+
+fn game_stats_keyboard_input(
+    mut state: ResMut<NextState<GameState>>,
+    mut char_evr: EventReader<ReceivedCharacter>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Input<Action>>,
+    g: Res<Game>,
+    mut page: ResMut<StatsPage>,
+    campaign: Res<Campaign>,
+) {
+    if actions.just_pressed(Action::Back) || actions.just_pressed(Action::Cancel) {
+        keys.reset_all();
+        actions.reset_all();
+        state.set(if campaign.in_battle { GameState::CampaignMap } else { GameState::InitialMenu });
+        return;
+    }
+    let wizards = g.player_info.len();
+    if wizards == 0 {
+        return;
+    }
+    if actions.just_pressed(Action::Move(Direction::Left)) {
+        page.0 = (page.0 + wizards - 1) % wizards;
+    }
+    if actions.just_pressed(Action::Move(Direction::Right)) {
+        page.0 = (page.0 + 1) % wizards;
+    }
+    for ev in &mut char_evr {
+        if let Some(d) = ev.char.to_digit(10) {
+            if (1..=wizards).contains(&(d as usize)) {
+                page.0 = d as usize - 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graph_columns() {
+        assert_eq!(graph_columns(&[1, 2, 3], 5), vec![1, 2, 3]);
+        let long: Vec<i8> = (1..=10).collect();
+        assert_eq!(graph_columns(&long, 5), vec![2, 4, 6, 8, 10]);
+        assert_eq!(graph_columns(&long, 3), vec![3, 6, 10]);
+    }
+}
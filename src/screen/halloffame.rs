@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+
+use crate::display::*;
+use crate::game::Game;
+use crate::gamepad::{PadMenu, PadMenuItem};
+use crate::gamestate::GameState;
+use crate::input::{action_keys, Action};
+use crate::locale::Locale;
+use crate::palette::wizard_colors;
+use crate::profile::{Profile, Profiles};
+use crate::settings::Settings;
+use crate::system;
+
+pub struct HallOfFamePlugin;
+
+impl Plugin for HallOfFamePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<FameDetails>()
+            .add_system(hall_of_fame_enter.in_schedule(OnEnter(GameState::HallOfFame)))
+            .add_system(hall_of_fame_keyboard_input.in_set(OnUpdate(GameState::HallOfFame)))
+            .add_system(hall_of_fame_draw.run_if(resource_changed::<FameDetails>()).in_set(OnUpdate(GameState::HallOfFame)))
+            .add_system(system::despawn_screen::<HallOfFameScreen>.in_schedule(OnExit(GameState::HallOfFame)))
+            ;
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+struct HallOfFameScreen;
+
+// Only the top few fit on the screen
+const SHOWN: usize = 8;
+const FAVOURITES: usize = 3;
+
+// The place in the rankings of the profile being looked at, if any
+#[derive(Default, Resource)]
+struct FameDetails(Option<usize>);
+
+fn hall_of_fame_enter(
+    mut details: ResMut<FameDetails>,
+) {
+    details.0 = None;
+}
+
+fn hall_of_fame_draw(
+    mut commands: Commands,
+    g: Res<Game>,
+    profiles: Res<Profiles>,
+    details: Res<FameDetails>,
+    locale: Res<Locale>,
+    settings: Res<Settings>,
+    mut pad_menu: ResMut<PadMenu>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+    existing: Query<Entity, With<HallOfFameScreen>>,
+) {
+    for e in &existing {
+        commands.entity(e).despawn();
+    }
+    let back = action_keys(&settings, Action::Back);
+    let ranked = profiles.ranked();
+    if let Some(profile) = details.0.and_then(|i| ranked.get(i)) {
+        draw_profile(&mut commands, &g, profile, &locale, &settings);
+        pad_menu.clear();
+        ev_text.send(BottomTextEvent::prompt(&centred(&locale.format("prompt.exit", &[&back]))));
+        return;
+    }
+    print_text(&centred(locale.text("fame.title")), &mut commands, g.fah(), Vec2::new(0.0, 9.0), WHITE, HallOfFameScreen);
+    if ranked.is_empty() {
+        print_text(&centred(locale.text("fame.empty")), &mut commands, g.fah(), Vec2::new(0.0, 5.0), WHITE, HallOfFameScreen);
+    } else {
+        for (text, x) in [("fame.name", 1.5), ("fame.rating", 8.0), ("fame.won", 10.5), ("fame.played", 12.5)] {
+            print_text(locale.text(text), &mut commands, g.fah(), Vec2::new(x, 8.0), WHITE, HallOfFameScreen);
+        }
+    }
+    let mut items = Vec::new();
+    for (i, profile) in ranked.iter().take(SHOWN).enumerate() {
+        let y = 7.0 - i as f32;
+        let key = char::from_digit(i as u32 + 1, 10).unwrap();
+        print_text(&key.to_string(), &mut commands, g.fah(), Vec2::new(0.5, y), WHITE, HallOfFameScreen);
+        print_text(&profile.name, &mut commands, g.fah(), Vec2::new(1.5, y), WHITE, HallOfFameScreen);
+        let columns = [
+            (format!("{:4.0}", profile.rating), 8.0),
+            (format!("{:3}", profile.stats.games_won), 10.5),
+            (format!("{:4}", profile.stats.games_played), 12.5),
+        ];
+        for (text, x) in columns {
+            print_text(&text, &mut commands, g.fah(), Vec2::new(x, y), WHITE, HallOfFameScreen);
+        }
+        items.push(PadMenuItem::new(Vec2::new(0.5, y), 1, key));
+    }
+    pad_menu.set(GameState::HallOfFame, items);
+    let shown = ranked.len().min(SHOWN);
+    let prompt = if shown == 0 { locale.format("prompt.exit", &[&back]) } else { locale.format("menu.press_keys", &[&shown, &back]) };
+    ev_text.send(BottomTextEvent::prompt(&centred(&prompt)));
+}
+
+fn draw_profile(
+    commands: &mut Commands,
+    g: &Game,
+    profile: &Profile,
+    locale: &Locale,
+    settings: &Settings,
+) {
+    let color = wizard_colors(settings)[profile.color_index];
+    print_wizard(commands, g.tah(), Vec2::new(0.5, 9.0), profile.character_icon as usize - 1, color, HallOfFameScreen);
+    print_text(&profile.name, commands, g.fah(), Vec2::new(1.5, 9.0), WHITE, HallOfFameScreen);
+    let stats = &profile.stats;
+    let mut lines = vec![
+        locale.format("fame.rating_line", &[&format!("{:.0}", profile.rating)]),
+        locale.format("fame.played_line", &[&stats.games_played]),
+        locale.format("fame.won_line", &[&stats.games_won]),
+        locale.format("fame.killed_line", &[&stats.wizards_killed]),
+        locale.format("fame.summoned_line", &[&stats.creatures_summoned]),
+        locale.text("fame.favourites").to_string(),
+    ];
+    for (spell, count) in stats.favourite_spells(FAVOURITES) {
+        lines.push(format!("  {} ({count})", locale.name(spell)));
+    }
+    for (i, line) in lines.iter().enumerate() {
+        print_text(line, commands, g.fah(), Vec2::new(0.5, 8.0 - i as f32), WHITE, HallOfFameScreen);
+    }
+}
+
+fn hall_of_fame_keyboard_input(
+    mut state: ResMut<NextState<GameState>>,
+    mut char_evr: EventReader<ReceivedCharacter>,
+    mut actions: ResMut<Input<Action>>,
+    profiles: Res<Profiles>,
+    mut details: ResMut<FameDetails>,
+) {
+    if actions.just_pressed(Action::Back) || actions.just_pressed(Action::Cancel) {
+        actions.reset_all();
+        if details.0.is_some() {
+            details.0 = None;
+        } else {
+            state.set(GameState::InitialMenu);
+        }
+        return;
+    }
+    if details.0.is_some() {
+        return;
+    }
+    let shown = profiles.0.len().min(SHOWN);
+    for ev in &mut char_evr {
+        if let Some(d) = ev.char.to_digit(10) {
+            if (1..=shown).contains(&(d as usize)) {
+                details.0 = Some(d as usize - 1);
+            }
+        }
+    }
+}
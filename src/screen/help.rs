@@ -2,21 +2,29 @@ use bevy::prelude::*;
 
 use crate::gamestate::GameState;
 use crate::game::Game;
-use crate::display::{print_text, WHITE, BottomTextEvent};
+use crate::display::{centred, print_text, wrap, WHITE, BottomTextEvent};
+use crate::gamepad::{PadMenu, PadMenuItem};
+use crate::input::{action_keys, any_just_pressed, key_name, Action, Binding, Direction, Preset};
+use crate::locale::Locale;
+use crate::settings::Settings;
 use crate::system;
 pub struct HelpPlugin;
 
 impl Plugin for HelpPlugin {
     fn build(&self, app: &mut App) {
         app
+            .init_resource::<CustomBindings>()
             .add_system(help_setup.in_schedule(OnEnter(GameState::Help)))
             .add_system(help_keyboard_input.in_set(OnUpdate(GameState::Help)))
             .add_system(system::despawn_screen::<HelpScreen>.in_schedule(OnExit(GameState::Help)))
 
             .add_system(help_keys_setup.in_schedule(OnEnter(GameState::HelpKeys)))
-            .add_system(help_subscreen_keyboard_input.in_set(OnUpdate(GameState::HelpKeys)))
+            .add_system(help_keys_keyboard_input.in_set(OnUpdate(GameState::HelpKeys)))
             .add_system(system::despawn_screen::<HelpScreen>.in_schedule(OnExit(GameState::HelpKeys)))
 
+            .add_system(help_rebind_setup.in_schedule(OnEnter(GameState::HelpRebind)))
+            .add_system(help_rebind_keyboard_input.in_set(OnUpdate(GameState::HelpRebind)))
+            .add_system(system::despawn_screen::<HelpScreen>.in_schedule(OnExit(GameState::HelpRebind)))
 
             .add_system(help_spells_setup.in_schedule(OnEnter(GameState::HelpSpells)))
             .add_system(help_subscreen_keyboard_input.in_set(OnUpdate(GameState::HelpSpells)))
@@ -51,26 +59,41 @@ struct HelpScreen;
 fn help_setup(
     mut commands: Commands,
     g: Res<Game>,
+    locale: Res<Locale>,
+    settings: Res<Settings>,
     mut ev_text: EventWriter<BottomTextEvent>,
+    mut pad_menu: ResMut<PadMenu>,
 ) {
     debug!("in help setup");
-    print_text("         Help screen", &mut commands, g.fah(), Vec2::new(0.0, 8.0), WHITE, HelpScreen);
-    print_text("1. Keys", &mut commands, g.fah(), Vec2::new(0.0, 7.0), WHITE, HelpScreen);
-    print_text("2. Spells", &mut commands, g.fah(), Vec2::new(0.0, 6.0), WHITE, HelpScreen);
-    print_text("3. Combat", &mut commands, g.fah(), Vec2::new(0.0, 5.0), WHITE, HelpScreen);
-    print_text("4. Undead", &mut commands, g.fah(), Vec2::new(0.0, 4.0), WHITE, HelpScreen);
-    print_text("5. Mounts", &mut commands, g.fah(), Vec2::new(0.0, 3.0), WHITE, HelpScreen);
-    print_text("6. Victory", &mut commands, g.fah(), Vec2::new(0.0, 2.0), WHITE, HelpScreen);
+    print_text(&centred(locale.text("help.title")), &mut commands, g.fah(), Vec2::new(0.0, 8.0), WHITE, HelpScreen);
+    let options = [
+        "help.menu.keys",
+        "help.menu.spells",
+        "help.menu.combat",
+        "help.menu.undead",
+        "help.menu.mounts",
+        "help.menu.victory",
+        "help.menu.options",
+    ];
+    let mut items = Vec::new();
+    for (i, key) in options.iter().enumerate() {
+        let option = format!("{}. {}", i + 1, locale.text(key));
+        let pos = Vec2::new(0.0, 7.0 - i as f32);
+        print_text(&option, &mut commands, g.fah(), pos, WHITE, HelpScreen);
+        items.push(PadMenuItem::new(pos, option.chars().count(), option.chars().next().unwrap()));
+    }
+    pad_menu.set(GameState::Help, items);
     debug!("printed help");
-    ev_text.send(BottomTextEvent::from("Press Keys 1-6 or 0 to return"));
+    ev_text.send(BottomTextEvent::prompt(&locale.format("menu.press_keys", &[&options.len(), &action_keys(&settings, Action::Back)])));
 }
 
 fn help_keyboard_input(
     mut state: ResMut<NextState<GameState>>,
     mut keys: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Input<Action>>,
 ) {
-    if keys.just_pressed(KeyCode::Key0) {
-        keys.reset(KeyCode::Key0);
+    if actions.just_pressed(Action::Back) || actions.just_pressed(Action::Cancel) {
+        actions.reset_all();
         state.set(GameState::InitialMenu);
         return
     }
@@ -103,19 +126,21 @@ fn help_keyboard_input(
         keys.reset(KeyCode::Key6);
         state.set(GameState::HelpVictory);
     }
+    if keys.just_pressed(KeyCode::Key7) {
+        keys.reset(KeyCode::Key7);
+        state.set(GameState::Options);
+    }
 }
 
 fn help_subscreen_keyboard_input(
     mut state: ResMut<NextState<GameState>>,
     mut keys: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Input<Action>>,
 ) {
-    let mut has_pressed = false;
-    for _ in keys.get_just_pressed() {
-        has_pressed = true;
-    }
-    if has_pressed {
-        println!("Got keypress, return to main help screeen");
+    if any_just_pressed(&keys, &actions) {
+        debug!("Got keypress, return to main help screeen");
         keys.reset_all();
+        actions.reset_all();
         state.set(GameState::Help);
     }
 }
@@ -123,65 +148,207 @@ fn help_subscreen_keyboard_input(
 fn help_keys_setup(
     mut commands: Commands,
     g: Res<Game>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
     mut ev_text: EventWriter<BottomTextEvent>,
+    mut pad_menu: ResMut<PadMenu>,
 ) {
-    print_text("              Keys", &mut commands, g.fah(), Vec2::new(0.0, 9.0), WHITE, HelpScreen);
-	print_text("AQWEDCXZ - Move in direction", &mut commands, g.fah(), Vec2::new(0.0, 7.0), WHITE, HelpScreen);
-	print_text("S - Select creature/wizard", &mut commands, g.fah(), Vec2::new(0.0, 6.0), WHITE, HelpScreen);
-	print_text("K - Cancel movement/attack", &mut commands, g.fah(), Vec2::new(0.0, 5.0), WHITE, HelpScreen);
-	print_text("I - Show information on", &mut commands, g.fah(), Vec2::new(0.0, 4.0), WHITE, HelpScreen);
-	print_text("    creature", &mut commands, g.fah(), Vec2::new(0.0, 3.0), WHITE, HelpScreen);
-	print_text("1-8 - Highlight creations of", &mut commands, g.fah(), Vec2::new(0.0, 2.0), WHITE, HelpScreen);
-	print_text("      player # 1-8", &mut commands, g.fah(), Vec2::new(0.0, 1.0), WHITE, HelpScreen);
-	print_text("0 - End turn", &mut commands, g.fah(), Vec2::new(0.0, 0.0), WHITE, HelpScreen);
-	ev_text.send(BottomTextEvent::from("   Press any key to continue "));
+    print_text(&centred(locale.text("help.keys.title")), &mut commands, g.fah(), Vec2::new(0.0, 9.0), WHITE, HelpScreen);
+    let keys = |action| action_keys(&settings, action);
+    let moves: Vec<String> = Direction::ALL.iter().map(|d| keys(Action::Move(*d))).collect();
+    let sep = if moves.iter().all(|m| m.len() == 1) { "" } else { " " };
+    let mut lines = wrap(&locale.format("help.keys.move", &[&moves.join(sep)]));
+    lines.push(locale.format("help.keys.select", &[&keys(Action::Select)]));
+    lines.push(locale.format("help.keys.cancel", &[&keys(Action::Cancel), &keys(Action::Undo)]));
+    lines.push(locale.format("help.keys.info", &[&keys(Action::Info), &keys(Action::Log)]));
+    lines.push(locale.text("help.keys.creations").to_string());
+    lines.push(locale.format("help.keys.end_turn", &[&keys(Action::EndTurn), &keys(Action::Back)]));
+    lines.push(locale.text("help.keys.pad").to_string());
+    let rebind = locale.text("help.keys.rebind");
+    lines.push(rebind.to_string());
+    let rebind_y = 9.0 - lines.len() as f32;
+    for (i, line) in lines.iter().enumerate() {
+        print_text(line, &mut commands, g.fah(), Vec2::new(0.0, 8.0 - i as f32), WHITE, HelpScreen);
+    }
+    pad_menu.set(GameState::HelpKeys, vec![PadMenuItem::new(Vec2::new(0.0, rebind_y), rebind.chars().count(), 'R')]);
+	ev_text.send(BottomTextEvent::prompt(&centred(locale.text("prompt.continue"))));
 }
 
-fn help_spells_setup(
+fn help_keys_keyboard_input(
+    mut state: ResMut<NextState<GameState>>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Input<Action>>,
+) {
+    if keys.just_pressed(KeyCode::R) {
+        keys.reset_all();
+        state.set(GameState::HelpRebind);
+        return;
+    }
+    if any_just_pressed(&keys, &actions) {
+        keys.reset_all();
+        actions.reset_all();
+        state.set(GameState::Help);
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+struct RebindPrompt;
+
+const PRESETS: [(&str, Preset); 3] = [
+    ("help.rebind.classic", Preset::Classic),
+    ("help.rebind.arrows", Preset::Arrows),
+    ("help.rebind.numpad", Preset::Numpad),
+];
+
+fn help_rebind_setup(
+    mut commands: Commands,
+    g: Res<Game>,
+    locale: Res<Locale>,
+    settings: Res<Settings>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+    mut pad_menu: ResMut<PadMenu>,
+) {
+    print_text(&centred(locale.text("help.rebind.title")), &mut commands, g.fah(), Vec2::new(0.0, 9.0), WHITE, HelpScreen);
+    let mut items = Vec::new();
+    let options: Vec<&str> = PRESETS.iter().map(|(key, _)| *key).chain(["help.rebind.each"]).collect();
+    for (i, key) in options.iter().enumerate() {
+        let option = format!("{}. {}", i + 1, locale.text(key));
+        let pos = Vec2::new(0.0, 7.0 - i as f32);
+        print_text(&option, &mut commands, g.fah(), pos, WHITE, HelpScreen);
+        items.push(PadMenuItem::new(pos, option.chars().count(), option.chars().next().unwrap()));
+    }
+    pad_menu.set(GameState::HelpRebind, items);
+    ev_text.send(BottomTextEvent::prompt(&locale.format("menu.press_keys", &[&options.len(), &action_keys(&settings, Action::Back)])));
+}
+
+// While choosing each key in turn, the bindings chosen so far
+#[derive(Default, Resource)]
+pub struct CustomBindings(Option<Vec<Binding>>);
+
+// Escape only closes the game while keys aren't being chosen, as it stops choosing them
+pub fn not_rebinding(custom: Res<CustomBindings>) -> bool {
+    custom.0.is_none()
+}
+
+fn help_rebind_keyboard_input(
     mut commands: Commands,
     g: Res<Game>,
+    locale: Res<Locale>,
+    mut state: ResMut<NextState<GameState>>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Input<Action>>,
+    mut settings: ResMut<Settings>,
+    mut custom: ResMut<CustomBindings>,
+    prompt: Query<Entity, With<RebindPrompt>>,
     mut ev_text: EventWriter<BottomTextEvent>,
 ) {
-    print_text("           Spells", &mut commands, g.fah(), Vec2::new(0.0, 9.0), WHITE, HelpScreen);
-	print_text("Select a spell then use", &mut commands, g.fah(), Vec2::new(0.0, 7.0), WHITE, HelpScreen);
-	print_text("direction keys to choose", &mut commands, g.fah(), Vec2::new(0.0, 6.0), WHITE, HelpScreen);
-	print_text("where to cast it.", &mut commands, g.fah(), Vec2::new(0.0, 5.0), WHITE, HelpScreen);
-	print_text("Press S to cast.", &mut commands, g.fah(), Vec2::new(0.0, 4.0), WHITE, HelpScreen);
-	print_text("Illusions always succeed but", &mut commands, g.fah(), Vec2::new(0.0, 3.0), WHITE, HelpScreen);
-	print_text("can be disbelieved by others.", &mut commands, g.fah(), Vec2::new(0.0, 2.0), WHITE, HelpScreen);
-	print_text("   ^=law *=chaos -=neutral", &mut commands, g.fah(), Vec2::new(0.0, 0.0), WHITE, HelpScreen);
-	ev_text.send(BottomTextEvent::from("   Press any key to continue "));
+    if let Some(bindings) = custom.0.as_mut() {
+        // Escape can't be bound so it's free to give up with, as is the pad's cancel button.
+        // Keys are reset after, so the window doesn't close on the same press.
+        let pad_cancel = actions.just_pressed(Action::Cancel) && keys.get_just_pressed().next().is_none();
+        if keys.just_pressed(KeyCode::Escape) || pad_cancel {
+            custom.0 = None;
+            for e in prompt.iter() {
+                commands.entity(e).despawn();
+            }
+            keys.reset_all();
+            actions.reset_all();
+            ev_text.send(BottomTextEvent::important(locale.text("help.rebind.cancelled")));
+            return;
+        }
+        let Some(key) = keys.get_just_pressed().copied().find(|k| key_name(*k).is_some()) else {
+            return;
+        };
+        // Leaving menus usually shares the end turn key, nothing else can be doubled up
+        let action = Action::ALL[bindings.len()];
+        let shared = action == Action::Back && bindings.iter().any(|b| b.key == key && b.action == Action::EndTurn);
+        if bindings.iter().any(|b| b.key == key) && !shared {
+            return;
+        }
+        bindings.push(Binding { key, action });
+        for e in prompt.iter() {
+            commands.entity(e).despawn();
+        }
+        if bindings.len() == Action::ALL.len() {
+            settings.key_bindings = custom.0.take().unwrap();
+            settings.save();
+            keys.reset_all();
+            state.set(GameState::HelpKeys);
+        } else {
+            print_rebind_prompt(&mut commands, g.fah(), &locale, Action::ALL[bindings.len()]);
+        }
+        return;
+    }
+    if actions.just_pressed(Action::Back) || actions.just_pressed(Action::Cancel) {
+        keys.reset_all();
+        actions.reset_all();
+        state.set(GameState::HelpKeys);
+        return;
+    }
+    for (i, key) in [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3].iter().enumerate() {
+        if keys.just_pressed(*key) {
+            keys.reset_all();
+            settings.key_bindings = PRESETS[i].1.bindings();
+            settings.save();
+            state.set(GameState::HelpKeys);
+            return;
+        }
+    }
+    if keys.just_pressed(KeyCode::Key4) {
+        keys.reset_all();
+        custom.0 = Some(Vec::new());
+        print_rebind_prompt(&mut commands, g.fah(), &locale, Action::ALL[0]);
+    }
 }
 
+fn print_rebind_prompt(commands: &mut Commands, fah: Handle<TextureAtlas>, locale: &Locale, action: Action) {
+    let text = locale.format("help.rebind.press", &[&locale.text(action.name())]);
+    print_text(&text, commands, fah.clone(), Vec2::new(0.0, 2.0), WHITE, RebindPrompt);
+    print_text(locale.text("help.rebind.escape"), commands, fah, Vec2::new(0.0, 1.0), WHITE, RebindPrompt);
+}
+
+// A title with paragraphs of text underneath, one per line of the locale text
+fn print_page(commands: &mut Commands, fah: Handle<TextureAtlas>, title: &str, text: &str) {
+    print_text(&centred(title), commands, fah.clone(), Vec2::new(0.0, 9.0), WHITE, HelpScreen);
+    let lines: Vec<String> = text.split('\n').flat_map(wrap).collect();
+    // Longer translations use the gap under the title
+    let top = if lines.len() > 8 { 8.0 } else { 7.0 };
+    for (i, line) in lines.iter().enumerate() {
+        print_text(line, commands, fah.clone(), Vec2::new(0.0, top - i as f32), WHITE, HelpScreen);
+    }
+}
+
+fn help_spells_setup(
+    mut commands: Commands,
+    g: Res<Game>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+) {
+    let text = locale.format("help.spells.text", &[&action_keys(&settings, Action::Select)]);
+    print_page(&mut commands, g.fah(), locale.text("help.spells.title"), &text);
+	ev_text.send(BottomTextEvent::prompt(&centred(locale.text("prompt.continue"))));
+}
 
 fn help_combat_setup(
     mut commands: Commands,
     g: Res<Game>,
+    locale: Res<Locale>,
     mut ev_text: EventWriter<BottomTextEvent>,
 ) {
-    print_text("           Combat", &mut commands, g.fah(), Vec2::new(0.0, 9.0), WHITE, HelpScreen);
-	//                                        #
-	print_text("Move next to another creature", &mut commands, g.fah(), Vec2::new(0.0, 7.0), WHITE, HelpScreen);
-	print_text("to engage them in combat.", &mut commands, g.fah(), Vec2::new(0.0, 6.0), WHITE, HelpScreen);
-	print_text("Flying creatures can attack", &mut commands, g.fah(), Vec2::new(0.0, 5.0), WHITE, HelpScreen);
-	print_text("remotely without engagement.", &mut commands, g.fah(), Vec2::new(0.0, 4.0), WHITE, HelpScreen);
-	print_text("If adjacent next turn you may", &mut commands, g.fah(), Vec2::new(0.0, 3.0), WHITE, HelpScreen);
-	print_text("remain engaged or may be able", &mut commands, g.fah(), Vec2::new(0.0, 2.0), WHITE, HelpScreen);
-	print_text("to break away.", &mut commands, g.fah(), Vec2::new(0.0, 1.0), WHITE, HelpScreen);
-	ev_text.send(BottomTextEvent::from("   Press any key to continue "));
+    print_page(&mut commands, g.fah(), locale.text("help.combat.title"), locale.text("help.combat.text"));
+	ev_text.send(BottomTextEvent::prompt(&centred(locale.text("prompt.continue"))));
 }
 
 fn help_combat_keyboard_input(
     mut state: ResMut<NextState<GameState>>,
     mut keys: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Input<Action>>,
 ) {
-    let mut has_pressed = false;
-    for _ in keys.get_just_pressed() {
-        has_pressed = true;
-    }
-    if has_pressed {
-        println!("Set ranged combat and reset all keypresses");
+    if any_just_pressed(&keys, &actions) {
+        debug!("Set ranged combat and reset all keypresses");
         keys.reset_all();
+        actions.reset_all();
         state.set(GameState::HelpRangedCombat);
     }
 }
@@ -189,68 +356,39 @@ fn help_combat_keyboard_input(
 fn help_ranged_combat_setup(
     mut commands: Commands,
     g: Res<Game>,
+    locale: Res<Locale>,
     mut ev_text: EventWriter<BottomTextEvent>,
 ) {
- 	print_text("        Ranged Combat", &mut commands, g.fah(), Vec2::new(0.0, 9.0), WHITE, HelpScreen);
-	//                                        #
-	print_text("Some characters have ranged", &mut commands, g.fah(), Vec2::new(0.0, 7.0), WHITE, HelpScreen);
-	print_text("combat.", &mut commands, g.fah(), Vec2::new(0.0, 6.0), WHITE, HelpScreen);
-	print_text("This always happens after", &mut commands, g.fah(), Vec2::new(0.0, 5.0), WHITE, HelpScreen);
-	print_text("movement (K to skip movement)", &mut commands, g.fah(), Vec2::new(0.0, 4.0), WHITE, HelpScreen);
-	print_text("Target is selected with", &mut commands, g.fah(), Vec2::new(0.0, 3.0), WHITE, HelpScreen);
-	print_text("direction keys, press S to", &mut commands, g.fah(), Vec2::new(0.0, 2.0), WHITE, HelpScreen);
-	print_text("fire. Target must be in line", &mut commands, g.fah(), Vec2::new(0.0, 1.0), WHITE, HelpScreen);
-	print_text("of sight.", &mut commands, g.fah(), Vec2::new(0.0, 0.0), WHITE, HelpScreen);
-	ev_text.send(BottomTextEvent::from("   Press any key to continue "));
+    print_page(&mut commands, g.fah(), locale.text("help.ranged.title"), locale.text("help.ranged.text"));
+	ev_text.send(BottomTextEvent::prompt(&centred(locale.text("prompt.continue"))));
 }
 
 fn help_undead_setup(
     mut commands: Commands,
     g: Res<Game>,
+    locale: Res<Locale>,
     mut ev_text: EventWriter<BottomTextEvent>,
 ) {
-    print_text("           Undead", &mut commands, g.fah(), Vec2::new(0.0, 9.0), WHITE, HelpScreen);
-	//                                        #
-	print_text("Some characters are undead.", &mut commands, g.fah(), Vec2::new(0.0, 7.0), WHITE, HelpScreen);
-	print_text("They can only be attacked by", &mut commands, g.fah(), Vec2::new(0.0, 6.0), WHITE, HelpScreen);
-	print_text("other undead characters or", &mut commands, g.fah(), Vec2::new(0.0, 5.0), WHITE, HelpScreen);
-	print_text("magic weapons.", &mut commands, g.fah(), Vec2::new(0.0, 4.0), WHITE, HelpScreen);
-	print_text("The raise dead spell will", &mut commands, g.fah(), Vec2::new(0.0, 2.0), WHITE, HelpScreen);
-	print_text("turn a corpse into an undead", &mut commands, g.fah(), Vec2::new(0.0, 1.0), WHITE, HelpScreen);
-	print_text("creature.", &mut commands, g.fah(), Vec2::new(0.0, 0.0), WHITE, HelpScreen);
-	ev_text.send(BottomTextEvent::from("   Press any key to continue "));
+    print_page(&mut commands, g.fah(), locale.text("help.undead.title"), locale.text("help.undead.text"));
+	ev_text.send(BottomTextEvent::prompt(&centred(locale.text("prompt.continue"))));
 }
 
-
 fn help_mounts_setup(
     mut commands: Commands,
     g: Res<Game>,
+    locale: Res<Locale>,
     mut ev_text: EventWriter<BottomTextEvent>,
 ) {
-    print_text("           Mounts", &mut commands, g.fah(), Vec2::new(0.0, 9.0), WHITE, HelpScreen);
-	//                                        #
-	print_text("Some characters can be ridden", &mut commands, g.fah(), Vec2::new(0.0, 7.0), WHITE, HelpScreen);
-	print_text("by wizards. Simply move your", &mut commands, g.fah(), Vec2::new(0.0, 6.0), WHITE, HelpScreen);
-	print_text("wizard onto the creature to", &mut commands, g.fah(), Vec2::new(0.0, 5.0), WHITE, HelpScreen);
-	print_text("mount it.", &mut commands, g.fah(), Vec2::new(0.0, 4.0), WHITE, HelpScreen);
-	print_text("This allows faster movement", &mut commands, g.fah(), Vec2::new(0.0, 3.0), WHITE, HelpScreen);
-	print_text("and your wizard cannot be", &mut commands, g.fah(), Vec2::new(0.0, 2.0), WHITE, HelpScreen);
-	print_text("killed unless their mount is", &mut commands, g.fah(), Vec2::new(0.0, 1.0), WHITE, HelpScreen);
-	print_text("killed first.", &mut commands, g.fah(), Vec2::new(0.0, 0.0), WHITE, HelpScreen);
-	ev_text.send(BottomTextEvent::from("   Press any key to continue "));
+    print_page(&mut commands, g.fah(), locale.text("help.mounts.title"), locale.text("help.mounts.text"));
+	ev_text.send(BottomTextEvent::prompt(&centred(locale.text("prompt.continue"))));
 }
 
 fn help_victory_setup(
     mut commands: Commands,
     g: Res<Game>,
+    locale: Res<Locale>,
     mut ev_text: EventWriter<BottomTextEvent>,
 ) {
-    print_text("          Victory", &mut commands, g.fah(), Vec2::new(0.0, 9.0), WHITE, HelpScreen);
-	//                                        #
-	print_text("To win the game, simply kill", &mut commands, g.fah(), Vec2::new(0.0, 7.0), WHITE, HelpScreen);
-	print_text("all the other wizards.", &mut commands, g.fah(), Vec2::new(0.0, 6.0), WHITE, HelpScreen);
-	print_text("When a player is killed, all", &mut commands, g.fah(), Vec2::new(0.0, 4.0), WHITE, HelpScreen);
-	print_text("of their creations will also", &mut commands, g.fah(), Vec2::new(0.0, 3.0), WHITE, HelpScreen);
-	print_text("vanish.", &mut commands, g.fah(), Vec2::new(0.0, 2.0), WHITE, HelpScreen);
-	ev_text.send(BottomTextEvent::from("   Press any key to continue "));
+    print_page(&mut commands, g.fah(), locale.text("help.victory.title"), locale.text("help.victory.text"));
+	ev_text.send(BottomTextEvent::prompt(&centred(locale.text("prompt.continue"))));
 }
@@ -0,0 +1,145 @@
+use bevy::prelude::*;
+
+use crate::cursor::Cursor;
+use crate::display::{centred, print_text, wrap, BottomTextEvent, WHITE, YELLOW};
+use crate::game::Game;
+use crate::gamestate::GameState;
+use crate::input::{action_keys, Action, Direction};
+use crate::locale::Locale;
+use crate::messagelog::MessageLog;
+use crate::settings::Settings;
+use crate::system;
+
+// Lines of the log shown at once, below the title
+const LOG_LINES: usize = 9;
+
+pub struct LogViewPlugin;
+
+impl Plugin for LogViewPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<LogView>()
+            .add_systems((log_view_setup, system::hide_board_entities).in_schedule(OnEnter(GameState::MessageLog)))
+            .add_system(log_view_keyboard.in_set(OnUpdate(GameState::MessageLog)))
+            .add_system(log_view_draw.run_if(resource_changed::<LogView>()).in_set(OnUpdate(GameState::MessageLog)))
+            .add_system(system::despawn_screen::<LogScreen>.in_schedule(OnExit(GameState::MessageLog)))
+            ;
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+struct LogScreen;
+
+// Where to go back to once the log is closed, and how many lines it is scrolled back from the end.
+// Scrolling moves the hidden cursor too, so it is put back where it was afterwards.
+#[derive(Resource)]
+pub struct LogView {
+    return_to: GameState,
+    scroll: usize,
+    cursor_pos: Vec2,
+}
+
+impl Default for LogView {
+    fn default() -> Self {
+        Self { return_to: GameState::TurnMenu, scroll: 0, cursor_pos: Vec2::ZERO }
+    }
+}
+
+impl LogView {
+    pub fn open(&mut self, from: GameState, state: &mut NextState<GameState>) {
+        self.return_to = from;
+        self.scroll = 0;
+        state.set(GameState::MessageLog);
+    }
+}
+
+fn log_lines(log: &MessageLog, locale: &Locale) -> Vec<(String, Color)> {
+    let mut lines = Vec::new();
+    let mut round = None;
+    for entry in &log.entries {
+        if round != Some(entry.round) {
+            round = Some(entry.round);
+            lines.push((locale.format("log.round", &[&entry.round]), YELLOW));
+        }
+        let text = entry.player.as_ref().map_or_else(|| entry.text.clone(), |p| format!("{p}: {}", entry.text));
+        lines.extend(wrap(&text).into_iter().map(|l| (l, WHITE)));
+    }
+    lines
+}
+
+fn log_view_setup(
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    mut cursor: ResMut<Cursor>,
+    mut view: ResMut<LogView>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+) {
+    view.cursor_pos = cursor.get_pos_v();
+    cursor.set_invisible();
+    let keys = |action| action_keys(&settings, action);
+    let text = locale.format("log.keys", &[
+        &keys(Action::Move(Direction::Up)),
+        &keys(Action::Move(Direction::Down)),
+        &keys(Action::Cancel),
+    ]);
+    ev_text.send(BottomTextEvent::prompt(&text));
+}
+
+fn log_view_draw(
+    mut commands: Commands,
+    g: Res<Game>,
+    locale: Res<Locale>,
+    log: Res<MessageLog>,
+    view: Res<LogView>,
+    existing: Query<Entity, With<LogScreen>>,
+) {
+    for e in &existing {
+        commands.entity(e).despawn();
+    }
+    print_text(&centred(locale.text("log.title")), &mut commands, g.fah(), Vec2::new(0.0, 9.0), WHITE, LogScreen);
+    let lines = log_lines(&log, &locale);
+    if lines.is_empty() {
+        print_text(locale.text("log.empty"), &mut commands, g.fah(), Vec2::new(0.0, 8.0), WHITE, LogScreen);
+        return;
+    }
+    let end = lines.len() - view.scroll.min(lines.len());
+    let start = end.saturating_sub(LOG_LINES);
+    for (i, (line, color)) in lines[start..end].iter().enumerate() {
+        print_text(line, &mut commands, g.fah(), Vec2::new(0.0, 8.0 - i as f32), *color, LogScreen);
+    }
+}
+
+fn log_view_keyboard(
+    mut keys: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Input<Action>>,
+    mut view: ResMut<LogView>,
+    log: Res<MessageLog>,
+    locale: Res<Locale>,
+    g: Res<Game>,
+    mut state: ResMut<NextState<GameState>>,
+    mut cursor: ResMut<Cursor>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+    board_q: Query<&mut Visibility, With<system::BoardEntity>>,
+) {
+    let max_scroll = log_lines(&log, &locale).len().saturating_sub(LOG_LINES);
+    if actions.just_pressed(Action::Move(Direction::Up)) && view.scroll < max_scroll {
+        view.scroll += 1;
+    }
+    if actions.just_pressed(Action::Move(Direction::Down)) && view.scroll > 0 {
+        view.scroll -= 1;
+    }
+    if actions.just_pressed(Action::Back) || actions.just_pressed(Action::Cancel) || actions.just_pressed(Action::Log) {
+        keys.reset_all();
+        actions.reset_all();
+        if view.return_to == GameState::MoveChoose {
+            // Back to the board, as it was left
+            system::show_board_entities(board_q);
+            cursor.set_pos(view.cursor_pos);
+            cursor.set_visible();
+            ev_text.send(BottomTextEvent::from(&locale.format("move.turn", &[&g.get_player().name])));
+        } else {
+            ev_text.send(BottomTextEvent::clear());
+        }
+        state.set(view.return_to.clone());
+    }
+}
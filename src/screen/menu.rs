@@ -1,28 +1,24 @@
 use bevy::prelude::*;
 
-use crate::board::BoardPutEntity;
 use crate::{display::*, spell::AllSpells};
+use crate::gamepad::{PadMenu, PadMenuItem};
+use crate::input::{action_keys, Action};
+use crate::locale::Locale;
+use crate::settings::Settings;
+use crate::palette::wizard_colors;
 use crate::player::Player;
+use crate::profile::Profiles;
 use crate::game::Game;
 use crate::system;
 use crate::gamestate::GameState;
-
-const WIZARD_COLORS :[Color; 8] = [
-    RED,
-    PURPLE,
-    GREEN,
-    AQUA,
-    MIDYELLOW,
-    YELLOW,
-    GREY,
-    WHITE,
-];
+use super::nameentry::{draw_keyboard, NameEditor, OnScreenKeyboard, MAX_NAME_LEN};
 
 pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         app
+            .init_resource::<CapturePlayer>()
             .add_system(initial_menu_setup.in_schedule(OnEnter(GameState::InitialMenu)))
             .add_system(initial_menu_keyboard_input.in_set(OnUpdate(GameState::InitialMenu)))
             .add_system(system::despawn_screen::<InitialMenuScreen>.in_schedule(OnExit(GameState::InitialMenu)))
@@ -44,16 +40,42 @@ struct InitialMenuScreen;
 fn initial_menu_setup(
     mut commands: Commands,
     game: Res<Game>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
     mut ev_text: EventWriter<BottomTextEvent>,
+    gamepads: Res<Gamepads>,
+    mut pad_menu: ResMut<PadMenu>,
 ) {
     get_border(&mut commands, game.tah());
-    print_text("  MAYHEM - Remake of Chaos", &mut commands, game.fah(), Vec2::new(0.5, 8.0), WHITE, InitialMenuScreen);
-    print_text("         By bobtfish", &mut commands, game.fah(), Vec2::new(0.5, 7.0), WHITE, InitialMenuScreen);
-    print_text("How many wizards?", &mut commands, game.fah(), Vec2::new(0.5, 5.0), WHITE, InitialMenuScreen);
-    print_text("(Press 2 to 8)", &mut commands, game.fah(), Vec2::new(0.5, 4.0), WHITE, InitialMenuScreen);
-    ev_text.send(BottomTextEvent::from("      Press H for help"));
+    print_text(&centred(locale.text("menu.title")), &mut commands, game.fah(), Vec2::new(0.0, 8.0), WHITE, InitialMenuScreen);
+    print_text(&centred(locale.text("menu.author")), &mut commands, game.fah(), Vec2::new(0.0, 7.0), WHITE, InitialMenuScreen);
+    let fame = centred(locale.text("menu.fame"));
+    print_text(&fame, &mut commands, game.fah(), Vec2::new(0.0, 6.0), WHITE, InitialMenuScreen);
+    let campaign = centred(locale.text("menu.campaign"));
+    print_text(&campaign, &mut commands, game.fah(), Vec2::new(0.0, 9.0), WHITE, InitialMenuScreen);
+    let challenges = centred(locale.text("menu.challenges"));
+    print_text(&challenges, &mut commands, game.fah(), Vec2::new(0.0, 3.0), WHITE, InitialMenuScreen);
+    let editor = centred(locale.text("menu.editor"));
+    print_text(&editor, &mut commands, game.fah(), Vec2::new(0.0, 0.0), WHITE, InitialMenuScreen);
+    print_text(locale.text("menu.how_many"), &mut commands, game.fah(), Vec2::new(0.5, 5.0), WHITE, InitialMenuScreen);
+    print_text(&locale.format("menu.press_range", &[&2, &8]), &mut commands, game.fah(), Vec2::new(0.5, 4.0), WHITE, InitialMenuScreen);
+    let help = locale.format("menu.help", &[&action_keys(&settings, Action::Help)]);
+    ev_text.send(BottomTextEvent::prompt(&centred(&help)));
     if game.players > 0 {
-        draw_level(game.players, &mut commands, game.fah());
+        draw_level(game.players, &mut commands, game.fah(), &locale, &gamepads, &mut pad_menu);
+    } else {
+        let mut items = draw_pad_digits(2..=8, Vec2::new(10.0, 4.0), &mut commands, game.fah(), &gamepads);
+        if !items.is_empty() {
+            let start = fame.len() - fame.trim_start().len();
+            items.push(PadMenuItem::new(Vec2::new(start as f32 / 2.0, 6.0), fame.trim().chars().count(), 'F'));
+            let start = campaign.len() - campaign.trim_start().len();
+            items.push(PadMenuItem::new(Vec2::new(start as f32 / 2.0, 9.0), campaign.trim().chars().count(), 'C'));
+            let start = challenges.len() - challenges.trim_start().len();
+            items.push(PadMenuItem::new(Vec2::new(start as f32 / 2.0, 3.0), challenges.trim().chars().count(), 'P'));
+            let start = editor.len() - editor.trim_start().len();
+            items.push(PadMenuItem::new(Vec2::new(start as f32 / 2.0, 0.0), editor.trim().chars().count(), 'E'));
+        }
+        pad_menu.set(GameState::InitialMenu, items);
     }
 }
 
@@ -61,10 +83,36 @@ fn draw_level(
     players: u8,
     commands: &mut Commands,
     fah: Handle<TextureAtlas>,
+    locale: &Locale,
+    gamepads: &Gamepads,
+    pad_menu: &mut PadMenu,
 ) {
     print_text(&players.to_string(), commands, fah.clone(), Vec2::new(8.0, 4.0), WHITE, InitialMenuScreen);
-    print_text("Level of computer wizards?", commands, fah.clone(), Vec2::new(0.5, 2.0), WHITE, InitialMenuScreen);
-    print_text("(Press 1 to 8)", commands, fah, Vec2::new(0.5, 1.0), WHITE, InitialMenuScreen);
+    print_text(locale.text("menu.level"), commands, fah.clone(), Vec2::new(0.5, 2.0), WHITE, InitialMenuScreen);
+    print_text(&locale.format("menu.press_range", &[&1, &8]), commands, fah.clone(), Vec2::new(0.5, 1.0), WHITE, InitialMenuScreen);
+    let items = draw_pad_digits(1..=8, Vec2::new(10.0, 1.0), commands, fah, gamepads);
+    pad_menu.set(GameState::InitialMenu, items);
+}
+
+// Without a keyboard the digits to choose from need to be on screen for the gamepad to pick
+fn draw_pad_digits(
+    digits: std::ops::RangeInclusive<u8>,
+    pos: Vec2,
+    commands: &mut Commands,
+    fah: Handle<TextureAtlas>,
+    gamepads: &Gamepads,
+) -> Vec<PadMenuItem> {
+    if gamepads.iter().next().is_none() {
+        return Vec::new();
+    }
+    let mut items = Vec::new();
+    for (i, digit) in digits.enumerate() {
+        let v = Vec2::new((i as f32).mul_add(0.5, pos.x), pos.y);
+        let key = (b'0' + digit) as char;
+        print_text(&key.to_string(), commands, fah.clone(), v, WHITE, InitialMenuScreen);
+        items.push(PadMenuItem::new(v, 1, key));
+    }
+    items
 }
 
 fn initial_menu_keyboard_input(
@@ -72,9 +120,12 @@ fn initial_menu_keyboard_input(
     mut state: ResMut<NextState<GameState>>,
     mut game: ResMut<Game>,
     mut commands: Commands,
-    keys: Res<Input<KeyCode>>,
+    locale: Res<Locale>,
+    actions: Res<Input<Action>>,
+    gamepads: Res<Gamepads>,
+    mut pad_menu: ResMut<PadMenu>,
 ) {
-    if keys.just_pressed(KeyCode::H) {
+    if actions.just_pressed(Action::Help) {
         state.set(GameState::Help);
         return
     }
@@ -82,10 +133,26 @@ fn initial_menu_keyboard_input(
         let c = ev.char as u32;
         let players = game.players;
         if players == 0 {
+            if ev.char.eq_ignore_ascii_case(&'f') {
+                state.set(GameState::HallOfFame);
+                return;
+            }
+            if ev.char.eq_ignore_ascii_case(&'c') {
+                state.set(GameState::CampaignMap);
+                return;
+            }
+            if ev.char.eq_ignore_ascii_case(&'p') {
+                state.set(GameState::Challenges);
+                return;
+            }
+            if ev.char.eq_ignore_ascii_case(&'e') {
+                state.set(GameState::Editor);
+                return;
+            }
             if (50..=56).contains(&c) {
                 game.players = (c-48) as u8;
                 info!("Players {}", game.players);
-                draw_level(game.players, &mut commands, game.fah());
+                draw_level(game.players, &mut commands, game.fah(), &locale, &gamepads, &mut pad_menu);
             }
         } else if (49..=56).contains(&c) {
             game.ai_level = (c-48) as u8;
@@ -101,132 +168,222 @@ fn initial_menu_keyboard_input(
 #[derive(Component, Clone, Copy)]
 struct PlayerNameMenuScreen;
 
+// The wizard being entered, a step at a time
+#[derive(Default, Resource)]
+struct CapturePlayer {
+    // Kept after the name is entered, so going back can carry on editing it
+    editor: NameEditor,
+    name: Option<String>,
+    computer_controlled: Option<bool>,
+    character_icon: Option<u8>,
+}
+
 fn player_name_menu_setup(
     mut commands: Commands,
     g: Res<Game>,
+    locale: Res<Locale>,
+    settings: Res<Settings>,
+    gamepads: Res<Gamepads>,
+    mut pad_menu: ResMut<PadMenu>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+    mut player: ResMut<CapturePlayer>,
 ) {
     debug!("Player name menu setup");
-    print_text("PLAYER", &mut commands, g.fah(), Vec2::new(0.5, 9.0), WHITE, PlayerNameMenuScreen);
+    *player = CapturePlayer::default();
+    draw_player_entry(&mut commands, &g, &locale, &settings, &player, &gamepads, &mut pad_menu, &mut ev_text);
+}
+
+// Everything chosen so far and the question for the next step
+fn draw_player_entry(
+    commands: &mut Commands,
+    g: &Game,
+    locale: &Locale,
+    settings: &Settings,
+    player: &CapturePlayer,
+    gamepads: &Gamepads,
+    pad_menu: &mut PadMenu,
+    ev_text: &mut EventWriter<BottomTextEvent>,
+) {
+    let pad = gamepads.iter().next().is_some();
     let n_player = g.player_info.len()+1;
-    print_text(&n_player.to_string(), &mut commands, g.fah(), Vec2::new(4.0, 9.0), WHITE, PlayerNameMenuScreen);
-    print_text("Enter name (12 letters max.)", &mut commands, g.fah(), Vec2::new(0.5, 8.0), WHITE, PlayerNameMenuScreen);
+    print_text(&locale.format("menu.player", &[&n_player]), commands, g.fah(), Vec2::new(0.5, 9.0), WHITE, PlayerNameMenuScreen);
+    print_text(&locale.format("menu.enter_name", &[&MAX_NAME_LEN]), commands, g.fah(), Vec2::new(0.5, 8.0), WHITE, PlayerNameMenuScreen);
+    let Some(name) = &player.name else {
+        player.editor.draw(commands, g.fah(), Vec2::new(0.5, 7.0), PlayerNameMenuScreen);
+        if pad {
+            let items = draw_keyboard(commands, g.fah(), locale);
+            pad_menu.set(GameState::PlayerNameMenu, items);
+        } else {
+            pad_menu.clear();
+        }
+        ev_text.send(BottomTextEvent::clear());
+        return;
+    };
+    ev_text.send(BottomTextEvent::prompt(&centred(&locale.format("menu.back", &[&action_keys(settings, Action::Cancel)]))));
+    print_text(name, commands, g.fah(), Vec2::new(0.5, 7.0), WHITE, PlayerNameMenuScreen);
+    print_text(locale.text("menu.computer"), commands, g.fah(), Vec2::new(0.5, 5.0), WHITE, PlayerNameMenuScreen);
+    let Some(computer_controlled) = player.computer_controlled else {
+        if pad {
+            print_text("Y N", commands, g.fah(), Vec2::new(11.0, 5.0), WHITE, PlayerNameMenuScreen);
+            pad_menu.set(GameState::PlayerNameMenu, vec![
+                PadMenuItem::new(Vec2::new(11.0, 5.0), 1, 'Y'),
+                PadMenuItem::new(Vec2::new(12.0, 5.0), 1, 'N'),
+            ]);
+        }
+        return;
+    };
+    let answer = locale.text(if computer_controlled { "menu.yes" } else { "menu.no" });
+    print_text(answer, commands, g.fah(), Vec2::new(11.0, 5.0), WHITE, PlayerNameMenuScreen);
+    print_text(locale.text("menu.character"), commands, g.fah(), Vec2::new(0.5, 4.0), WHITE, PlayerNameMenuScreen);
+    let Some(icon) = player.character_icon else {
+        let items = show_wizards(g.fah(), g.tah(), commands, settings, true, 3.0, &[]);
+        pad_menu.set(GameState::PlayerNameMenu, items);
+        return;
+    };
+    print_text(&icon.to_string(), commands, g.fah(), Vec2::new(9.0, 4.0), WHITE, PlayerNameMenuScreen);
+    print_text(locale.text("menu.color"), commands, g.fah(), Vec2::new(0.5, 2.0), WHITE, PlayerNameMenuScreen);
+    let items = show_wizards(g.fah(), g.tah(), commands, settings, false, 1.0, &used_colors(g));
+    pad_menu.set(GameState::PlayerNameMenu, items);
 }
 
-#[derive(Default)]
-struct CapturePlayer {
-    name: Option<String>,
-    computer_controlled: Option<bool>,
-    character_icon: Option<u8>,
-    color: Option<Color>,
+// Colours already taken by the wizards entered so far
+fn used_colors(g: &Game) -> Vec<usize> {
+    g.player_info.iter().map(|p| p.color_index).collect()
 }
 
-const MAX_NAME_LEN: usize = 12;
 fn player_name_menu_keyboard_input(
     mut char_evr: EventReader<ReceivedCharacter>,
     mut state: ResMut<NextState<GameState>>,
     mut g: ResMut<Game>,
     mut commands: Commands,
-    mut string: Local<String>,
-    mut player: Local<CapturePlayer>,
+    locale: Res<Locale>,
+    settings: Res<Settings>,
+    mut player: ResMut<CapturePlayer>,
     keys: Res<Input<KeyCode>>,
+    actions: Res<Input<Action>>,
     allspells: Res<AllSpells>,
+    profiles: Res<Profiles>,
+    gamepads: Res<Gamepads>,
+    mut pad_menu: ResMut<PadMenu>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+    existing: Query<Entity, Or<(With<PlayerNameMenuScreen>, With<OnScreenKeyboard>)>>,
 ) {
     if player.name.is_none() {
-        if keys.just_pressed(KeyCode::Return) && string.len() >= 1 {
-            player.name = Some(string.clone());
-            *string = String::new();
-            print_text("Computer Controlled?", &mut commands, g.fah(), Vec2::new(0.5, 5.0), WHITE, PlayerNameMenuScreen);
-            return;
-        }
-        for ev in char_evr.iter() {
-            if ev.char == 0x7f as char {
-                string.pop();
-            } else if string.len() < MAX_NAME_LEN && ((ev.char >= 'a' && ev.char <= 'z') || (ev.char >= 'A' && ev.char <= 'Z') || (ev.char >= '0' && ev.char <= '9') || ev.char == ' ') {
-                string.push(ev.char);
-            }
-            print_text(&string, &mut commands, g.fah(), Vec2::new(0.5, 7.0), WHITE, PlayerNameMenuScreen);
-            let spaces = MAX_NAME_LEN - string.len();
-            for i in string.len()..string.len()+spaces {
-                print_text(" ", &mut commands, g.fah(), Vec2::new(0.5 + f32::from(i as u8)/2.0, 7.0), WHITE, PlayerNameMenuScreen);
+        let changed = if keys.just_pressed(KeyCode::Return) && !player.editor.is_empty() {
+            player.name = Some(player.editor.text());
+            // A returning player gets their wizard back, choosing another colour if theirs is taken
+            if let Some(profile) = profiles.get(&player.editor.text()) {
+                player.computer_controlled = Some(false);
+                player.character_icon = Some(profile.character_icon);
+                if !used_colors(&g).contains(&profile.color_index) {
+                    add_player(&mut g, &player, profile.color_index, &settings, &allspells);
+                    state.set(GameState::PlayerNameMenuTransition);
+                    return;
+                }
             }
+            true
+        } else {
+            player.editor.update(&keys, &mut char_evr)
+        };
+        if changed {
+            redraw_player_entry(&mut commands, &g, &locale, &settings, &player, &gamepads, &mut pad_menu, &mut ev_text, &existing);
         }
         return;
     }
-    if player.computer_controlled.is_none() {
-        if keys.just_pressed(KeyCode::Y) {
-            player.computer_controlled = Some(true);
-            print_text("YES", &mut commands, g.fah(), Vec2::new(11.0, 5.0), WHITE, PlayerNameMenuScreen);
+    // Read every character, so none are left over to be typed into the name after going back
+    let choice = char_evr.iter().filter_map(|ev| ev.char.to_digit(10)).filter(|d| (1..=8).contains(d)).last();
+    let changed = if actions.just_pressed(Action::Cancel) {
+        // Back a step, keeping what was entered
+        if player.character_icon.take().is_none() && player.computer_controlled.take().is_none() {
+            player.name = None;
         }
-        if keys.just_pressed(KeyCode::N) {
+        true
+    } else if player.computer_controlled.is_none() {
+        if actions.just_pressed(Action::Yes) {
+            player.computer_controlled = Some(true);
+        } else if actions.just_pressed(Action::No) {
             player.computer_controlled = Some(false);
-            print_text("NO", &mut commands, g.fah(), Vec2::new(11.0, 5.0), WHITE, PlayerNameMenuScreen);
-        }
-        if player.computer_controlled.is_some() {
-            print_text("Which character?", &mut commands, g.fah(), Vec2::new(0.5, 4.0), WHITE, PlayerNameMenuScreen);
-            show_wizards(g.fah(), g.tah(), &mut commands, true, 3.0);
-        }
-        return;
-    }
-    if player.character_icon.is_none() {
-        for ev in char_evr.iter() {
-            let c = ev.char as u32;
-            if (49..=56).contains(&c) {
-                let choice = c-48;
-                player.character_icon = Some(choice as u8);
-                print_text(&choice.to_string(), &mut commands, g.fah(), Vec2::new(9.0, 4.0), WHITE, PlayerNameMenuScreen);
-                print_text("Which color?", &mut commands, g.fah(), Vec2::new(0.5, 2.0), WHITE, PlayerNameMenuScreen);
-                show_wizards(g.fah(), g.tah(), &mut commands, false, 1.0);
-            }
         }
+        player.computer_controlled.is_some()
+    } else if player.character_icon.is_some() {
+        let Some(color_index) = choice.map(|d| d as usize - 1).filter(|c| !used_colors(&g).contains(c)) else {
+            return;
+        };
+        add_player(&mut g, &player, color_index, &settings, &allspells);
+        state.set(GameState::PlayerNameMenuTransition);
         return;
+    } else {
+        player.character_icon = choice.map(|d| d as u8);
+        choice.is_some()
+    };
+    if changed {
+        redraw_player_entry(&mut commands, &g, &locale, &settings, &player, &gamepads, &mut pad_menu, &mut ev_text, &existing);
     }
-    if player.color.is_none() {
-        for ev in char_evr.iter() {
-            let c = ev.char as u32;
-            if (49..=56).contains(&c) {
-                let choice = c-48;
-                player.color= Some(WIZARD_COLORS[choice as usize - 1]);
-                //print_text(&*choice.to_string(), &mut commands, g.fah(), Vec2::new(7.5, 3.0), PlayerNameMenuScreen);
-            }
-        }
-    }
-    if player.color.is_some() {
-        let mut p = Player::new(
-            player.name.clone().unwrap(),
-            player.computer_controlled.unwrap(),
-            player.character_icon.unwrap(),
-            player.color.unwrap(),
-        );
-        p.pick_spells(&allspells);
-        g.player_info.push(p);
-        *player = CapturePlayer{..Default::default()};
-        state.set(GameState::PlayerNameMenuTransition);
+}
+
+fn add_player(g: &mut Game, player: &CapturePlayer, color_index: usize, settings: &Settings, allspells: &AllSpells) {
+    let mut p = Player::new(
+        player.name.clone().unwrap(),
+        player.computer_controlled.unwrap(),
+        player.character_icon.unwrap(),
+        color_index,
+        wizard_colors(settings)[color_index],
+    );
+    p.ai_level = g.ai_level;
+    p.pick_spells(allspells);
+    g.player_info.push(p);
+}
+
+fn redraw_player_entry(
+    commands: &mut Commands,
+    g: &Game,
+    locale: &Locale,
+    settings: &Settings,
+    player: &CapturePlayer,
+    gamepads: &Gamepads,
+    pad_menu: &mut PadMenu,
+    ev_text: &mut EventWriter<BottomTextEvent>,
+    existing: &Query<Entity, Or<(With<PlayerNameMenuScreen>, With<OnScreenKeyboard>)>>,
+) {
+    for e in existing {
+        commands.entity(e).despawn();
     }
+    draw_player_entry(commands, g, locale, settings, player, gamepads, pad_menu, ev_text);
 }
 
-fn show_wizards(fah: Handle<TextureAtlas>, tah: Handle<TextureAtlas>, commands: &mut Commands, colors: bool, y: f32) {
-    for (i, col) in WIZARD_COLORS.iter().enumerate() {
-        print_text(&(i+1).to_string(), commands, fah.clone(), Vec2::new((i as f32).mul_add(1.5, 0.5), y), WHITE, PlayerNameMenuScreen);
+// Colours in use by other wizards are left out, so no two wizards look the same
+fn show_wizards(
+    fah: Handle<TextureAtlas>,
+    tah: Handle<TextureAtlas>,
+    commands: &mut Commands,
+    settings: &Settings,
+    colors: bool,
+    y: f32,
+    used: &[usize],
+) -> Vec<PadMenuItem> {
+    let mut items = Vec::new();
+    for (i, col) in wizard_colors(settings).iter().enumerate() {
+        if used.contains(&i) {
+            continue;
+        }
+        let pos = Vec2::new((i as f32).mul_add(1.5, 0.5), y);
+        let key = char::from_digit(i as u32 + 1, 10).unwrap();
+        print_text(&key.to_string(), commands, fah.clone(), pos, WHITE, PlayerNameMenuScreen);
         let color = if colors { WHITE } else { *col };
         print_wizard(commands, tah.clone(), Vec2::new((i as f32).mul_add(1.5, 1.25), y), i, color, PlayerNameMenuScreen);
+        // Highlight both the number and the wizard next to it
+        items.push(PadMenuItem::new(pos, 3, key));
     }
+    items
 }
 
+// Once every wizard is entered they can all be checked over before the game starts
 fn player_name_menu_transition(
     mut state: ResMut<NextState<GameState>>,
-    mut g: ResMut<Game>,
-    mut commands: Commands,
-    mut ev_board_put: EventWriter<BoardPutEntity>,
+    g: Res<Game>,
 ) {
     if g.players == g.player_info.len() as u8 {
-        let tah = g.tah();
-        let positions = crate::player::get_start_positions(g.players as usize).unwrap();
-        for (i, p) in &mut g.player_info.iter_mut().enumerate() {
-            let pos = positions[i];
-            p.spawn(&mut commands, tah.clone(), pos);
-            ev_board_put.send(BoardPutEntity { entity: p.handle.unwrap(), pos });
-        }
-        state.set(GameState::TurnMenu);
+        state.set(GameState::PlayerSetup);
     } else {
         state.set(GameState::PlayerNameMenu);
     }
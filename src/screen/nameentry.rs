@@ -0,0 +1,176 @@
+use bevy::prelude::*;
+
+use crate::display::{print_text, WHITE};
+use crate::font::char_to_pos;
+use crate::gamepad::PadMenuItem;
+use crate::locale::Locale;
+
+pub const MAX_NAME_LEN: usize = 12;
+
+// Under the text, not over it like the pad highlight
+const CURSOR_Z: f32 = 0.5;
+
+// Tag component for the on screen keyboard used to enter names with a gamepad
+#[derive(Component, Clone, Copy)]
+pub struct OnScreenKeyboard;
+
+const KEYBOARD_ROWS: [&str; 3] = [
+    "ABCDEFGHIJKLM",
+    "NOPQRSTUVWXYZ",
+    "0123456789",
+];
+
+// A name being typed, with a cursor which can be moved to fix mistakes
+#[derive(Default)]
+pub struct NameEditor {
+    chars: Vec<char>,
+    cursor: usize,
+}
+
+impl NameEditor {
+    pub fn new(name: &str) -> Self {
+        let chars: Vec<char> = name.chars().collect();
+        Self { cursor: chars.len(), chars }
+    }
+    pub fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+    fn insert(&mut self, c: char) -> bool {
+        // Accented letters too, as long as the font has them
+        let allowed = (c.is_alphanumeric() && char_to_pos(c) > 0) || c == ' ';
+        if !allowed || self.chars.len() >= MAX_NAME_LEN {
+            return false;
+        }
+        self.chars.insert(self.cursor, c);
+        self.cursor += 1;
+        true
+    }
+    fn backspace(&mut self) -> bool {
+        if self.cursor == 0 {
+            return false;
+        }
+        self.cursor -= 1;
+        self.chars.remove(self.cursor);
+        true
+    }
+    fn delete(&mut self) -> bool {
+        if self.cursor >= self.chars.len() {
+            return false;
+        }
+        self.chars.remove(self.cursor);
+        true
+    }
+    fn move_cursor(&mut self, to: usize) -> bool {
+        let to = to.min(self.chars.len());
+        let moved = to != self.cursor;
+        self.cursor = to;
+        moved
+    }
+    // Returns whether anything changed and the name needs drawing again
+    pub fn update(&mut self, keys: &Input<KeyCode>, char_evr: &mut EventReader<ReceivedCharacter>) -> bool {
+        let chars: Vec<char> = char_evr.iter().map(|ev| ev.char).collect();
+        self.update_chars(keys, &chars)
+    }
+    // The same, for screens which look at what was typed themselves
+    pub fn update_chars(&mut self, keys: &Input<KeyCode>, chars: &[char]) -> bool {
+        let mut changed = false;
+        if keys.just_pressed(KeyCode::Back) {
+            changed |= self.backspace();
+        }
+        if keys.just_pressed(KeyCode::Delete) {
+            changed |= self.delete();
+        }
+        if keys.just_pressed(KeyCode::Left) {
+            changed |= self.move_cursor(self.cursor.saturating_sub(1));
+        }
+        if keys.just_pressed(KeyCode::Right) {
+            changed |= self.move_cursor(self.cursor + 1);
+        }
+        if keys.just_pressed(KeyCode::Home) {
+            changed |= self.move_cursor(0);
+        }
+        if keys.just_pressed(KeyCode::End) {
+            changed |= self.move_cursor(self.chars.len());
+        }
+        for c in chars {
+            changed |= self.insert(*c);
+        }
+        changed
+    }
+    // The name with a line under where the next letter goes
+    pub fn draw(&self, commands: &mut Commands, fah: Handle<TextureAtlas>, pos: Vec2, component: impl Component + Copy) {
+        print_text(&self.text(), commands, fah, pos, WHITE, component);
+        let x = (self.cursor as f32).mul_add(0.5, pos.x);
+        commands.spawn(SpriteBundle {
+            sprite: Sprite {
+                color: WHITE,
+                custom_size: Some(Vec2::new(0.5, 0.0625)),
+                ..default()
+            },
+            transform: Transform::from_xyz(x, pos.y - 0.4375, CURSOR_Z),
+            ..default()
+        }).insert(component);
+    }
+}
+
+pub fn draw_keyboard(
+    commands: &mut Commands,
+    fah: Handle<TextureAtlas>,
+    locale: &Locale,
+) -> Vec<PadMenuItem> {
+    let mut items = Vec::new();
+    for (row, keys) in KEYBOARD_ROWS.iter().enumerate() {
+        let y = 5.0 - row as f32;
+        for (col, key) in keys.chars().enumerate() {
+            let pos = Vec2::new(0.5 + col as f32, y);
+            print_text(&key.to_string(), commands, fah.clone(), pos, WHITE, OnScreenKeyboard);
+            items.push(PadMenuItem::new(pos, 1, key));
+        }
+    }
+    // Cursor keys, space, delete and enter, sent as keys the name entry already handles
+    let keys = [
+        ("<", '<'),
+        (">", '>'),
+        (locale.text("menu.key.space"), ' '),
+        (locale.text("menu.key.delete"), '\u{7f}'),
+        (locale.text("menu.key.end"), '\r'),
+    ];
+    let mut x = 0.5;
+    for (label, key) in keys {
+        let pos = Vec2::new(x, 2.0);
+        print_text(label, commands, fah.clone(), pos, WHITE, OnScreenKeyboard);
+        items.push(PadMenuItem::new(pos, label.chars().count(), key));
+        x += (label.chars().count() as f32).mul_add(0.5, 1.0);
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_editor() {
+        let mut name = NameEditor::new("Gndalf");
+        assert!(name.move_cursor(1));
+        assert!(name.insert('a'));
+        assert_eq!(name.text(), "Gandalf");
+        assert!(name.move_cursor(100));
+        assert!(!name.delete());
+        assert!(name.backspace());
+        assert_eq!(name.text(), "Gandal");
+        assert!(name.move_cursor(0));
+        assert!(!name.backspace());
+        assert!(name.delete());
+        assert_eq!(name.text(), "andal");
+        assert!(!name.insert('\u{7f}'));
+        assert!(name.insert('é'));
+        for c in "xxxxxxxxxx".chars() {
+            name.insert(c);
+        }
+        assert_eq!(name.text().chars().count(), MAX_NAME_LEN);
+    }
+}
@@ -0,0 +1,129 @@
+use bevy::prelude::*;
+
+use crate::display::{centred, print_text, BottomTextEvent, WHITE};
+use crate::game::Game;
+use crate::gamepad::{PadMenu, PadMenuItem};
+use crate::gamestate::GameState;
+use crate::input::{action_keys, Action};
+use crate::locale::{languages, Locale};
+use crate::settings::{Settings, MAX_VOLUME};
+use crate::system;
+
+pub struct OptionsPlugin;
+
+impl Plugin for OptionsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_system(options_draw.in_schedule(OnEnter(GameState::Options)))
+            .add_system(options_keyboard_input.in_set(OnUpdate(GameState::Options)))
+            .add_system(options_draw.run_if(resource_changed::<Settings>().or_else(resource_changed::<Locale>())).in_set(OnUpdate(GameState::Options)))
+            .add_system(system::despawn_screen::<OptionsScreen>.in_schedule(OnExit(GameState::Options)))
+            ;
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+struct OptionsScreen;
+
+fn options_lines(settings: &Settings, locale: &Locale) -> Vec<String> {
+    let sound = locale.text(if settings.muted { "options.off" } else { "options.on" });
+    let fullscreen = locale.text(if settings.fullscreen { "options.on" } else { "options.off" });
+    let markers = locale.text(if settings.owner_markers { "options.on" } else { "options.off" });
+    let pass = locale.text(if settings.pass_screen { "options.on" } else { "options.off" });
+    let digits = locale.text(if settings.chance_digits { "options.on" } else { "options.off" });
+    let options = [
+        locale.format("options.sound", &[&sound]),
+        locale.format("options.volume", &[&settings.volume, &MAX_VOLUME]),
+        locale.format("options.language", &[&locale.text("language")]),
+        locale.format("options.fullscreen", &[&fullscreen]),
+        locale.format("options.palette", &[&locale.text(settings.palette.name())]),
+        locale.format("options.markers", &[&markers]),
+        locale.format("options.pass", &[&pass]),
+        locale.format("options.chance_digits", &[&digits]),
+    ];
+    options.iter().enumerate().map(|(i, option)| format!("{}. {option}", i + 1)).collect()
+}
+
+// Redrawn whenever a setting changes, to show the new value
+fn options_draw(
+    mut commands: Commands,
+    g: Res<Game>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+    mut pad_menu: ResMut<PadMenu>,
+    existing: Query<Entity, With<OptionsScreen>>,
+) {
+    for e in &existing {
+        commands.entity(e).despawn();
+    }
+    print_text(&centred(locale.text("options.title")), &mut commands, g.fah(), Vec2::new(0.0, 9.0), WHITE, OptionsScreen);
+    let lines = options_lines(&settings, &locale);
+    let mut items = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let pos = Vec2::new(0.0, 8.0 - i as f32);
+        print_text(line, &mut commands, g.fah(), pos, WHITE, OptionsScreen);
+        items.push(PadMenuItem::new(pos, line.chars().count(), line.chars().next().unwrap()));
+    }
+    // Keep the pad highlight where it was when redrawing after a change
+    if !pad_menu.active_in(&GameState::Options) {
+        pad_menu.set(GameState::Options, items);
+    }
+    ev_text.send(BottomTextEvent::prompt(&locale.format("menu.press_keys", &[&lines.len(), &action_keys(&settings, Action::Back)])));
+}
+
+fn options_keyboard_input(
+    mut state: ResMut<NextState<GameState>>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Input<Action>>,
+    mut settings: ResMut<Settings>,
+) {
+    if actions.just_pressed(Action::Back) || actions.just_pressed(Action::Cancel) {
+        actions.reset_all();
+        state.set(GameState::Help);
+        return;
+    }
+    if keys.just_pressed(KeyCode::Key1) {
+        keys.reset(KeyCode::Key1);
+        settings.muted = !settings.muted;
+        settings.save();
+    }
+    if keys.just_pressed(KeyCode::Key2) {
+        keys.reset(KeyCode::Key2);
+        settings.volume = (settings.volume + 1) % (MAX_VOLUME + 1);
+        settings.muted = false;
+        settings.save();
+    }
+    if keys.just_pressed(KeyCode::Key3) {
+        keys.reset(KeyCode::Key3);
+        let languages = languages();
+        let next = languages.iter().position(|l| *l == settings.language).map_or(0, |i| (i + 1) % languages.len());
+        settings.language.clone_from(&languages[next]);
+        settings.save();
+    }
+    if keys.just_pressed(KeyCode::Key4) {
+        keys.reset(KeyCode::Key4);
+        settings.fullscreen = !settings.fullscreen;
+        settings.save();
+    }
+    if keys.just_pressed(KeyCode::Key5) {
+        keys.reset(KeyCode::Key5);
+        settings.palette = settings.palette.next();
+        settings.save();
+    }
+    if keys.just_pressed(KeyCode::Key6) {
+        keys.reset(KeyCode::Key6);
+        settings.owner_markers = !settings.owner_markers;
+        settings.save();
+    }
+    if keys.just_pressed(KeyCode::Key7) {
+        keys.reset(KeyCode::Key7);
+        settings.pass_screen = !settings.pass_screen;
+        settings.save();
+    }
+    if keys.just_pressed(KeyCode::Key8) {
+        keys.reset(KeyCode::Key8);
+        settings.chance_digits = !settings.chance_digits;
+        settings.save();
+    }
+}
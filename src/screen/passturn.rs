@@ -0,0 +1,91 @@
+use bevy::prelude::*;
+
+use crate::cursor::Cursor;
+use crate::display::{centred, print_text, BottomTextEvent, WHITE};
+use crate::game::Game;
+use crate::gamestate::GameState;
+use crate::input::{any_just_pressed, Action};
+use crate::locale::Locale;
+use crate::settings::Settings;
+use crate::system;
+
+// A blank screen between hot-seat players, so the one taking over doesn't see what the last one
+// chose. Turned on in the options, and only shown to people: computer wizards go straight on.
+pub struct PassTurnPlugin;
+
+impl Plugin for PassTurnPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<PassTurn>()
+            .add_systems((pass_turn_setup, system::hide_board_entities).in_schedule(OnEnter(GameState::PassTurn)))
+            .add_system(pass_turn_keyboard.in_set(OnUpdate(GameState::PassTurn)))
+            .add_system(system::despawn_screen::<PassTurnScreen>.in_schedule(OnExit(GameState::PassTurn)))
+            ;
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+struct PassTurnScreen;
+
+// Where the current player is going once they have the keyboard
+#[derive(Resource)]
+pub struct PassTurn {
+    next: GameState,
+}
+
+impl Default for PassTurn {
+    fn default() -> Self {
+        Self { next: GameState::TurnMenu }
+    }
+}
+
+impl PassTurn {
+    // Goes to next, by way of the pass screen if the current player needs it
+    pub fn to(&mut self, next: GameState, g: &Game, settings: &Settings, state: &mut NextState<GameState>) {
+        let humans = g.player_info.iter().filter(|p| p.alive && !p.computer_controlled).count();
+        // Nobody to hide anything from with only one person playing
+        if settings.pass_screen && humans > 1 && !g.get_player().computer_controlled {
+            self.next = next;
+            state.set(GameState::PassTurn);
+        } else {
+            state.set(next);
+        }
+    }
+}
+
+fn pass_turn_setup(
+    mut commands: Commands,
+    g: Res<Game>,
+    locale: Res<Locale>,
+    mut cursor: ResMut<Cursor>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+) {
+    cursor.set_invisible();
+    let text = centred(&locale.format("pass.to", &[&g.get_player().name]));
+    print_text(&text, &mut commands, g.fah(), Vec2::new(0.0, 5.0), WHITE, PassTurnScreen);
+    ev_text.send(BottomTextEvent::clear());
+    ev_text.send(BottomTextEvent::prompt(&centred(locale.text("pass.any_key"))));
+}
+
+fn pass_turn_keyboard(
+    mut keys: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Input<Action>>,
+    pass: Res<PassTurn>,
+    g: Res<Game>,
+    locale: Res<Locale>,
+    mut state: ResMut<NextState<GameState>>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+    board_q: Query<&mut Visibility, With<system::BoardEntity>>,
+) {
+    if !any_just_pressed(&keys, &actions) {
+        return;
+    }
+    keys.reset_all();
+    actions.reset_all();
+    if pass.next == GameState::MoveChoose {
+        // Back to the board, which nothing else puts back on the way into moving
+        system::show_board_entities(board_q);
+        ev_text.send(BottomTextEvent::from(&locale.format("move.turn", &[&g.get_player().name])));
+    }
+    state.set(pass.next.clone());
+}
@@ -0,0 +1,373 @@
+use bevy::prelude::*;
+use rand::prelude::SliceRandom;
+use rand::Rng;
+
+use crate::board::BoardPutEntity;
+use crate::clock::Clock;
+use crate::display::*;
+use crate::draft::Draft;
+use crate::game::{start_game, Game, GameRng};
+use crate::gamepad::{PadMenu, PadMenuItem};
+use crate::gamestate::GameState;
+use crate::locale::Locale;
+use crate::messagelog::LogEvent;
+use crate::palette::{wizard_colors, WIZARD_COUNT};
+use crate::player::{deal_spells, team_letter, Player};
+use crate::profile::{Profile, Profiles};
+use crate::rules::PRESETS;
+use crate::settings::Settings;
+use crate::spell::AllSpells;
+use crate::system;
+use super::passturn::PassTurn;
+use super::nameentry::{draw_keyboard, NameEditor, OnScreenKeyboard, MAX_NAME_LEN};
+
+pub struct PlayerSetupPlugin;
+
+impl Plugin for PlayerSetupPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<PlayerSetup>()
+            .add_system(player_setup_enter.in_schedule(OnEnter(GameState::PlayerSetup)))
+            .add_system(player_setup_keyboard_input.in_set(OnUpdate(GameState::PlayerSetup)))
+            .add_system(player_setup_draw.run_if(resource_changed::<PlayerSetup>().or_else(resource_changed::<Game>()).or_else(resource_changed::<Clock>())).in_set(OnUpdate(GameState::PlayerSetup)))
+            .add_system(system::despawn_screen::<PlayerSetupScreen>.in_schedule(OnExit(GameState::PlayerSetup)))
+            .add_system(system::despawn_screen::<OnScreenKeyboard>.in_schedule(OnExit(GameState::PlayerSetup)))
+            ;
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+struct PlayerSetupScreen;
+
+#[derive(Default, Resource)]
+struct PlayerSetup {
+    selected: usize,
+    renaming: Option<NameEditor>,
+    // Wizards in each team, or None for everyone against everyone
+    team_size: Option<usize>,
+    // Place in the rules presets
+    rules: usize,
+}
+
+// The team games there are, as the number of wizards and the size of each team
+const TEAM_MODES: [(usize, usize); 3] = [(4, 2), (6, 3), (6, 2)];
+
+// Keys for changing the chosen wizard, and what they do
+const COMMANDS: [(char, &str); 9] = [
+    ('U', "setup.rules"),
+    ('N', "setup.name"),
+    ('P', "setup.profile"),
+    ('C', "setup.computer"),
+    ('I', "setup.icon"),
+    ('O', "setup.colour"),
+    ('R', "setup.random"),
+    ('T', "setup.time"),
+    ('S', "setup.start"),
+];
+
+fn player_setup_enter(
+    mut setup: ResMut<PlayerSetup>,
+) {
+    *setup = PlayerSetup::default();
+}
+
+fn player_setup_draw(
+    mut commands: Commands,
+    g: Res<Game>,
+    setup: Res<PlayerSetup>,
+    clock: Res<Clock>,
+    locale: Res<Locale>,
+    gamepads: Res<Gamepads>,
+    mut pad_menu: ResMut<PadMenu>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+    existing: Query<Entity, Or<(With<PlayerSetupScreen>, With<OnScreenKeyboard>)>>,
+) {
+    for e in &existing {
+        commands.entity(e).despawn();
+    }
+    let pad = gamepads.iter().next().is_some();
+    if let Some(editor) = &setup.renaming {
+        print_text(&locale.format("setup.rename", &[&(setup.selected + 1)]), &mut commands, g.fah(), Vec2::new(0.5, 9.0), WHITE, PlayerSetupScreen);
+        print_text(&locale.format("menu.enter_name", &[&MAX_NAME_LEN]), &mut commands, g.fah(), Vec2::new(0.5, 8.0), WHITE, PlayerSetupScreen);
+        editor.draw(&mut commands, g.fah(), Vec2::new(0.5, 7.0), PlayerSetupScreen);
+        if pad {
+            let items = draw_keyboard(&mut commands, g.fah(), &locale);
+            pad_menu.set(GameState::PlayerSetup, items);
+        } else {
+            pad_menu.clear();
+        }
+        ev_text.send(BottomTextEvent::prompt(&centred(locale.text("setup.rename_done"))));
+        return;
+    }
+    // The rules take the title's place, to leave room for all the commands
+    let rules = locale.format("setup.rules_line", &[&locale.text(PRESETS[setup.rules].0)]);
+    print_text(&rules, &mut commands, g.fah(), Vec2::new(0.5, 9.0), WHITE, PlayerSetupScreen);
+    let mut items = Vec::new();
+    for (i, player) in g.player_info.iter().enumerate() {
+        let y = 8.0 - i as f32;
+        let key = char::from_digit(i as u32 + 1, 10).unwrap();
+        if i == setup.selected {
+            print_text(">", &mut commands, g.fah(), Vec2::new(0.0, y), WHITE, PlayerSetupScreen);
+        }
+        print_text(&key.to_string(), &mut commands, g.fah(), Vec2::new(0.5, y), WHITE, PlayerSetupScreen);
+        print_text(&player.name, &mut commands, g.fah(), Vec2::new(1.5, y), WHITE, PlayerSetupScreen);
+        print_wizard(&mut commands, g.tah(), Vec2::new(8.0, y), player.character_icon as usize - 1, player.color, PlayerSetupScreen);
+        if player.computer_controlled {
+            print_text(locale.text("setup.cpu"), &mut commands, g.fah(), Vec2::new(9.0, y), WHITE, PlayerSetupScreen);
+        }
+        if let Some(team) = player.team {
+            print_text(&team_letter(team).to_string(), &mut commands, g.fah(), Vec2::new(1.0, y), YELLOW, PlayerSetupScreen);
+        }
+        items.push(PadMenuItem::new(Vec2::new(0.5, y), 1, key));
+    }
+    // Team games only go up to six wizards, which leaves room under the list
+    if next_team_size(g.player_info.len(), None).is_some() {
+        let teams = setup.team_size.map_or_else(|| locale.text("setup.no_teams").to_string(), |size| describe_teams(g.player_info.len(), size));
+        let line = format!("M {}", locale.format("setup.teams", &[&teams]));
+        print_text(&line, &mut commands, g.fah(), Vec2::new(0.5, 1.0), WHITE, PlayerSetupScreen);
+        items.push(PadMenuItem::new(Vec2::new(0.5, 1.0), line.chars().count(), 'M'));
+    }
+    for (i, (key, text)) in COMMANDS.iter().enumerate() {
+        let pos = Vec2::new(11.0, 9.0 - i as f32);
+        let line = format!("{key} {}", locale.text(text));
+        print_text(&line, &mut commands, g.fah(), pos, WHITE, PlayerSetupScreen);
+        items.push(PadMenuItem::new(pos, line.chars().count(), *key));
+    }
+    // Under both lists, there's no room for it by the T
+    print_text(&clock.describe(&locale), &mut commands, g.fah(), Vec2::new(0.5, 0.0), WHITE, PlayerSetupScreen);
+    // Keep the pad highlight where it was when redrawing after a change
+    if !pad_menu.active_in(&GameState::PlayerSetup) {
+        pad_menu.set(GameState::PlayerSetup, items);
+    }
+    ev_text.send(BottomTextEvent::prompt(&centred(&locale.format("setup.pick", &[&g.player_info.len()]))));
+}
+
+// The team game after this one for the number of wizards, back to None after the last
+fn next_team_size(players: usize, current: Option<usize>) -> Option<usize> {
+    let sizes: Vec<usize> = TEAM_MODES.iter().filter(|(n, _)| *n == players).map(|(_, size)| *size).collect();
+    current.and_then(|c| sizes.iter().position(|s| *s == c))
+        .map_or_else(|| sizes.first().copied(), |i| sizes.get(i + 1).copied())
+}
+
+// Like 2 v 2 v 2
+fn describe_teams(players: usize, size: usize) -> String {
+    vec![size.to_string(); players / size].join(" v ")
+}
+
+// The wizards in order make up the teams, so the first few are team A
+fn set_teams(players: &mut [Player], size: Option<usize>) {
+    for (i, player) in players.iter_mut().enumerate() {
+        player.team = size.map(|size| (i / size) as u8);
+    }
+}
+
+// The next colour round from the wizard's own which no other wizard has
+fn next_free_color(players: &[Player], i: usize) -> usize {
+    let used: Vec<usize> = players.iter().enumerate().filter(|(j, _)| *j != i).map(|(_, p)| p.color_index).collect();
+    let current = players[i].color_index;
+    (1..=WIZARD_COUNT)
+        .map(|step| (current + step) % WIZARD_COUNT)
+        .find(|c| !used.contains(c))
+        .unwrap_or(current)
+}
+
+// The next saved profile after the wizard's own which isn't already in the game
+fn next_profile<'a>(profiles: &'a Profiles, players: &[Player], i: usize) -> Option<&'a Profile> {
+    let in_game = |name: &str| players.iter().any(|p| p.name == name);
+    let start = profiles.0.iter().position(|p| p.name == players[i].name).map_or(0, |k| k + 1);
+    (0..profiles.0.len())
+        .map(|k| &profiles.0[(start + k) % profiles.0.len()])
+        .find(|p| !in_game(&p.name))
+}
+
+fn use_profile(players: &mut [Player], i: usize, profile: &Profile) {
+    let player = &mut players[i];
+    player.name.clone_from(&profile.name);
+    player.computer_controlled = false;
+    player.character_icon = profile.character_icon;
+    player.color_index = profile.color_index;
+    if players.iter().enumerate().any(|(j, p)| j != i && p.color_index == profile.color_index) {
+        players[i].color_index = next_free_color(players, i);
+    }
+}
+
+// New looks for the computer wizards, from the colours the human players haven't taken
+fn randomise_computer_wizards(players: &mut [Player], rng: &mut impl Rng) {
+    let mut free: Vec<usize> = (0..WIZARD_COUNT)
+        .filter(|c| !players.iter().any(|p| !p.computer_controlled && p.color_index == *c))
+        .collect();
+    free.shuffle(rng);
+    for player in players.iter_mut().filter(|p| p.computer_controlled) {
+        player.character_icon = rng.gen_range(1..=8);
+        player.color_index = free.pop().unwrap_or(player.color_index);
+    }
+}
+
+fn recolor(players: &mut [Player], settings: &Settings) {
+    let colors = wizard_colors(settings);
+    for player in players {
+        player.color = colors[player.color_index];
+    }
+}
+
+fn player_setup_keyboard_input(
+    mut char_evr: EventReader<ReceivedCharacter>,
+    mut state: ResMut<NextState<GameState>>,
+    mut g: ResMut<Game>,
+    mut setup: ResMut<PlayerSetup>,
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    profiles: Res<Profiles>,
+    mut clock: ResMut<Clock>,
+    mut pass: ResMut<PassTurn>,
+    mut ev_board_put: EventWriter<BoardPutEntity>,
+    mut ev_log: EventWriter<LogEvent>,
+    mut pad_menu: ResMut<PadMenu>,
+    mut rng: ResMut<GameRng>,
+    allspells: Res<AllSpells>,
+) {
+    let selected = setup.selected;
+    // Only redrawn when the name actually changes
+    if let Some(editor) = setup.bypass_change_detection().renaming.as_mut() {
+        if keys.just_pressed(KeyCode::Return) {
+            if !editor.is_empty() {
+                g.player_info[selected].name = editor.text();
+            }
+            setup.renaming = None;
+            // Back to the list rather than the on screen keyboard
+            pad_menu.clear();
+        } else if editor.update(&keys, &mut char_evr) {
+            setup.set_changed();
+        }
+        return;
+    }
+    let mut start = keys.just_pressed(KeyCode::Return);
+    for ev in &mut char_evr {
+        if let Some(d) = ev.char.to_digit(10) {
+            if (1..=g.player_info.len()).contains(&(d as usize)) {
+                setup.selected = d as usize - 1;
+            }
+            continue;
+        }
+        match ev.char.to_ascii_uppercase() {
+            'N' => setup.renaming = Some(NameEditor::new(&g.player_info[selected].name)),
+            'P' => {
+                if let Some(profile) = next_profile(&profiles, &g.player_info, selected) {
+                    use_profile(&mut g.player_info, selected, profile);
+                    recolor(&mut g.player_info, &settings);
+                }
+            }
+            'C' => g.player_info[selected].computer_controlled ^= true,
+            'I' => {
+                let player = &mut g.player_info[selected];
+                player.character_icon = player.character_icon % 8 + 1;
+            }
+            'O' => {
+                g.player_info[selected].color_index = next_free_color(&g.player_info, selected);
+                recolor(&mut g.player_info, &settings);
+            }
+            'R' => {
+                randomise_computer_wizards(&mut g.player_info, &mut rand::thread_rng());
+                recolor(&mut g.player_info, &settings);
+            }
+            'U' => setup.rules = (setup.rules + 1) % PRESETS.len(),
+            'T' => clock.next_preset(),
+            'M' => {
+                setup.team_size = next_team_size(g.player_info.len(), setup.team_size);
+                set_teams(&mut g.player_info, setup.team_size);
+            }
+            'S' => start = true,
+            _ => {}
+        }
+    }
+    if start {
+        // Fresh dice, in case a challenge seeded them last
+        *rng = GameRng::default();
+        // Everyone rolled and dealt again for the rules being played
+        g.rules = PRESETS[setup.rules].1;
+        let rules = g.rules;
+        // With a draft, only Disbelieve until the spells are picked
+        let dealt = if rules.draft { 0 } else { rules.spell_count };
+        for player in &mut g.player_info {
+            player.roll_stats(&rules, &mut rng.0);
+            player.spells.spells = deal_spells(&allspells, dealt, &mut rng.0);
+        }
+        if rules.draft {
+            commands.insert_resource(Draft::new(&allspells, g.player_info.len(), rules.spell_count, &mut rng.0));
+            state.set(GameState::Draft);
+            return;
+        }
+        start_game(&mut g, &mut commands, &mut ev_board_put, &mut ev_log, &locale);
+        ev_log.send(LogEvent::game(&g, &locale.format("log.rules", &[&locale.text(PRESETS[setup.rules].0)])));
+        clock.start_game(g.player_info.len());
+        pass.to(GameState::TurnMenu, &g, &settings, &mut state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::Stats;
+
+    fn wizards(colors: &[usize]) -> Vec<Player> {
+        colors.iter().enumerate().map(|(i, c)| Player::new(format!("W{i}"), i > 0, 1, *c, WHITE)).collect()
+    }
+
+    #[test]
+    fn test_next_free_color() {
+        let players = wizards(&[0, 1, 3]);
+        assert_eq!(next_free_color(&players, 0), 2);
+        assert_eq!(next_free_color(&players, 2), 4);
+        let players = wizards(&[0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(next_free_color(&players, 7), 7);
+    }
+
+    #[test]
+    fn test_next_profile() {
+        let mut profiles = Profiles::default();
+        for (name, color) in [("Ann", 5), ("Bob", 0), ("Cy", 3)] {
+            profiles.0.push(Profile { name: name.to_string(), character_icon: 2, color_index: color, rating: 1500.0, stats: Stats::default() });
+        }
+        let mut players = wizards(&[0, 1]);
+        players[0].name = "Bob".to_string();
+        let next = next_profile(&profiles, &players, 1).unwrap();
+        assert_eq!(next.name, "Ann");
+        use_profile(&mut players, 1, next);
+        assert_eq!(players[1].color_index, 5);
+        assert!(!players[1].computer_controlled);
+        // Bob is already playing
+        let next = next_profile(&profiles, &players, 1).unwrap();
+        assert_eq!(next.name, "Cy");
+        use_profile(&mut players, 1, next);
+        // Taking a colour someone else has moves on to the next free one
+        use_profile(&mut players, 0, &profiles.0[2]);
+        assert_eq!(players[0].color_index, 4);
+    }
+
+    #[test]
+    fn test_teams() {
+        assert_eq!(next_team_size(2, None), None);
+        assert_eq!(next_team_size(4, None), Some(2));
+        assert_eq!(next_team_size(4, Some(2)), None);
+        assert_eq!(next_team_size(6, Some(3)), Some(2));
+        assert_eq!(describe_teams(6, 2), "2 v 2 v 2");
+        let mut players = wizards(&[0, 1, 2, 3, 4, 5]);
+        set_teams(&mut players, Some(3));
+        let teams: Vec<Option<u8>> = players.iter().map(|p| p.team).collect();
+        assert_eq!(teams, [Some(0), Some(0), Some(0), Some(1), Some(1), Some(1)]);
+    }
+
+    #[test]
+    fn test_randomise_computer_wizards() {
+        let mut players = wizards(&[3, 3, 3, 3]);
+        randomise_computer_wizards(&mut players, &mut rand::thread_rng());
+        let mut colors: Vec<usize> = players.iter().map(|p| p.color_index).collect();
+        assert_eq!(colors[0], 3);
+        colors.sort_unstable();
+        colors.dedup();
+        assert_eq!(colors.len(), 4);
+        assert!(players.iter().all(|p| (1..=8).contains(&p.character_icon)));
+    }
+}
@@ -1,12 +1,24 @@
 use bevy::prelude::*;
 
+use crate::ai;
+use crate::challenge;
+use crate::clock::TimeUp;
 use crate::gamestate::GameState;
 use crate::display::BottomTextEvent;
-use crate::game::Game;
+use crate::fog::Fog;
+use crate::game::{Game, GameRng};
 use crate::board::{BoardPutEntity, GameBoard};
-use crate::player::CastFailed;
-use crate::cursor::{CURSOR_SPELL, PositionCursorOnEntity, Cursor};
-use crate::system;
+use crate::player::{CastFailed, CastSpellResult};
+use crate::creature::CreatureComponent;
+use crate::cursor::{CURSOR_SPELL, CursorMovedEvent, PositionCursorOnEntity, Cursor};
+use crate::highlight::{HighlightEvent, TARGET_COLOR};
+use crate::input::{any_just_pressed, Action};
+use crate::locale::Locale;
+use crate::messagelog::LogEvent;
+use crate::spell::{check_target, ASpell};
+use crate::constants::{WIDTH, HEIGHT};
+use crate::vec::Vec2I;
+use crate::system::{self, Named};
 
 pub struct SpellCastingPlugin;
 
@@ -17,13 +29,17 @@ impl Plugin for SpellCastingPlugin {
         .add_event::<CastSpellResult>()
 
         .add_system(system::show_board_entities.in_schedule(OnEnter(GameState::CastSpellSetup)))
-        .add_system(spell_next.in_set(OnUpdate(GameState::CastSpellSetup)))
+        .add_system(spell_next.run_if(challenge::undecided).in_set(OnUpdate(GameState::CastSpellSetup)))
 
         .add_system(cast_spell_setup.in_schedule(OnEnter(GameState::CastSpell)))
-        .add_system(cast_spell_keyboard.in_set(OnUpdate(GameState::CastSpell)))
+        .add_system(cast_spell_show_targets.in_schedule(OnEnter(GameState::CastSpell)))
+        .add_system(cast_spell_keyboard.run_if(ai::human_turn).in_set(OnUpdate(GameState::CastSpell)))
+        .add_system(cast_spell_computer.run_if(ai::computer_turn).in_set(OnUpdate(GameState::CastSpell)))
         .add_system(cast_spell.in_set(OnUpdate(GameState::CastSpell)))
         .add_system(cast_spell_result.in_set(OnUpdate(GameState::CastSpell)))
-        .add_system(super::board::board_describe_piece.in_set(OnUpdate(GameState::CastSpell)))
+        .add_system(cast_spell_describe_target.in_set(OnUpdate(GameState::CastSpell)))
+        .add_system(cast_spell_time_up.in_set(OnUpdate(GameState::CastSpell)))
+        .add_system(super::board::board_piece_info.in_set(OnUpdate(GameState::CastSpell)))
         .add_system(cast_spell_finish.in_schedule(OnExit(GameState::CastSpell)))
         ;
     }
@@ -33,13 +49,13 @@ fn spell_next(
     mut state: ResMut<NextState<GameState>>,
     mut g: ResMut<Game>,
 ) {
-    println!("spell_next");
+    debug!("spell_next");
     if g.player_turn >= g.players {
-        g.player_turn = 0;
-        println!("Spell casting finished, do movement now");
+        g.first_player_turn();
+        debug!("Spell casting finished, do movement now");
         state.set(GameState::MoveSetup);
     } else {
-        println!("Player turn to cast spell");
+        debug!("Player turn to cast spell");
         // Next player's turn to cast a spell
         state.set(GameState::CastSpell);
     }
@@ -47,15 +63,16 @@ fn spell_next(
 
 fn cast_spell_setup(
     g: Res<Game>,
+    locale: Res<Locale>,
     mut cursor: ResMut<Cursor>,
     mut ev_text: EventWriter<BottomTextEvent>,
     mut ev_cursor_pos: EventWriter<PositionCursorOnEntity>,
 ) {
-    println!("cast_spell_setup");
+    debug!("cast_spell_setup");
     cursor.set_type(CURSOR_SPELL);
     let player = g.get_player();
     if let Some(spell_name) = player.get_chosen_spell_name() {
-        ev_text.send(BottomTextEvent::from(&spell_name));
+        ev_text.send(BottomTextEvent::from(locale.name(&spell_name)));
         ev_cursor_pos.send(PositionCursorOnEntity(player.handle.unwrap()));
         cursor.hide_till_moved();
     }
@@ -65,6 +82,76 @@ struct CastSpell {
     target: Vec2
 }
 
+// As the wizard casting sees it: pieces in the fog aren't known about, so they neither stand in
+// the way nor fill the square, and are only found when the spell is cast
+fn preview_target(board: &GameBoard, fog: &Fog, spell: &dyn ASpell, from: Vec2I, to: Vec2I) -> Result<(), CastFailed> {
+    check_target(spell, from, to, |pos| board.has_entity_at(Vec2::from(pos)) && !fog.hides(pos))
+}
+
+fn cast_target(board: &GameBoard, spell: &dyn ASpell, from: Vec2I, to: Vec2I) -> Result<(), CastFailed> {
+    check_target(spell, from, to, |pos| board.has_entity_at(Vec2::from(pos)))
+}
+
+// Tint every square the chosen spell could be cast at
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+fn cast_spell_show_targets(
+    g: Res<Game>,
+    board: Res<GameBoard>,
+    fog: Res<Fog>,
+    mut ev_highlight: EventWriter<HighlightEvent>,
+) {
+    let player = g.get_player();
+    let Some(spell) = player.spells.get_chosen_spell() else {
+        return;
+    };
+    if spell.cast_range() == 0 {
+        return;
+    }
+    let from = board.get_entity_pos(player.handle.unwrap());
+    let mut squares = Vec::new();
+    for x in 0..WIDTH as i8 {
+        for y in 0..HEIGHT as i8 {
+            let to = Vec2I::new(x, y);
+            if GameBoard::in_bounds(to) && preview_target(&board, &fog, spell, from, to).is_ok() {
+                squares.push((to, TARGET_COLOR));
+            }
+        }
+    }
+    ev_highlight.send(HighlightEvent::Show(squares));
+}
+
+// Say whether the square under the cursor can be targeted before the spell is cast at it
+fn cast_spell_describe_target(
+    g: Res<Game>,
+    locale: Res<Locale>,
+    board: Res<GameBoard>,
+    mut ev_cursor: EventReader<CursorMovedEvent>,
+    mut ev_text: EventWriter<BottomTextEvent>,
+    named_q: Query<&Named>,
+    fog: Res<Fog>,
+) {
+    let player = g.get_player();
+    let Some(spell) = player.spells.get_chosen_spell() else {
+        return;
+    };
+    let Some(cur) = ev_cursor.iter().last() else {
+        return;
+    };
+    if spell.cast_range() == 0 {
+        return;
+    }
+    let from = board.get_entity_pos(player.handle.unwrap());
+    let to = Vec2I::from(cur.0);
+    let text = match preview_target(&board, &fog, spell, from, to) {
+        Ok(()) => board.get_entity(cur.0)
+            .filter(|_| !fog.hides(to))
+            .and_then(|e| named_q.get(e).ok())
+            .map_or_else(|| locale.text("cast.valid_target").to_string(), |named| locale.format("cast.target", &[&locale.name(&named.name)])),
+        Err(e) => locale.text(e.message()).to_string(),
+    };
+    ev_text.send(BottomTextEvent::from(&text));
+}
+
 fn cast_spell(
     mut g: ResMut<Game>,
     mut ev_cast: EventReader<CastSpell>,
@@ -74,11 +161,14 @@ fn cast_spell(
     mut ev_cast_res: EventWriter<CastSpellResult>,
     mut ev_board_put: EventWriter<BoardPutEntity>,
     board: Res<GameBoard>,
+    locale: Res<Locale>,
+    mut ev_log: EventWriter<LogEvent>,
+    creature_q: Query<&CreatureComponent>,
 ) {
     let player = g.get_player();
     let spell = player.spells.get_chosen_spell();
     if spell.is_none() {
-        println!("STATE POP - no spell");
+        debug!("STATE POP - no spell");
         state.set(GameState::CastSpellSetup);
         return;
     }
@@ -90,51 +180,83 @@ fn cast_spell(
         let to;
         if cast_range > 0 {
             to = e.target;
-            if board.get_entity(to).is_some() {
-                ev_cast_res.send(Err(CastFailed::NotThere));
+            let spell = g.get_player().spells.get_chosen_spell().unwrap();
+            if let Err(failed) = cast_target(&board, spell, Vec2I::from(from), Vec2I::from(to)) {
+                ev_cast_res.send(Err(failed));
                 return
             }
         } else {
             to = from;
         }
+        let spell = g.get_player().spells.get_chosen_spell().unwrap();
+        let english_name = spell.name();
+        let law_chaos = spell.law_rating();
+        let disbelieved = english_name == "Disbelieve"
+            && board.get_entity(to).and_then(|e| creature_q.get(e).ok()).is_some_and(|c| c.is_illusion);
+        let spell_name = locale.name(&english_name);
+        let log = LogEvent::player(&g, &locale.format("log.cast", &[&spell_name]));
         let player = g.get_player_mut();
+        let illusion = player.spells.illusion;
         let res = player.cast(from, to, &mut commands, tah.clone());
+        if res.is_ok() {
+            player.stats.cast(&english_name);
+            player.spells_law_chaos = player.spells_law_chaos.saturating_add(law_chaos);
+            if disbelieved {
+                player.stats.illusions_disbelieved += 1;
+            }
+        }
+        if let Ok(Some(_)) = res {
+            player.stats.creatures_summoned += 1;
+            if illusion {
+                player.stats.illusions_cast += 1;
+            }
+        }
         if let Ok(e) = res {
+            ev_log.send(log);
             if let Some(entity) = e {
                 ev_board_put.send(BoardPutEntity{
                     entity,
                     pos: to,
                 });
             }
-            println!("State POP");
+            debug!("State POP");
             state.set(GameState::CastSpellSetup);
         }
         ev_cast_res.send(res);
     }
 }
 
-type CastSpellResult = Result<Option<Entity>, CastFailed>;
+// A computer wizard whose cast is refused, at something it couldn't see in the fog, gives the
+// spell up rather than trying the same square again
 fn cast_spell_result(
+    mut g: ResMut<Game>,
     mut ev_cast: EventReader<CastSpellResult>,
+    locale: Res<Locale>,
     mut cursor: ResMut<Cursor>,
     mut ev_text: EventWriter<BottomTextEvent>,
+    mut state: ResMut<NextState<GameState>>,
 ) {
     for e in ev_cast.iter() {
         match e {
             Ok(_e) => {
             },
-            Err(CastFailed::OutOfRange) => {
-                ev_text.send(BottomTextEvent::from("Out of range"));
+            Err(failed) => {
+                let player = g.get_player_mut();
+                player.stats.spells_failed += 1;
+                if player.computer_controlled {
+                    player.spells.chosen_spell = None;
+                    state.set(GameState::CastSpellSetup);
+                }
+                ev_text.send(BottomTextEvent::important(locale.text(failed.message())));
                 cursor.hide_till_moved();
             }
-            Err(CastFailed::NotThere) => {
-            }
         }
     }
 }
 
 fn cast_spell_keyboard(
     mut keys: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Input<Action>>,
     cursor: Res<Cursor>,
     mut ev_cast: EventWriter<CastSpell>,
     g: ResMut<Game>,
@@ -145,25 +267,98 @@ fn cast_spell_keyboard(
     }
     let range = spell.unwrap().cast_range();
     if range == 0 {
-        let mut has_pressed = false;
-        for _ in keys.get_just_pressed() {
-            has_pressed = true;
-        }
-        if has_pressed {
-            println!("Key pressed on distance 0 spell");
+        if any_just_pressed(&keys, &actions) {
+            debug!("Key pressed on distance 0 spell");
             keys.reset_all();
+            actions.reset_all();
             ev_cast.send(CastSpell{target: Vec2::ZERO});
         }
         return;
     }
-    if keys.just_pressed(KeyCode::S) {
-        keys.reset(KeyCode::S);
+    if actions.just_pressed(Action::Select) {
+        actions.reset(Action::Select);
         let to = cursor.get_pos_v();
         ev_cast.send(CastSpell{target: to});
     }
 }
 
-fn cast_spell_finish(mut g: ResMut<Game>) {
-    println!("Finish cast spell, increment player turn");
-    g.player_turn += 1;
+// Computer wizards cast at the square they choose, and keep the spell if there's nowhere to cast it
+#[allow(clippy::too_many_arguments)]
+fn cast_spell_computer(
+    mut g: ResMut<Game>,
+    board: Res<GameBoard>,
+    fog: Res<Fog>,
+    pieces: ai::PieceQuery,
+    mut rng: ResMut<GameRng>,
+    time: Res<Time>,
+    mut wait: Local<Option<Timer>>,
+    mut cursor: ResMut<Cursor>,
+    mut ev_cast: EventWriter<CastSpell>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    let player = g.get_player();
+    let Some(spell) = player.spells.get_chosen_spell() else {
+        return;
+    };
+    if !ai::thought(&mut wait, &time) {
+        return;
+    }
+    if spell.cast_range() == 0 {
+        ev_cast.send(CastSpell{target: Vec2::ZERO});
+        return;
+    }
+    let from = board.get_entity_pos(player.handle.unwrap());
+    let view = ai::View::new(&g, &board, &fog, &pieces);
+    if let Some(to) = ai::choose_target(&view, spell, from, &mut rng.0) {
+        cursor.set_pos(Vec2::from(to));
+        ev_cast.send(CastSpell{target: Vec2::from(to)});
+    } else {
+        g.get_player_mut().spells.chosen_spell = None;
+        state.set(GameState::CastSpellSetup);
+    }
+}
+
+// Out of time before casting, the spell stays unused
+fn cast_spell_time_up(
+    mut ev_time_up: EventReader<TimeUp>,
+    mut state: ResMut<NextState<GameState>>,
+    mut g: ResMut<Game>,
+) {
+    if ev_time_up.iter().last().is_none() {
+        return;
+    }
+    g.get_player_mut().spells.chosen_spell = None;
+    state.set(GameState::CastSpellSetup);
+}
+
+fn cast_spell_finish(
+    mut g: ResMut<Game>,
+    mut ev_highlight: EventWriter<HighlightEvent>,
+) {
+    ev_highlight.send(HighlightEvent::Clear);
+    debug!("Finish cast spell, increment player turn");
+    g.next_player_turn();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spell::load_all_spells;
+
+    // Every square the preview tints can be cast at, and none of the others can
+    #[test]
+    fn test_preview_matches_casting() {
+        let mut board = GameBoard::new();
+        let from = Vec2I::new(3, 3);
+        for (i, pos) in [from, Vec2I::new(5, 3), Vec2I::new(3, 6), Vec2I::new(8, 8)].into_iter().enumerate() {
+            board.put_entity(Vec2::from(pos), Entity::from_raw(i as u32));
+        }
+        let fog = Fog::default();
+        for spell in load_all_spells().iter().filter(|s| s.cast_range() > 0) {
+            for to in GameBoard::squares() {
+                let preview = preview_target(&board, &fog, spell.as_ref(), from, to).is_ok();
+                assert_eq!(preview, cast_target(&board, spell.as_ref(), from, to).is_ok(), "{} at {to:?}", spell.name());
+            }
+        }
+    }
 }
@@ -1,11 +1,23 @@
 use bevy::prelude::*;
 
+use crate::ai;
+use crate::clock::TimeUp;
 use crate::cursor::{CURSOR_BOX, Cursor, PositionCursorOnEntity};
 use crate::display::*;
-use crate::game::Game;
+use crate::game::{Game, GameRng};
+use crate::gamepad::{PadMenu, PadMenuItem};
 use crate::gamestate::GameState;
+use crate::input::{action_keys, Action};
+use crate::font::char_to_pos;
+use crate::locale::Locale;
+use crate::rules::Illusions;
+use crate::palette::chance_color;
+use crate::settings::Settings;
+use crate::spell::SpellBox;
 use crate::system;
 use super::board;
+use super::logview::LogView;
+use super::passturn::PassTurn;
 
 pub struct TurnMenuPlugin;
 
@@ -13,11 +25,18 @@ impl Plugin for TurnMenuPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_event::<TurnMenuEvent>()
+            .init_resource::<PickIllusion>()
 
             .add_systems((turn_menu_setup, system::hide_board_entities).in_schedule(OnEnter(GameState::TurnMenu)))
-            .add_system(turn_menu_keyboard.in_set(OnUpdate(GameState::TurnMenu)))
+            .add_system(turn_menu_keyboard.run_if(ai::human_turn).in_set(OnUpdate(GameState::TurnMenu)))
+            .add_system(turn_menu_computer.run_if(ai::computer_turn).in_set(OnUpdate(GameState::TurnMenu)))
             .add_system(system::despawn_screen::<TurnMenu>.in_schedule(OnExit(GameState::TurnMenu)))
 
+            .add_system(turn_menu_time_up.in_set(OnUpdate(GameState::TurnMenu)))
+            .add_system(turn_menu_time_up.in_set(OnUpdate(GameState::TurnMenuExamineSpell)))
+            .add_system(turn_menu_time_up.in_set(OnUpdate(GameState::TurnMenuSelectSpell)))
+            .add_system(turn_menu_time_up.in_set(OnUpdate(GameState::TurnMenuExamineBoard)))
+
             // Specific transition/setup when going to next player
             .add_system(turn_menu_transition.in_set(OnUpdate(GameState::TurnMenuTransition)))
 
@@ -44,6 +63,7 @@ impl Plugin for TurnMenuPlugin {
             .add_systems((
                     turn_menu_examine_board_keyboard,
                     board::board_describe_piece,
+                    board::board_piece_info,
                 ).in_set(OnUpdate(GameState::TurnMenuExamineBoard)))
             .add_systems((
                     turn_menu_examine_board_exit,
@@ -59,19 +79,33 @@ struct TurnMenu;
 fn turn_menu_setup(
     mut commands: Commands,
     g: Res<Game>,
+    locale: Res<Locale>,
     mut keys: ResMut<Input<KeyCode>>,
+    mut pad_menu: ResMut<PadMenu>,
 ) {
     keys.clear();
     print_text(&g.get_player().name, &mut commands, g.fah(), Vec2::new(1.0, 7.0), WHITE, TurnMenu);
-    print_text("1. Examine Spells", &mut commands, g.fah(), Vec2::new(1.0, 5.0), WHITE, TurnMenu);
-    print_text("2. Select Spell", &mut commands, g.fah(), Vec2::new(1.0, 4.0), WHITE, TurnMenu);
-    print_text("3. Examine Board", &mut commands, g.fah(), Vec2::new(1.0, 3.0), WHITE, TurnMenu);
-    print_text("4. Continue with Game", &mut commands, g.fah(), Vec2::new(1.0, 2.0), WHITE, TurnMenu);
+    let options = [
+        "turn.examine_spells",
+        "turn.select_spell",
+        "turn.examine_board",
+        "turn.continue",
+        "turn.log",
+    ];
+    let mut items = Vec::new();
+    for (i, key) in options.iter().enumerate() {
+        let option = format!("{}. {}", i + 1, locale.text(key));
+        let pos = Vec2::new(1.0, 5.0 - i as f32);
+        print_text(&option, &mut commands, g.fah(), pos, WHITE, TurnMenu);
+        items.push(PadMenuItem::new(pos, option.chars().count(), option.chars().next().unwrap()));
+    }
+    pad_menu.set(GameState::TurnMenu, items);
 }
 
 fn turn_menu_keyboard(
     mut state: ResMut<NextState<GameState>>,
     mut keys: ResMut<Input<KeyCode>>,
+    mut log_view: ResMut<LogView>,
 ) {
     if keys.just_pressed(KeyCode::Key1) {
         keys.reset(KeyCode::Key4);
@@ -89,41 +123,114 @@ fn turn_menu_keyboard(
         keys.reset(KeyCode::Key4);
         state.set(GameState::TurnMenuTransition);
     }
+    if keys.just_pressed(KeyCode::Key5) {
+        keys.reset(KeyCode::Key5);
+        log_view.open(GameState::TurnMenu, &mut state);
+    }
+}
+
+// Computer wizards pick their spell straight from the menu, and never cast illusions
+fn turn_menu_computer(
+    mut state: ResMut<NextState<GameState>>,
+    mut g: ResMut<Game>,
+    mut rng: ResMut<GameRng>,
+    time: Res<Time>,
+    mut wait: Local<Option<Timer>>,
+) {
+    if !ai::thought(&mut wait, &time) {
+        return;
+    }
+    let level = g.get_player().ai_level;
+    let spells = &mut g.get_player_mut().spells;
+    spells.chosen_spell = ai::choose_spell(&spells.spells, level, &mut rng.0);
+    spells.illusion = false;
+    state.set(GameState::TurnMenuTransition);
+}
+
+// Out of time before choosing, so no spell this round
+fn turn_menu_time_up(
+    mut ev_time_up: EventReader<TimeUp>,
+    mut state: ResMut<NextState<GameState>>,
+    mut g: ResMut<Game>,
+    mut pick_illusion: ResMut<PickIllusion>,
+) {
+    if ev_time_up.iter().last().is_none() {
+        return;
+    }
+    let spells = &mut g.get_player_mut().spells;
+    spells.chosen_spell = None;
+    spells.illusion = false;
+    pick_illusion.0 = false;
+    state.set(GameState::TurnMenuTransition);
 }
 
 fn turn_menu_transition(
     mut state: ResMut<NextState<GameState>>,
     mut g: ResMut<Game>,
     mut cursor: ResMut<Cursor>,
+    settings: Res<Settings>,
+    mut pass: ResMut<PassTurn>,
 ) {
-    g.player_turn += 1;
+    g.next_player_turn();
     if g.player_turn >= g.players {
-        g.player_turn = 0;
+        g.first_player_turn();
         cursor.set_visible();
         state.set(GameState::CastSpellSetup);
     } else {
-        state.set(GameState::TurnMenu);
+        pass.to(GameState::TurnMenu, &g, &settings, &mut state);
     }
 }
 
+// Spells in two columns, each with a letter and coloured by its chance of being cast
+pub fn print_spell_list(
+    spells: &[SpellBox],
+    commands: &mut Commands,
+    fah: Handle<TextureAtlas>,
+    locale: &Locale,
+    settings: &Settings,
+    screen: impl Component + std::marker::Copy,
+) -> Vec<PadMenuItem> {
+    let mut items = Vec::new();
+    for (i, spell) in (0_u8..).zip(spells.iter()) {
+        let x = if 1 == i % 2 { 7.0 } else { 0.5 };
+        let key = (i+65) as char;
+        let mut name_str = key.to_string();
+        name_str.push_str(spell.get_sep());
+        name_str.push_str(locale.name(&spell.name()));
+        let pos = Vec2::new(x, 8.0-f32::from(i/2));
+        let color = chance_color(settings, spell.casting_chance_band());
+        print_text(&name_str, commands, fah.clone(), pos, color, screen);
+        if settings.chance_digits {
+            // A half size digit just left of the key
+            let chance = spell.casting_chance();
+            let mark = if chance >= 100 { '+' } else { char::from_digit(u32::from(chance / 10), 10).unwrap() };
+            let mut marker = get_sprite_sheet_bundle(fah.clone(), pos - Vec2::new(0.375, 0.0), char_to_pos(mark), color);
+            marker.transform.scale *= Vec3::new(0.5, 0.5, 1.0);
+            commands.spawn(marker).insert(screen);
+        }
+        items.push(PadMenuItem::new(pos, name_str.chars().count(), key));
+    }
+    if settings.chance_digits {
+        print_text(locale.text("turn.chance_legend"), commands, fah, Vec2::new(0.5, 1.0), WHITE, screen);
+    }
+    items
+}
+
 fn turn_menu_choose_spell_setup(
     mut commands: Commands,
     g: Res<Game>,
+    locale: Res<Locale>,
+    settings: Res<Settings>,
     screen: impl Component + std::marker::Copy,
     mut ev_text: EventWriter<BottomTextEvent>,
+    mut pad_menu: ResMut<PadMenu>,
+    state: GameState,
 ) {
-    let mut n_player = (g.player_info.len()+1).to_string();
-    n_player.push_str("'s spells");
+    let n_player = locale.format("turn.spells", &[&(g.player_info.len()+1)]);
     print_text(&n_player, &mut commands, g.fah(), Vec2::new(0.5, 9.0), WHITE, screen);
-    let player = g.get_player();
-    for (i, spell) in (0_u8..).zip(player.spells.spells.iter()) {
-        let x = if 1 == i % 2 { 7.0 } else { 0.5 };
-        let mut name_str = ((i+65) as char).to_string();
-        name_str.push_str(spell.get_sep());
-        name_str.push_str(&spell.name());
-        print_text(&name_str, &mut commands, g.fah(), Vec2::new(x, 8.0-f32::from(i/2)), spell.casting_chance_color(), screen);
-    }
-    ev_text.send(BottomTextEvent::from("      Press 0 to exit"));
+    let items = print_spell_list(&g.get_player().spells.spells, &mut commands, g.fah(), &locale, &settings, screen);
+    pad_menu.set(state, items);
+    ev_text.send(BottomTextEvent::prompt(&centred(&locale.format("prompt.exit", &[&action_keys(&settings, Action::Back)]))));
 }
 
 #[derive(Component, Clone, Copy)]
@@ -132,21 +239,24 @@ struct ExamineSpellScreen;
 fn turn_menu_examine_spell_setup(
     commands: Commands,
     g: Res<Game>,
+    locale: Res<Locale>,
+    settings: Res<Settings>,
     ev_text: EventWriter<BottomTextEvent>,
+    pad_menu: ResMut<PadMenu>,
 ) {
-    turn_menu_choose_spell_setup(commands, g, ExamineSpellScreen, ev_text);
+    turn_menu_choose_spell_setup(commands, g, locale, settings, ExamineSpellScreen, ev_text, pad_menu, GameState::TurnMenuExamineSpell);
 }
 
 fn turn_menu_choose_spell_keyboard(
     mut state: ResMut<NextState<GameState>>,
-    mut keys: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Input<Action>>,
     mut char_evr: ResMut<Events<ReceivedCharacter>>,
     g: Res<Game>,
     mut ev_choose_spell: EventWriter<TurnMenuEvent>,
 ) {
     let player = g.get_player();
-    if keys.just_pressed(KeyCode::Key0) {
-        keys.reset(KeyCode::Key0);
+    if actions.just_pressed(Action::Back) || actions.just_pressed(Action::Cancel) {
+        actions.reset_all();
         state.set(GameState::TurnMenu);
     }
     for ev in char_evr.drain() {
@@ -171,7 +281,7 @@ fn turn_menu_examine_spell_keyboard(
     mut ev_choose_spell: EventReader<TurnMenuEvent>,
 ) {
     for _ in ev_choose_spell.iter() {
-        print!("LEAVE examine spell, set state TurnMenuExamineOneSpell");
+        debug!("LEAVE examine spell, set state TurnMenuExamineOneSpell");
         state.set(GameState::TurnMenuExamineOneSpell);
     }
 }
@@ -182,6 +292,7 @@ struct ExamineOneSpellScreen;
 fn turn_menu_examine_one_spell_setup(
     mut commands: Commands,
     g: Res<Game>,
+    locale: Res<Locale>,
     mut ev_choose_spell: EventReader<TurnMenuEvent>,
     mut ev_text: EventWriter<BottomTextEvent>,
 ) {
@@ -189,12 +300,12 @@ fn turn_menu_examine_one_spell_setup(
         let spell_id = ev.0;
         // FIXME
         let spell = g.get_player().spells.get_spell(spell_id);
-        print_text(&spell.name(), &mut commands, g.fah(), Vec2::new(1.0, 9.0), WHITE, ExamineOneSpellScreen);
-        for (i, line) in spell.get_description().iter().enumerate() {
+        print_text(locale.name(&spell.name()), &mut commands, g.fah(), Vec2::new(1.0, 9.0), WHITE, ExamineOneSpellScreen);
+        for (i, line) in spell.get_description(&locale).iter().enumerate() {
             print_text(line, &mut commands, g.fah(), Vec2::new(1.0, 8.0-i as f32), WHITE, ExamineOneSpellScreen);
         }
     }
-    ev_text.send(BottomTextEvent::from("      Any key to exit"));
+    ev_text.send(BottomTextEvent::prompt(&centred(locale.text("prompt.any_key_exit"))));
 }
 
 fn turn_menu_examine_one_spell_keyboard(
@@ -212,42 +323,56 @@ struct SelectSpellScreen;
 fn turn_menu_select_spell_setup(
     commands: Commands,
     g: Res<Game>,
+    locale: Res<Locale>,
+    settings: Res<Settings>,
     ev_text: EventWriter<BottomTextEvent>,
+    pad_menu: ResMut<PadMenu>,
 ) {
-    turn_menu_choose_spell_setup(commands, g, SelectSpellScreen, ev_text);
+    turn_menu_choose_spell_setup(commands, g, locale, settings, SelectSpellScreen, ev_text, pad_menu, GameState::TurnMenuSelectSpell);
 }
 
-#[derive(Default)]
+// Waiting for an answer to whether the chosen spell should be an illusion
+#[derive(Default, Resource)]
 struct PickIllusion(bool);
 
 fn turn_menu_select_spell_keyboard(
     mut state: ResMut<NextState<GameState>>,
     mut ev_choose_spell: EventReader<TurnMenuEvent>,
     mut g: ResMut<Game>,
+    locale: Res<Locale>,
     mut ev_text: EventWriter<BottomTextEvent>,
-    mut pickillusion: Local<PickIllusion>,
-    mut keys: ResMut<Input<KeyCode>>,
+    mut pickillusion: ResMut<PickIllusion>,
+    mut actions: ResMut<Input<Action>>,
+    mut pad_menu: ResMut<PadMenu>,
 ) {
-    if (*pickillusion).0 {
-        if keys.just_pressed(KeyCode::Y) {
-            keys.reset(KeyCode::Y);
+    if pickillusion.0 {
+        if actions.just_pressed(Action::Yes) {
+            actions.reset(Action::Yes);
             g.get_player_mut().spells.illusion = true;
-            (*pickillusion).0 = false;
+            pickillusion.0 = false;
             state.set(GameState::TurnMenu);
         }
-        if keys.just_pressed(KeyCode::N) {
-            keys.reset(KeyCode::N);
+        if actions.just_pressed(Action::No) {
+            actions.reset(Action::No);
             g.get_player_mut().spells.illusion = false;
-            (*pickillusion).0 = false;
+            pickillusion.0 = false;
             state.set(GameState::TurnMenu);
         }
     } else {
         for ev in ev_choose_spell.iter() {
             g.get_player_mut().spells.set_chosen(ev.0);
-            let can_be_illusion = g.get_player_mut().spells.get_chosen_spell().unwrap().can_be_illusion();
+            let can_be_illusion = g.rules.illusions == Illusions::Allowed
+                && g.get_player_mut().spells.get_chosen_spell().unwrap().can_be_illusion();
             if can_be_illusion {
-                (*pickillusion).0 = true;
-                ev_text.send(BottomTextEvent::from("Illusion? (Y/N)"));
+                pickillusion.0 = true;
+                let question = locale.text("turn.illusion");
+                ev_text.send(BottomTextEvent::prompt(&format!("{question} (Y/N)")));
+                // Y and N as printed in the bottom text
+                let y = (question.chars().count() + 2) as f32 / 2.0;
+                pad_menu.set(GameState::TurnMenuSelectSpell, vec![
+                    PadMenuItem::new(Vec2::new(y, -1.5), 1, 'Y'),
+                    PadMenuItem::new(Vec2::new(y + 1.0, -1.5), 1, 'N'),
+                ]);
             } else {
                 state.set(GameState::TurnMenu);
             }
@@ -256,12 +381,14 @@ fn turn_menu_select_spell_keyboard(
 }
 
 fn turn_menu_examine_board_setup(
+    locale: Res<Locale>,
+    settings: Res<Settings>,
     mut ev_text: EventWriter<BottomTextEvent>,
     mut cursor: ResMut<Cursor>,
     g: Res<Game>,
     mut ev_cursor_pos: EventWriter<PositionCursorOnEntity>,
 ) {
-    ev_text.send(BottomTextEvent::from("      Press 0 to exit"));
+    ev_text.send(BottomTextEvent::prompt(&centred(&locale.format("prompt.exit", &[&action_keys(&settings, Action::Back)]))));
     cursor.set_type(CURSOR_BOX);
     cursor.set_visible();
     cursor.hide_till_moved();
@@ -271,10 +398,10 @@ fn turn_menu_examine_board_setup(
 
 fn turn_menu_examine_board_keyboard(
     mut state: ResMut<NextState<GameState>>,
-    mut keys: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Input<Action>>,
 ) {
-    if keys.just_pressed(KeyCode::Key0) {
-        keys.reset(KeyCode::Key0);
+    if actions.just_pressed(Action::Back) || actions.just_pressed(Action::Cancel) {
+        actions.reset_all();
         state.set(GameState::TurnMenu);
     }
 }
@@ -0,0 +1,134 @@
+use bevy::prelude::*;
+use bevy::render::camera::Viewport;
+use bevy::window::{PrimaryWindow, WindowMode, WindowResized};
+use crate::constants::*;
+use crate::settings::Settings;
+
+// How long to wait after the window stops being resized before saving its size
+const SAVE_DELAY: f32 = 0.5;
+
+pub struct ScreenSizePlugin;
+
+impl Plugin for ScreenSizePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_startup_system(restore_window)
+            .add_system(toggle_fullscreen)
+            .add_system(apply_fullscreen.run_if(resource_changed::<Settings>()))
+            .add_system(remember_window_size)
+            .add_system(fit_camera);
+    }
+}
+
+// The game screen in pixels, before scaling
+const GAME_SIZE: UVec2 = UVec2::new((SPRITE_SIZE * WIDTH) as u32, (SPRITE_SIZE * HEIGHT) as u32);
+
+// The largest whole number scale the game fits in the window at, and where it goes to be
+// in the middle with black bars around it
+fn fit(window: UVec2) -> Option<(u32, Viewport)> {
+    let scale = (window.x / GAME_SIZE.x).min(window.y / GAME_SIZE.y);
+    if scale == 0 {
+        return None;
+    }
+    let size = GAME_SIZE * scale;
+    Some((scale, Viewport {
+        physical_position: (window - size) / 2,
+        physical_size: size,
+        ..default()
+    }))
+}
+
+fn restore_window(
+    settings: Res<Settings>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let mut window = windows.single_mut();
+    window.resolution.set(settings.window_width, settings.window_height);
+    window.resize_constraints.min_width = GAME_SIZE.x as f32;
+    window.resize_constraints.min_height = GAME_SIZE.y as f32;
+}
+
+fn toggle_fullscreen(
+    keys: Res<Input<KeyCode>>,
+    mut settings: ResMut<Settings>,
+) {
+    if keys.just_pressed(KeyCode::F11) {
+        settings.fullscreen = !settings.fullscreen;
+        settings.save();
+    }
+}
+
+fn apply_fullscreen(
+    settings: Res<Settings>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let mut window = windows.single_mut();
+    let mode = if settings.fullscreen { WindowMode::BorderlessFullscreen } else { WindowMode::Windowed };
+    if window.mode != mode {
+        info!("Window mode {mode:?}");
+        window.mode = mode;
+    }
+}
+
+// Dragging the window edge sends a stream of resizes, only the last one is saved
+fn remember_window_size(
+    mut ev_resized: EventReader<WindowResized>,
+    mut settings: ResMut<Settings>,
+    mut pending: Local<Option<(Vec2, Timer)>>,
+    time: Res<Time>,
+) {
+    if let Some(resized) = ev_resized.iter().last() {
+        if !settings.fullscreen {
+            let size = Vec2::new(resized.width, resized.height);
+            *pending = Some((size, Timer::from_seconds(SAVE_DELAY, TimerMode::Once)));
+        }
+    }
+    let Some((size, timer)) = pending.as_mut() else {
+        return;
+    };
+    if timer.tick(time.delta()).finished() {
+        if Vec2::new(settings.window_width, settings.window_height) != *size {
+            settings.window_width = size.x;
+            settings.window_height = size.y;
+            settings.save();
+        }
+        *pending = None;
+    }
+}
+
+fn fit_camera(
+    windows: Query<&Window, (With<PrimaryWindow>, Changed<Window>)>,
+    mut camera_q: Query<(&mut Camera, &mut Transform)>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let physical = UVec2::new(window.physical_width(), window.physical_height());
+    let Some((scale, viewport)) = fit(physical) else {
+        return;
+    };
+    // The camera works in logical pixels, so the scale factor comes back out
+    let pixels_per_square = (scale as f32) * SPRITE_SIZE as f32 / window.scale_factor() as f32;
+    for (mut camera, mut transform) in &mut camera_q {
+        camera.viewport = Some(viewport.clone());
+        transform.scale = Vec3::new(1.0 / pixels_per_square, 1.0 / pixels_per_square, 1.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit() {
+        assert!(fit(UVec2::new(200, 1000)).is_none());
+        let (scale, viewport) = fit(GAME_SIZE * 4).unwrap();
+        assert_eq!(scale, 4);
+        assert_eq!(viewport.physical_position, UVec2::ZERO);
+        // Limited by the height, with black bars all round
+        let (scale, viewport) = fit(UVec2::new(1920, 1080)).unwrap();
+        assert_eq!(scale, 5);
+        assert_eq!(viewport.physical_size, UVec2::new(1280, 960));
+        assert_eq!(viewport.physical_position, UVec2::new(320, 60));
+    }
+}
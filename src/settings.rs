@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use crate::constants::{SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::locale::DEFAULT_LANGUAGE;
+use crate::input::{add_missing_bindings, default_bindings, Binding};
+use crate::palette::{default_custom_colors, Palette, WIZARD_COUNT};
+
+const SETTINGS_FILE: &str = "settings.ron";
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Settings::load());
+    }
+}
+
+// Player preferences which are kept between runs
+#[derive(Clone, Debug, Resource, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Settings {
+    #[serde(default = "default_bindings")]
+    pub key_bindings: Vec<Binding>,
+    // Out of MAX_VOLUME
+    #[serde(default = "default_volume")]
+    pub volume: u8,
+    #[serde(default)]
+    pub muted: bool,
+    #[serde(default = "default_language")]
+    pub language: String,
+    #[serde(default)]
+    pub fullscreen: bool,
+    // The window size last time it wasn't fullscreen, in logical pixels
+    #[serde(default = "default_window_width")]
+    pub window_width: f32,
+    #[serde(default = "default_window_height")]
+    pub window_height: f32,
+    #[serde(default)]
+    pub palette: Palette,
+    // Wizard colours for the custom palette, as red, green, blue
+    #[serde(default = "default_custom_colors")]
+    pub custom_colors: [[u8; 3]; WIZARD_COUNT],
+    // Show which player owns each piece without relying on colour
+    #[serde(default)]
+    pub owner_markers: bool,
+    // Each spell's casting chance as a digit, for telling the colour bands apart
+    #[serde(default)]
+    pub chance_digits: bool,
+    // A screen asking for the keyboard to be handed over before each person's turn
+    #[serde(default)]
+    pub pass_screen: bool,
+}
+
+pub const MAX_VOLUME: u8 = 10;
+
+const fn default_volume() -> u8 {
+    5
+}
+
+const fn default_window_width() -> f32 {
+    SCREEN_WIDTH
+}
+
+const fn default_window_height() -> f32 {
+    SCREEN_HEIGHT
+}
+
+fn default_language() -> String {
+    DEFAULT_LANGUAGE.to_string()
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            key_bindings: default_bindings(),
+            volume: default_volume(),
+            muted: false,
+            language: default_language(),
+            fullscreen: false,
+            window_width: default_window_width(),
+            window_height: default_window_height(),
+            palette: Palette::default(),
+            custom_colors: default_custom_colors(),
+            owner_markers: false,
+            chance_digits: false,
+            pass_screen: false,
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        let Ok(f) = File::open(SETTINGS_FILE) else {
+            return Self::default();
+        };
+        let mut settings: Self = ron::de::from_reader(f).unwrap_or_else(|e| {
+            warn!("Ignoring unreadable {SETTINGS_FILE}: {e}");
+            Self::default()
+        });
+        add_missing_bindings(&mut settings.key_bindings);
+        settings
+    }
+    pub fn save(&self) {
+        let res = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())
+            .and_then(|s| std::fs::write(SETTINGS_FILE, s).map_err(|e| e.to_string()));
+        if let Err(e) = res {
+            warn!("Could not save {SETTINGS_FILE}: {e}");
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use std::sync::Arc;
+use bevy::audio::{AddAudioSource, Decodable, Source};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::utils::{Duration, HashMap};
+use crate::board::{BoardKill, RangedAttack};
+use crate::cursor::CursorMovedEvent;
+use crate::display::StartExplosion;
+use crate::game::Game;
+use crate::player::CastSpellResult;
+use crate::settings::{Settings, MAX_VOLUME};
+
+const SAMPLE_RATE: u32 = 22050;
+// Square waves at full volume are painful
+const AMPLITUDE: f32 = 0.25;
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_audio_source::<Beep>()
+            .add_startup_system(make_sounds)
+            .add_systems((
+                cursor_sound,
+                cast_sound,
+                melee_sound,
+                ranged_sound,
+                death_sound,
+                turn_sound,
+            ));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Sound {
+    Cursor,
+    CastSuccess,
+    CastFail,
+    Summon,
+    Hit,
+    Shot,
+    Death,
+    TurnChange,
+}
+
+impl Sound {
+    const ALL: [Self; 8] = [
+        Self::Cursor,
+        Self::CastSuccess,
+        Self::CastFail,
+        Self::Summon,
+        Self::Hit,
+        Self::Shot,
+        Self::Death,
+        Self::TurnChange,
+    ];
+    // Everything is built from square wave tones and noise, like the Spectrum's beeper
+    fn samples(self) -> Vec<f32> {
+        match self {
+            Self::Cursor => sweep(1000.0, 1000.0, 0.015),
+            Self::CastSuccess => sweep(400.0, 1600.0, 0.3),
+            Self::CastFail => sweep(600.0, 150.0, 0.4),
+            Self::Summon => [523.0, 659.0, 784.0, 1047.0].iter().flat_map(|f| sweep(*f, *f, 0.06)).collect(),
+            Self::Hit => noise(0.12),
+            Self::Shot => sweep(2000.0, 800.0, 0.12),
+            Self::Death => noise(0.15).into_iter().chain(sweep(300.0, 50.0, 0.5)).collect(),
+            Self::TurnChange => sweep(880.0, 880.0, 0.08).into_iter().chain(sweep(660.0, 660.0, 0.08)).collect(),
+        }
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss, clippy::cast_precision_loss)]
+fn sample_count(secs: f32) -> usize {
+    (secs * SAMPLE_RATE as f32) as usize
+}
+
+// A square wave sliding from one frequency to another
+#[allow(clippy::cast_precision_loss)]
+fn sweep(from: f32, to: f32, secs: f32) -> Vec<f32> {
+    let n = sample_count(secs);
+    let mut phase = 0.0_f32;
+    (0..n).map(|i| {
+        let freq = (to - from).mul_add(i as f32 / n as f32, from);
+        phase = (phase + freq / SAMPLE_RATE as f32).fract();
+        if phase < 0.5 { AMPLITUDE } else { -AMPLITUDE }
+    }).collect()
+}
+
+fn noise(secs: f32) -> Vec<f32> {
+    // xorshift, so the same sound comes out every time
+    let mut x: u32 = 0x1234_5678;
+    (0..sample_count(secs)).map(|_| {
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        if x & 1 == 0 { AMPLITUDE } else { -AMPLITUDE }
+    }).collect()
+}
+
+// A sound generated at startup rather than loaded from a file
+#[derive(Clone, TypeUuid)]
+#[uuid = "9f1b6a2c-4d3e-4c8b-a1f0-6e2d5c7b8a94"]
+pub struct Beep {
+    samples: Arc<[f32]>,
+}
+
+pub struct BeepDecoder {
+    samples: Arc<[f32]>,
+    pos: usize,
+}
+
+impl Iterator for BeepDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.samples.get(self.pos).copied();
+        self.pos += 1;
+        sample
+    }
+}
+
+impl Source for BeepDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.samples.len().saturating_sub(self.pos))
+    }
+    fn channels(&self) -> u16 {
+        1
+    }
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f64(self.samples.len() as f64 / f64::from(SAMPLE_RATE)))
+    }
+}
+
+impl Decodable for Beep {
+    type DecoderItem = f32;
+    type Decoder = BeepDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        BeepDecoder { samples: self.samples.clone(), pos: 0 }
+    }
+}
+
+#[derive(Resource)]
+struct Sounds(HashMap<Sound, Handle<Beep>>);
+
+fn make_sounds(
+    mut commands: Commands,
+    mut beeps: ResMut<Assets<Beep>>,
+) {
+    let sounds = Sound::ALL.iter()
+        .map(|s| (*s, beeps.add(Beep { samples: s.samples().into() })))
+        .collect();
+    commands.insert_resource(Sounds(sounds));
+}
+
+fn play(sound: Sound, audio: &Audio<Beep>, sounds: &Sounds, settings: &Settings) {
+    if settings.muted || settings.volume == 0 {
+        return;
+    }
+    let volume = f32::from(settings.volume) / f32::from(MAX_VOLUME);
+    audio.play_with_settings(sounds.0[&sound].clone(), PlaybackSettings::ONCE.with_volume(volume));
+}
+
+fn cursor_sound(
+    mut ev: EventReader<CursorMovedEvent>,
+    audio: Res<Audio<Beep>>,
+    sounds: Res<Sounds>,
+    settings: Res<Settings>,
+) {
+    if ev.iter().count() > 0 {
+        play(Sound::Cursor, &audio, &sounds, &settings);
+    }
+}
+
+fn cast_sound(
+    mut ev: EventReader<CastSpellResult>,
+    audio: Res<Audio<Beep>>,
+    sounds: Res<Sounds>,
+    settings: Res<Settings>,
+) {
+    for res in ev.iter() {
+        let sound = match res {
+            Ok(Some(_)) => Sound::Summon,
+            Ok(None) => Sound::CastSuccess,
+            // Refused, the spell is still there to try elsewhere
+            Err(_) => Sound::CastFail,
+        };
+        play(sound, &audio, &sounds, &settings);
+    }
+}
+
+fn melee_sound(
+    mut ev: EventReader<StartExplosion>,
+    audio: Res<Audio<Beep>>,
+    sounds: Res<Sounds>,
+    settings: Res<Settings>,
+) {
+    for _e in ev.iter() {
+        play(Sound::Hit, &audio, &sounds, &settings);
+    }
+}
+
+fn ranged_sound(
+    mut ev: EventReader<RangedAttack>,
+    audio: Res<Audio<Beep>>,
+    sounds: Res<Sounds>,
+    settings: Res<Settings>,
+) {
+    for _e in ev.iter() {
+        play(Sound::Shot, &audio, &sounds, &settings);
+    }
+}
+
+fn death_sound(
+    mut ev: EventReader<BoardKill>,
+    audio: Res<Audio<Beep>>,
+    sounds: Res<Sounds>,
+    settings: Res<Settings>,
+) {
+    for _e in ev.iter() {
+        play(Sound::Death, &audio, &sounds, &settings);
+    }
+}
+
+// Whenever it becomes another player's go
+fn turn_sound(
+    g: Res<Game>,
+    mut last_turn: Local<Option<u8>>,
+    audio: Res<Audio<Beep>>,
+    sounds: Res<Sounds>,
+    settings: Res<Settings>,
+) {
+    if g.round == 0 || *last_turn == Some(g.player_turn) {
+        return;
+    }
+    *last_turn = Some(g.player_turn);
+    if g.player_turn < g.players {
+        play(Sound::TurnChange, &audio, &sounds, &settings);
+    }
+}
@@ -1,16 +1,49 @@
 use bevy::prelude::*;
 use crate::constants::{NEUTRAL, CHAOS, LAW};
-use crate::creature::load_creatures;
-use crate::display::{WHITE, GREEN, AQUA, YELLOW, PURPLE, RepeatAnimation};
-use crate::player::{PlayerSpell, Player};
-use crate::board::MoveableComponent;
+use crate::creature::{load_creatures, Creature};
+use crate::display::RepeatAnimation;
+use crate::locale::Locale;
+use crate::player::{CastFailed, PlayerSpell, Player};
+use crate::board::{GameBoard, MoveableComponent};
 use crate::system::RangedCombat;
+use crate::vec::Vec2I;
 
 #[derive(Resource, Deref)]
 pub struct AllSpells(Vec<SpellBox>);
 
 pub type SpellBox = Box<dyn ASpell + Sync + Send>;
 
+// What has to be on the square a spell is cast at
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SpellTarget {
+    #[default]
+    EmptySquare,
+    Creature,
+}
+
+// Range is measured as a straight line, rounded down
+pub fn in_cast_range(from: Vec2I, to: Vec2I, range: u8) -> bool {
+    let d = to - from;
+    let dist = Vec2::new(f32::from(d.x), f32::from(d.y)).length().floor();
+    dist <= f32::from(range)
+}
+
+// Whether the spell can be cast from the caster's square at the target square, going by which
+// squares are taken to have something on them
+pub fn check_target(spell: &dyn ASpell, from: Vec2I, to: Vec2I, occupied: impl Fn(Vec2I) -> bool) -> Result<(), CastFailed> {
+    if !in_cast_range(from, to, spell.cast_range()) {
+        return Err(CastFailed::OutOfRange);
+    }
+    if spell.needs_line_of_sight() && !GameBoard::line_clear(from, to, |pos| occupied(Vec2I::from(pos))) {
+        return Err(CastFailed::NoLineOfSight);
+    }
+    match spell.target() {
+        SpellTarget::EmptySquare if occupied(to) => Err(CastFailed::NotThere),
+        SpellTarget::Creature if !occupied(to) || from == to => Err(CastFailed::NoTarget),
+        _ => Ok(()),
+    }
+}
+
 pub trait ASpell {
     fn name(&self) -> String;
     fn clone(&self) -> SpellBox;
@@ -19,9 +52,19 @@ pub trait ASpell {
         false
     }
     fn cast_range(&self) -> u8;
+    fn target(&self) -> SpellTarget {
+        SpellTarget::EmptySquare
+    }
+    fn needs_line_of_sight(&self) -> bool {
+        true
+    }
     fn can_be_illusion(&self) -> bool {
         false
     }
+    // What a creature spell summons
+    fn creature(&self) -> Option<&Creature> {
+        None
+    }
     fn law_rating(&self) -> i8;
     fn get_sep(&self) -> &str {
         let law_rating = self.law_rating();
@@ -34,23 +77,17 @@ pub trait ASpell {
         LAW
     }
     fn casting_chance(&self) -> u8;
-    fn casting_chance_color(&self) -> Color {
-        let chance = self.casting_chance();
-        if chance >= 100 {
-            return WHITE;
-        }
-        if chance >= 80 {
-            return YELLOW;
-        }
-        if chance >= 60 {
-            return AQUA;
+    // 0 for always cast down to 4 for less than 40%, coloured by the palette
+    fn casting_chance_band(&self) -> usize {
+        match self.casting_chance() {
+            100.. => 0,
+            80..=99 => 1,
+            60..=79 => 2,
+            40..=59 => 3,
+            _ => 4,
         }
-        if chance >= 40 {
-            return GREEN;
-        }
-        PURPLE
     }
-    fn get_description(&self) -> Vec<String>;
+    fn get_description(&self, locale: &Locale) -> Vec<String>;
 }
 
 #[derive(Default, Clone)]
@@ -62,6 +99,7 @@ pub struct Spell {
     pub cast_range: u8,
     pub tries: u8,
     pub no_line_of_sight_needed: bool,
+    pub target: SpellTarget,
 }
 
 impl ASpell for Spell {
@@ -83,10 +121,16 @@ impl ASpell for Spell {
     fn cast_range(&self) -> u8 {
         self.cast_range
     }
+    fn target(&self) -> SpellTarget {
+        self.target
+    }
+    fn needs_line_of_sight(&self) -> bool {
+        !self.no_line_of_sight_needed
+    }
     fn casting_chance(&self) -> u8 {
         self.casting_chance
     }
-    fn get_description(&self) -> Vec<String> {
+    fn get_description(&self, _locale: &Locale) -> Vec<String> {
         Vec::new()
     }
 }
@@ -97,6 +141,9 @@ pub fn load_all_spells() -> AllSpells {
             name: "Disbelieve".to_string(),
             reusable: true,
             casting_chance: 100,
+            cast_range: 20,
+            no_line_of_sight_needed: true,
+            target: SpellTarget::Creature,
             ..Default::default()
         }),
         Box::new(Spell {
@@ -108,11 +155,13 @@ pub fn load_all_spells() -> AllSpells {
         }),
         Box::new(Spell {
             name: "Subversion".to_string(),
+            target: SpellTarget::Creature,
             cast_range: 7,
             ..Default::default()
         }),
         Box::new(Spell {
             name: "Vengence".to_string(),
+            target: SpellTarget::Creature,
             casting_chance: 80,
             cast_range: 20,
             no_line_of_sight_needed: true,
@@ -120,6 +169,7 @@ pub fn load_all_spells() -> AllSpells {
         }),
         Box::new(Spell {
             name: "Decree".to_string(),
+            target: SpellTarget::Creature,
             casting_chance: 80,
             cast_range: 20,
             law_rating: 1,
@@ -128,6 +178,7 @@ pub fn load_all_spells() -> AllSpells {
         }),
         Box::new(Spell {
             name: "Dark Power".to_string(),
+            target: SpellTarget::Creature,
             casting_chance: 50,
             cast_range: 20,
             law_rating: -2,
@@ -137,6 +188,7 @@ pub fn load_all_spells() -> AllSpells {
         }),
         Box::new(Spell {
             name: "Justice".to_string(),
+            target: SpellTarget::Creature,
             casting_chance: 50,
             cast_range: 20,
             law_rating: 2,
@@ -170,12 +222,14 @@ pub fn load_all_spells() -> AllSpells {
         }),
         Box::new(Spell {
             name: "Lightning".to_string(),
+            target: SpellTarget::Creature,
             casting_chance: 100,
             cast_range: 4,
             ..Default::default()
         }),
         Box::new(Spell {
             name: "Magic Bolt".to_string(),
+            target: SpellTarget::Creature,
             casting_chance: 100,
             cast_range: 6,
             ..Default::default()
@@ -230,8 +284,10 @@ pub fn load_all_spells() -> AllSpells {
         }),
         // FIXME - shadow form (80% makes movement 3, player flashes)
     ];
-    let creature_map = load_creatures();
-    for (_, c) in creature_map {
+    // In name order, so the same seed deals the same spells every time
+    let mut creatures: Vec<Creature> = load_creatures().into_values().collect();
+    creatures.sort_by(|a, b| a.name.cmp(&b.name));
+    for c in creatures {
         spells.push(c.to_spell());
     }
     AllSpells(spells)
@@ -0,0 +1,358 @@
+use bevy::ecs::schedule::ExecutorKind;
+use bevy::prelude::*;
+use bevy::time::TimePlugin;
+use bevy::utils::Duration;
+use bevy::window::ExitCondition;
+use std::fmt::Write as _;
+use std::panic::{self, AssertUnwindSafe};
+use crate::board::BoardPutEntity;
+use crate::game::{start_game, Game, GamePlugins, GameRng};
+use crate::gamestate::GameState;
+use crate::locale::Locale;
+use crate::messagelog::LogEvent;
+use crate::palette::wizard_colors;
+use crate::player::{deal_spells, get_start_positions, Player};
+use crate::rules::{Rules, CLASSIC};
+use crate::settings::{Settings, SettingsPlugin};
+use crate::spell::{load_all_spells, AllSpells};
+
+// Computer wizards playing each other with no window, for comparing ai_level values:
+//   mayhem-rs tournament --players 2,4 --levels 1,5,9 --games 500 --seed 7 --csv results.csv
+// Every game is played by the game's own plugins on MinimalPlugins, with time going on a quarter
+// of a second each update. Each game has its own seed, the base seed plus the game's number, so a
+// game that crashes can be played again on its own with --seed and --games 1.
+const USAGE: &str = "usage: mayhem-rs tournament [--players N,..] [--levels L,..] [--games N] [--seed N] [--max-rounds N] [--csv FILE]";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    pub players: Vec<usize>,
+    pub levels: Vec<u8>,
+    pub games: u32,
+    pub seed: u64,
+    pub max_rounds: u32,
+    pub csv: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            players: vec![2],
+            levels: vec![0, 5],
+            games: 100,
+            seed: 0,
+            max_rounds: 100,
+            csv: None,
+        }
+    }
+}
+
+pub fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value.trim().parse().map_err(|_| format!("bad number {value}"))
+}
+
+fn parse_list<T: std::str::FromStr>(value: &str) -> Result<Vec<T>, String> {
+    value.split(',').map(parse_number).collect()
+}
+
+impl Config {
+    // None if the first argument isn't "tournament", so the game starts as normal
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Option<Result<Self, String>> {
+        if args.next().as_deref() != Some("tournament") {
+            return None;
+        }
+        Some(Self::parse(args).map_err(|e| format!("{e}\n{USAGE}")))
+    }
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut config = Self::default();
+        while let Some(arg) = args.next() {
+            let value = args.next().ok_or_else(|| format!("{arg} needs a value"))?;
+            match arg.as_str() {
+                "--players" => config.players = parse_list(&value)?,
+                "--levels" => config.levels = parse_list(&value)?,
+                "--games" => config.games = parse_number(&value)?,
+                "--seed" => config.seed = parse_number(&value)?,
+                "--max-rounds" => config.max_rounds = parse_number(&value)?,
+                "--csv" => config.csv = Some(value),
+                _ => return Err(format!("unknown option {arg}")),
+            }
+        }
+        if let Some(n) = config.players.iter().find(|n| get_start_positions(**n).is_err()) {
+            return Err(format!("can't have {n} players"));
+        }
+        if config.levels.is_empty() || config.levels.iter().any(|l| *l > 9) {
+            return Err("levels go from 0 to 9".to_string());
+        }
+        Ok(config)
+    }
+}
+
+// Wins and rounds lasted for a seat, or an ai_level across every seat it played in
+#[derive(Clone, Debug, Default)]
+struct Record {
+    played: u32,
+    wins: u32,
+    survived: u32,
+    rounds: u32,
+}
+
+impl Record {
+    fn add(&mut self, won: bool, survived: bool, rounds: u32) {
+        self.played += 1;
+        self.wins += u32::from(won);
+        self.survived += u32::from(survived);
+        self.rounds += rounds;
+    }
+    fn rate(&self, n: u32) -> f32 {
+        if self.played == 0 { 0.0 } else { n as f32 / self.played as f32 }
+    }
+}
+
+// Results for one number of players
+#[derive(Debug, Default)]
+struct Table {
+    players: usize,
+    games: Record,
+    crashes: Vec<u64>,
+    seats: Vec<Record>,
+    levels: Vec<(u8, Record)>,
+}
+
+// How a game came out
+#[derive(Debug)]
+struct GameResult {
+    // Seat of the last wizard standing, None for a draw
+    winner: Option<usize>,
+    rounds: u32,
+    // Rounds each seat lasted, and whether it was still alive at the end
+    lasted: Vec<u32>,
+    alive: Vec<bool>,
+}
+
+// Updates a round can take before the game is counted as stuck
+const STUCK: u32 = 10_000;
+// Short enough that a computer wizard thinks for a few updates, by which time the board has
+// caught up with the last move whatever order the systems ran in
+const STEP: Duration = Duration::from_millis(250);
+
+// The wizards for the game being played, one to a seat
+#[derive(Resource)]
+struct Seats {
+    levels: Vec<u8>,
+    max_rounds: u32,
+}
+
+// Straight past the menu into the game, with the rules played as classic but for the round limit
+fn seat_wizards(
+    seats: Res<Seats>,
+    mut g: ResMut<Game>,
+    mut rng: ResMut<GameRng>,
+    allspells: Res<AllSpells>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    mut commands: Commands,
+    mut ev_board_put: EventWriter<BoardPutEntity>,
+    mut ev_log: EventWriter<LogEvent>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    g.rules = Rules { round_limit: Some(seats.max_rounds), ..CLASSIC };
+    let rules = g.rules;
+    let colors = wizard_colors(&settings);
+    for (seat, level) in seats.levels.iter().enumerate() {
+        let mut player = Player::new(format!("Seat {}", seat + 1), true, seat as u8 % 8 + 1, seat, colors[seat]);
+        player.ai_level = *level;
+        player.roll_stats(&rules, &mut rng.0);
+        player.spells.spells = deal_spells(&allspells, rules.spell_count, &mut rng.0);
+        g.player_info.push(player);
+    }
+    g.players = g.player_info.len() as u8;
+    start_game(&mut g, &mut commands, &mut ev_board_put, &mut ev_log, &locale);
+    state.set(GameState::TurnMenu);
+}
+
+// The game's clock, which moves on by STEP every update so nobody waits for the computer wizards
+fn tick(mut time: ResMut<Time>) {
+    let now = time.last_update().unwrap_or_else(|| time.startup()) + STEP;
+    time.update_with_instant(now);
+}
+
+// One game, with no window or sound. Systems run one at a time, so a seed always plays out the same.
+fn game_app(seed: u64, levels: &[u8], max_rounds: u32) -> App {
+    let mut app = App::new();
+    app
+        .add_plugins(MinimalPlugins.build().disable::<TimePlugin>())
+        .add_plugin(AssetPlugin::default())
+        .add_plugin(bevy::input::InputPlugin)
+        .add_plugin(WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+        })
+        .add_asset::<Image>()
+        .add_asset::<TextureAtlas>()
+        .init_resource::<Time>()
+        .add_system(tick.in_base_set(CoreSet::First))
+        // The player's own settings don't come into it
+        .insert_resource(Settings::default())
+        .insert_resource(load_all_spells())
+        .add_state::<GameState>()
+        .add_plugins(GamePlugins.build().disable::<SettingsPlugin>())
+        .insert_resource(GameRng::seeded(seed))
+        .insert_resource(Seats { levels: levels.to_vec(), max_rounds })
+        .add_system(seat_wizards.in_set(OnUpdate(GameState::InitialMenu)));
+    for (_, schedule) in app.world.resource_mut::<Schedules>().iter_mut() {
+        schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+    }
+    app
+}
+
+// None if the game got stuck
+fn play_game(seed: u64, levels: &[u8], max_rounds: u32) -> Option<GameResult> {
+    let mut app = game_app(seed, levels, max_rounds);
+    let (mut round, mut updates) = (0, 0);
+    while app.world.resource::<State<GameState>>().0 != GameState::GameOver {
+        app.update();
+        let now = app.world.resource::<Game>().round;
+        updates = if now == round { updates + 1 } else { 0 };
+        round = now;
+        if updates > STUCK {
+            return None;
+        }
+    }
+    let g = app.world.resource::<Game>();
+    let rounds = g.round.min(max_rounds);
+    Some(GameResult {
+        winner: if g.living_sides() == 1 { g.player_info.iter().position(|p| p.alive) } else { None },
+        rounds,
+        lasted: g.player_info.iter().map(|p| p.death.as_ref().map_or(rounds, |d| d.round)).collect(),
+        alive: g.player_info.iter().map(|p| p.alive).collect(),
+    })
+}
+
+struct Tournament {
+    config: Config,
+    tables: Vec<Table>,
+    game: u32,
+}
+
+impl Tournament {
+    fn new(config: Config) -> Self {
+        let tables = config.players.iter().map(|n| Table {
+            players: *n,
+            seats: vec![Record::default(); *n],
+            levels: config.levels.iter().map(|l| (*l, Record::default())).collect(),
+            ..default()
+        }).collect();
+        Self { config, tables, game: 0 }
+    }
+    // Seats take the levels in turn, starting somewhere different each game
+    fn seat_levels(&self, players: usize, seed: u64) -> Vec<u8> {
+        let levels = &self.config.levels;
+        (0..players).map(|seat| levels[(seat + seed as usize) % levels.len()]).collect()
+    }
+    fn play(&mut self, t: usize) {
+        let players = self.tables[t].players;
+        let seed = self.config.seed.wrapping_add(u64::from(self.game));
+        let levels = self.seat_levels(players, seed);
+        let max_rounds = self.config.max_rounds;
+        let result = panic::catch_unwind(AssertUnwindSafe(|| play_game(seed, &levels, max_rounds)));
+        let table = &mut self.tables[t];
+        let Ok(Some(result)) = result else {
+            eprintln!("Game with {players} players and seed {seed} crashed");
+            table.crashes.push(seed);
+            return;
+        };
+        table.games.add(result.winner.is_some(), false, result.rounds);
+        for (seat, level) in levels.iter().enumerate() {
+            let won = result.winner == Some(seat);
+            let alive = result.alive[seat];
+            table.seats[seat].add(won, alive, result.lasted[seat]);
+            if let Some((_, record)) = table.levels.iter_mut().find(|(l, _)| l == level) {
+                record.add(won, alive, result.lasted[seat]);
+            }
+        }
+    }
+    fn report(&self) -> String {
+        let mut text = String::new();
+        for table in &self.tables {
+            let games = &table.games;
+            let _ = writeln!(text, "{} players: {} games, {} drawn, {} crashed, {:.1} rounds on average",
+                table.players, games.played, games.played - games.wins, table.crashes.len(), games.rate(games.rounds));
+            for (level, record) in &table.levels {
+                let _ = writeln!(text, "  level {level}: won {:5.1}%, survived {:5.1}%",
+                    record.rate(record.wins) * 100.0, record.rate(record.survived) * 100.0);
+            }
+            let positions = get_start_positions(table.players).unwrap();
+            for (seat, (record, pos)) in table.seats.iter().zip(positions).enumerate() {
+                let _ = writeln!(text, "  seat {} at {},{}: won {:5.1}%, survived {:5.1}%, lasted {:.1} rounds",
+                    seat + 1, pos.x, pos.y, record.rate(record.wins) * 100.0, record.rate(record.survived) * 100.0, record.rate(record.rounds));
+            }
+        }
+        text
+    }
+    // The all rows count games rather than wizards, so their wins are the games someone won
+    fn csv(&self) -> String {
+        let mut text = "players,group,key,x,y,played,wins,win_rate,survival_rate,avg_rounds,crashes\n".to_string();
+        let mut row = |players: usize, group: &str, key: &str, pos: Option<Vec2>, r: &Record, crashes: usize| {
+            let (x, y) = pos.map_or((String::new(), String::new()), |p| (p.x.to_string(), p.y.to_string()));
+            let _ = writeln!(text, "{players},{group},{key},{x},{y},{},{},{:.4},{:.4},{:.2},{crashes}",
+                r.played, r.wins, r.rate(r.wins), r.rate(r.survived), r.rate(r.rounds));
+        };
+        for table in &self.tables {
+            row(table.players, "all", "", None, &table.games, table.crashes.len());
+            for (level, record) in &table.levels {
+                row(table.players, "level", &level.to_string(), None, record, 0);
+            }
+            let positions = get_start_positions(table.players).unwrap();
+            for (seat, (record, pos)) in table.seats.iter().zip(positions).enumerate() {
+                row(table.players, "seat", &(seat + 1).to_string(), Some(pos), record, 0);
+            }
+        }
+        text
+    }
+}
+
+pub fn run(config: Config) {
+    let mut tournament = Tournament::new(config);
+    for t in 0..tournament.tables.len() {
+        for _ in 0..tournament.config.games {
+            tournament.play(t);
+            tournament.game += 1;
+        }
+    }
+    print!("{}", tournament.report());
+    if let Some(path) = &tournament.config.csv {
+        if let Err(e) = std::fs::write(path, tournament.csv()) {
+            eprintln!("Could not write {path}: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> impl Iterator<Item = String> + '_ {
+        s.split_whitespace().map(String::from)
+    }
+
+    #[test]
+    fn test_config_from_args() {
+        assert!(Config::from_args(args("")).is_none());
+        let config = Config::from_args(args("tournament --players 2,3 --levels 1,9 --games 10 --seed 5 --csv out.csv")).unwrap().unwrap();
+        assert_eq!(config.players, vec![2, 3]);
+        assert_eq!(config.levels, vec![1, 9]);
+        assert_eq!(config.games, 10);
+        assert_eq!(config.seed, 5);
+        assert_eq!(config.csv.as_deref(), Some("out.csv"));
+        assert!(Config::from_args(args("tournament --players 9")).unwrap().is_err());
+        assert!(Config::from_args(args("tournament --games")).unwrap().is_err());
+    }
+
+    #[test]
+    fn test_play_game() {
+        let result = play_game(3, &[5, 5], 4).unwrap();
+        assert!(result.rounds <= 4);
+        assert_eq!(result.lasted.len(), 2);
+        assert_eq!(result.winner.is_some(), result.alive.iter().filter(|a| **a).count() == 1);
+    }
+}